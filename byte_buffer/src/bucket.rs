@@ -3,7 +3,6 @@
 use crate::utils::make_buffer;
 use std::ptr::{self, NonNull};
 use std::sync::atomic::{AtomicPtr, AtomicU16, Ordering};
-use std::u16;
 
 const CAPACITY: usize = 16;
 
@@ -4,6 +4,7 @@ use crate::channel::{Receiver, Sender};
 use crate::lock::{lock, unlock};
 use crate::utils::*;
 use std::io::ErrorKind;
+use std::ptr;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::vec;
 
@@ -11,7 +12,7 @@ const DEFAULT_GROWTH: usize = 4;
 const DEFAULT_CAPACITY: usize = 512;
 
 static mut BUFFER: Option<BufferPool> = None;
-static mut SIZE_CAP: AtomicUsize = AtomicUsize::new(512);
+static SIZE_CAP: AtomicUsize = AtomicUsize::new(512);
 
 struct Store {
     buf: Vec<u8>,
@@ -53,12 +54,12 @@ impl PoolManagement for BufferPool {
         slice_capacity: usize,
         worker_chan: Sender<WorkerOp>,
     ) {
-        unsafe {
-            if store.len() > SIZE_CAP.load(Ordering::SeqCst) {
-                SIZE_CAP.store(store.len(), Ordering::SeqCst);
-            }
+        if store.len() > SIZE_CAP.load(Ordering::SeqCst) {
+            SIZE_CAP.store(store.len(), Ordering::SeqCst);
+        }
 
-            BUFFER.replace(BufferPool {
+        unsafe {
+            (*ptr::addr_of_mut!(BUFFER)).replace(BufferPool {
                 store,
                 //                pool,
                 slice_capacity,
@@ -132,7 +133,7 @@ impl PoolManagement for BufferPool {
                     result = Some(buf.extend(count));
                 }
                 BufOp::ReleaseAndExtend(vec, dirty) => {
-                    if buf.store.len() < unsafe { SIZE_CAP.load(Ordering::SeqCst) } {
+                    if buf.store.len() < SIZE_CAP.load(Ordering::SeqCst) {
                         let id = buf.store.len();
 
                         buf.store.push(vec);
@@ -199,9 +200,7 @@ impl PoolManagement for BufferPool {
     }
 
     fn set_size_limit(limit: usize) {
-        unsafe {
-            SIZE_CAP.store(limit, Ordering::SeqCst);
-        }
+        SIZE_CAP.store(limit, Ordering::SeqCst);
     }
 }
 
@@ -249,12 +248,10 @@ impl PoolOps for BufferPool {
         //TODO: do not blow up the roof
 
         let capacity = self.slice_capacity;
-        let start = self.store.len();
-
         self.store.reserve(additional);
         //        self.pool.reserve(additional);
 
-        (0..additional).for_each(|offset| {
+        (0..additional).for_each(|_| {
             self.store.push(vec::from_elem(0, capacity));
             //            self.pool.push(start + offset);
         });
@@ -292,10 +289,10 @@ impl Drop for BufferPool {
 
 #[inline]
 fn buffer_ref() -> Option<&'static BufferPool> {
-    unsafe { BUFFER.as_ref() }
+    unsafe { (*ptr::addr_of!(BUFFER)).as_ref() }
 }
 
 #[inline]
 fn buffer_mut() -> Option<&'static mut BufferPool> {
-    unsafe { BUFFER.as_mut() }
+    unsafe { (*ptr::addr_of_mut!(BUFFER)).as_mut() }
 }
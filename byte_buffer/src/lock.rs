@@ -1,5 +1,6 @@
+use std::hint;
 use std::io::ErrorKind;
-use std::sync::atomic::{self, AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 const LOCK_TIMEOUT: usize = 64;
 static LOCK: AtomicBool = AtomicBool::new(false);
@@ -8,7 +9,10 @@ pub(crate) fn lock() -> Result<(), ErrorKind> {
     let mut count = 1;
 
     loop {
-        if let Ok(true) = LOCK.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed) {
+        if LOCK
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
             break;
         }
 
@@ -31,6 +35,6 @@ pub(crate) fn unlock() {
 #[inline(always)]
 pub(crate) fn cpu_relax(count: usize) {
     for _ in 0..(1 << count) {
-        atomic::spin_loop_hint()
+        hint::spin_loop()
     }
 }
@@ -3,6 +3,7 @@ extern crate syncpool;
 use std::collections::HashMap;
 use std::mem::MaybeUninit;
 use std::pin::Pin;
+use std::ptr;
 use std::sync::mpsc;
 use std::sync::mpsc::SyncSender;
 use std::thread;
@@ -32,12 +33,24 @@ unsafe fn pool_setup() -> (
     Pin<&'static mut SyncPool<ComplexStruct>>,
     Pin<&'static mut SyncPool<ComplexStruct>>,
 ) {
-    POOL.as_mut_ptr().write(SyncPool::with_size(COUNT / 2));
+    let pool = (*ptr::addr_of_mut!(POOL)).as_mut_ptr();
+    pool.write(SyncPool::with_size(COUNT / 2));
 
-    (
-        Pin::new(&mut *POOL.as_mut_ptr()),
-        Pin::new(&mut *POOL.as_mut_ptr()),
-    )
+    // wipe the returned structs, such that the next checkout starts from a clean slate
+    (*pool).reset_handle(sanitizer);
+
+    (Pin::new(&mut *pool), Pin::new(&mut *pool))
+}
+
+/// Clear the contents while keeping the allocated buffers around for the next checkout.
+fn sanitizer(content: &mut ComplexStruct) {
+    content.id = 0;
+    content.name.clear();
+    content.body.clear();
+    content.flags.clear();
+    content.children.clear();
+    content.index.clear();
+    content.rev_index.clear();
 }
 
 /// Main example body
@@ -3,6 +3,7 @@
 extern crate syncpool;
 
 use std::collections::HashMap;
+use std::ptr;
 use std::sync::mpsc;
 use std::thread;
 use std::time::{Duration, Instant};
@@ -62,7 +63,7 @@ fn main() {
     let trials = TRIAL_RUNS;
     let mut sum = 0;
 
-    println!("Init len: {}", unsafe { POOL.as_ref().unwrap().len() });
+    println!("Init len: {}", pool().len());
 
     for i in 0..trials {
         let res = if async_mode {
@@ -83,10 +84,10 @@ fn main() {
         sum += res;
 
         println!(">>> Trial: {}; Advance: {} us <<<", i, res);
-        //        println!("Remainder len: {}", unsafe { POOL.as_ref().unwrap().len() });
+        //        println!("Remainder len: {}", pool().len());
     }
 
-    println!("Remainder len: {}", unsafe { POOL.as_ref().unwrap().len() });
+    println!("Remainder len: {}", pool().len());
 
     println!(
         "\nAverage: {} ms\n",
@@ -114,10 +115,15 @@ fn pool_setup() {
         });
         */
 
-        POOL.replace(pool);
+        (*ptr::addr_of_mut!(POOL)).replace(pool);
     }
 }
 
+/// The shared pool, which must have been set up by `pool_setup`.
+fn pool() -> &'static mut SyncPool<TestStruct> {
+    unsafe { (*ptr::addr_of_mut!(POOL)).as_mut().unwrap() }
+}
+
 fn sanitizer(data: &mut TestStruct) {
     data.id = 21;
 }
@@ -130,14 +136,14 @@ fn run(alloc: bool) -> u128 {
 
     let send_one = thread::spawn(move || {
         for i in 0..TEST_SIZE {
-            if i % DENOMINATOR == 0 {
+            if i.is_multiple_of(DENOMINATOR) {
                 thread::sleep(Duration::from_nanos(BUSY_PERIOD));
             }
 
             let mut data = if alloc {
                 Default::default()
             } else {
-                unsafe { POOL.as_mut().unwrap().get() }
+                pool().get()
             };
 
             assert!(data.id == 21 || data.id == 0, "Wrong id: {}", data.id);
@@ -155,14 +161,14 @@ fn run(alloc: bool) -> u128 {
 
     let send_two = thread::spawn(move || {
         for i in 0..TEST_SIZE {
-            if i % DENOMINATOR == 0 {
+            if i.is_multiple_of(DENOMINATOR) {
                 thread::sleep(Duration::from_nanos(BUSY_PERIOD));
             }
 
             let mut data = if alloc {
                 Default::default()
             } else {
-                unsafe { POOL.as_mut().unwrap().get() }
+                pool().get()
             };
 
             assert!(data.id == 21 || data.id == 0, "Wrong id: {}", data.id);
@@ -185,9 +191,7 @@ fn run(alloc: bool) -> u128 {
             //            assert_eq!(arr.len(), BUF_CAP);
 
             if !alloc {
-                unsafe {
-                    POOL.as_mut().unwrap().put(arr);
-                }
+                pool().put(arr);
             }
         }
 
@@ -196,14 +200,14 @@ fn run(alloc: bool) -> u128 {
 
     for i in 0..TEST_SIZE {
         // sleep a bit to create some concurrent actions
-        if i % DENOMINATOR == 1 {
+        if !i.is_multiple_of(DENOMINATOR) {
             thread::sleep(Duration::from_nanos(BUSY_PERIOD));
         }
 
         let mut data = if alloc {
             Default::default()
         } else {
-            unsafe { POOL.as_mut().unwrap().get() }
+            pool().get()
         };
 
         //        assert!(data.id == 21 || data.id == 0, "Wrong id: {}", data.id);
@@ -215,7 +219,7 @@ fn run(alloc: bool) -> u128 {
 
         if !alloc {
             // when done using the object, make sure to put it back so the pool won't dry up
-            unsafe { POOL.as_mut().unwrap().put(data) };
+            pool().put(data);
         }
     }
 
@@ -0,0 +1,93 @@
+extern crate syncpool;
+
+use std::ptr;
+use std::thread;
+use std::time::Instant;
+use syncpool::prelude::*;
+
+const TRIAL_RUNS: usize = 32;
+const TEST_SIZE: usize = 16 * 1024;
+const BATCH: usize = 4;
+
+static mut POOL: Option<SyncPool<Buffer>> = None;
+
+/// The shared pool, which must have been set up by `pool_setup`.
+fn pool() -> &'static mut SyncPool<Buffer> {
+    unsafe { (*ptr::addr_of_mut!(POOL)).as_mut().unwrap() }
+}
+
+struct Buffer {
+    id: usize,
+    buf: [u8; 1024],
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Buffer {
+            id: 0,
+            buf: [0u8; 1024],
+        }
+    }
+}
+
+/// Compare the cursor policies: every thread keeps checking out a small batch of elements and then
+/// returning them, with different number of threads competing for the buckets.
+fn main() {
+    for &policy in [CursorPolicy::Sticky, CursorPolicy::Advancing].iter() {
+        for &threads in [1usize, 2, 4, 8].iter() {
+            let mut sum = 0;
+
+            for _ in 0..TRIAL_RUNS {
+                pool_setup(policy);
+                sum += run(threads);
+            }
+
+            println!(
+                "policy: {:?}, threads: {} -> average: {} us",
+                policy,
+                threads,
+                sum / (TRIAL_RUNS as u128)
+            );
+        }
+    }
+}
+
+fn pool_setup(policy: CursorPolicy) {
+    let mut pool = SyncPool::with_size(64);
+    pool.set_cursor_policy(policy);
+
+    unsafe {
+        (*ptr::addr_of_mut!(POOL)).replace(pool);
+    }
+}
+
+fn run(threads: usize) -> u128 {
+    let now = Instant::now();
+
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            thread::spawn(|| {
+                let mut batch = Vec::with_capacity(BATCH);
+
+                for i in 0..TEST_SIZE {
+                    let mut data = pool().get();
+                    data.id = i;
+                    data.buf[0] = 42;
+                    batch.push(data);
+
+                    if batch.len() == BATCH {
+                        for data in batch.drain(..) {
+                            pool().put(data);
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        worker.join().unwrap_or_default();
+    }
+
+    now.elapsed().as_micros()
+}
@@ -0,0 +1,113 @@
+extern crate syncpool;
+
+use std::ptr;
+use std::thread;
+use std::time::Instant;
+use syncpool::prelude::*;
+
+const TRIAL_RUNS: usize = 16;
+const TEST_SIZE: usize = 16 * 1024;
+const POOL_ELEMS: usize = 1024;
+const BUF_SIZE: usize = 16 * 1024;
+const BATCH: usize = 8;
+
+static mut POOL: Option<SyncPool<Buffer>> = None;
+
+/// The shared pool, which must have been set up by `pool_setup`.
+fn pool() -> &'static mut SyncPool<Buffer> {
+    unsafe { (*ptr::addr_of_mut!(POOL)).as_mut().unwrap() }
+}
+
+struct Buffer {
+    id: usize,
+    buf: Vec<u8>,
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Buffer {
+            id: 0,
+            buf: vec![0u8; BUF_SIZE],
+        }
+    }
+}
+
+/// Short-lived elements over a pool much larger than the cache (16 MiB of buffers): every thread
+/// checks out a small batch of buffers, writes all of them, and returns the batch. Without a
+/// hardware counter, the cache warmth is approximated by the rate of `get` handing back one of the
+/// buffers the same thread has returned in its last batch, along with the run time.
+fn main() {
+    for &order in [CheckoutOrder::RoundRobin, CheckoutOrder::Lifo].iter() {
+        for &threads in [1usize, 2, 4].iter() {
+            let mut sum = 0;
+            let mut warm = 0;
+
+            for _ in 0..TRIAL_RUNS {
+                pool_setup(order);
+
+                let (elapsed, hits) = run(threads);
+                sum += elapsed;
+                warm += hits;
+            }
+
+            println!(
+                "order: {:?}, threads: {} -> average: {} us, warm reuse rate: {:.3}",
+                order,
+                threads,
+                sum / (TRIAL_RUNS as u128),
+                warm as f64 / (TRIAL_RUNS * threads * TEST_SIZE) as f64
+            );
+        }
+    }
+}
+
+fn pool_setup(order: CheckoutOrder) {
+    let mut pool = SyncPool::with_size(POOL_ELEMS);
+    pool.set_order(order);
+
+    unsafe {
+        (*ptr::addr_of_mut!(POOL)).replace(pool);
+    }
+}
+
+fn run(threads: usize) -> (u128, usize) {
+    let now = Instant::now();
+
+    let workers: Vec<_> = (0..threads)
+        .map(|t| {
+            thread::spawn(move || {
+                let mut hits = 0;
+                let mut batch = Vec::with_capacity(BATCH);
+
+                for round in 0..TEST_SIZE / BATCH {
+                    // the tag of the buffers this thread has returned in the last round
+                    let last = t * TEST_SIZE + round;
+
+                    for _ in 0..BATCH {
+                        let mut data = pool().get();
+                        if data.id == last {
+                            hits += 1;
+                        }
+
+                        data.buf.iter_mut().for_each(|b| *b = round as u8);
+                        data.id = last + 1;
+                        batch.push(data);
+                    }
+
+                    for data in batch.drain(..) {
+                        pool().put(data);
+                    }
+                }
+
+                hits
+            })
+        })
+        .collect();
+
+    let hits = workers
+        .into_iter()
+        .map(|worker| worker.join().unwrap_or_default())
+        .sum();
+
+    (now.elapsed().as_micros(), hits)
+}
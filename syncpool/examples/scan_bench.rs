@@ -0,0 +1,102 @@
+use std::hint::black_box;
+use std::time::Instant;
+
+const TRIAL_RUNS: usize = 32;
+const TEST_SIZE: usize = 1024 * 1024;
+
+const GET_MASK: u16 = 0b1010_1010_1010_1010;
+const PUT_MASK: u16 = 0b1111_1111_1111_1111;
+const FULL_FLAG: u16 = 0b0101_0101_0101_0101;
+
+/// The bitmaps of a bucket, where each position is 2 bits: the element bit and the lock bit.
+const CASES: [(&str, u16, bool); 4] = [
+    ("get, first taken", 0b0101_0101_0101_0101, true),
+    ("get, only last taken", 0b0100_0000_0000_0000, true),
+    ("put, first free", 0b0000_0000_0000_0000, false),
+    ("put, only last free", 0b0001_0101_0101_0101, false),
+];
+
+/// The bucket scan is crate-private, so both versions are copied here: the branch-free scan of
+/// `utils::enter`, against the walk over the pairs it replaced. `utils_test::access_exhaustive`
+/// keeps the two in agreement on every bitmap.
+fn main() {
+    for &(name, src, get) in CASES.iter() {
+        let mut folded = 0;
+        let mut walked = 0;
+
+        for _ in 0..TRIAL_RUNS {
+            folded += run(src, get, fold_enter);
+            walked += run(src, get, walk_enter);
+        }
+
+        println!(
+            "{} -> folded scan: {} us, pair walk: {} us",
+            name,
+            folded / (TRIAL_RUNS as u128),
+            walked / (TRIAL_RUNS as u128)
+        );
+    }
+}
+
+fn run(src: u16, get: bool, enter: fn(u16, bool) -> Result<u16, ()>) -> u128 {
+    let now = Instant::now();
+
+    for _ in 0..TEST_SIZE {
+        let _ = black_box(enter(black_box(src), black_box(get)));
+    }
+
+    now.elapsed().as_micros()
+}
+
+fn fold_enter(src: u16, get: bool) -> Result<u16, ()> {
+    let base = if get { src ^ GET_MASK } else { src ^ PUT_MASK };
+
+    let wanted = base & (base >> 1) & FULL_FLAG;
+    if wanted == 0 {
+        return Err(());
+    }
+
+    Ok(wanted.trailing_zeros() as u16 / 2)
+}
+
+fn walk_enter(src: u16, get: bool) -> Result<u16, ()> {
+    let mut base = if get {
+        if src == 0 {
+            return Err(());
+        }
+
+        src ^ GET_MASK
+    } else {
+        if src == FULL_FLAG {
+            return Err(());
+        }
+
+        src ^ PUT_MASK
+    };
+
+    let mut pos: u16 = {
+        let val = (base & PUT_MASK).trailing_zeros() as u16;
+        if val > 14 {
+            return Err(());
+        }
+
+        if val % 2 == 1 {
+            base >>= val + 1;
+            val.div_ceil(2)
+        } else {
+            base >>= val;
+            val / 2
+        }
+    };
+
+    while base > 0 {
+        if base & 0b11 == 0b11 {
+            return Ok(pos);
+        }
+
+        pos += 1;
+        base >>= 2;
+    }
+
+    Err(())
+}
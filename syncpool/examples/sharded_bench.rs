@@ -0,0 +1,87 @@
+extern crate syncpool;
+
+use std::ptr;
+use std::thread;
+use std::time::Instant;
+use syncpool::prelude::*;
+use syncpool::ShardedPool;
+
+const TRIAL_RUNS: usize = 32;
+const TEST_SIZE: usize = 64 * 1024;
+const PER_SHARD: usize = 32;
+
+static mut SINGLE: Option<SyncPool<Buffer>> = None;
+static mut SHARDED: Option<ShardedPool<Buffer>> = None;
+
+struct Buffer {
+    id: usize,
+    buf: [u8; 1024],
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Buffer {
+            id: 0,
+            buf: [0u8; 1024],
+        }
+    }
+}
+
+/// Every thread keeps checking out and returning an element, either from a single pool holding
+/// all the elements, or from its own shard of a sharded pool with the same total capacity.
+fn main() {
+    for &threads in [1usize, 2, 4, 8].iter() {
+        let mut single = 0;
+        let mut sharded = 0;
+
+        for _ in 0..TRIAL_RUNS {
+            unsafe {
+                (*ptr::addr_of_mut!(SINGLE)).replace(SyncPool::with_size(threads * PER_SHARD));
+                (*ptr::addr_of_mut!(SHARDED)).replace(ShardedPool::new(threads, PER_SHARD));
+            }
+
+            single += run(threads, false);
+            sharded += run(threads, true);
+        }
+
+        println!(
+            "threads: {} -> single pool: {} us, sharded pool: {} us",
+            threads,
+            single / (TRIAL_RUNS as u128),
+            sharded / (TRIAL_RUNS as u128)
+        );
+    }
+}
+
+fn run(threads: usize, sharded: bool) -> u128 {
+    let now = Instant::now();
+
+    let workers: Vec<_> = (0..threads)
+        .map(|shard| {
+            thread::spawn(move || {
+                for i in 0..TEST_SIZE {
+                    if sharded {
+                        // each thread owns a shard, so the shards are never shared
+                        let pool = unsafe { (*ptr::addr_of_mut!(SHARDED)).as_mut().unwrap() };
+                        let mut data = pool.get_at(shard);
+                        data.id = i;
+                        data.buf[0] = 42;
+                        pool.put_at(shard, data);
+                    } else {
+                        let pool = unsafe { (*ptr::addr_of_mut!(SINGLE)).as_mut().unwrap() };
+                        let mut data = pool.get();
+                        data.id = i;
+                        data.buf[0] = 42;
+                        pool.put(data);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        worker.join().unwrap_or_default();
+    }
+
+    now.elapsed().as_micros()
+}
@@ -0,0 +1,118 @@
+extern crate syncpool;
+
+use std::ptr;
+use std::thread;
+use std::time::Instant;
+use syncpool::prelude::*;
+
+const TRIAL_RUNS: usize = 32;
+const TEST_SIZE: usize = 16 * 1024;
+const POOL_ELEMS: usize = 512;
+const IDLE_ELEMS: usize = 16;
+const BATCH: usize = 4;
+
+static mut POOL: Option<SyncPool<Buffer>> = None;
+
+/// The shared pool, which must have been set up by `pool_setup`.
+fn pool() -> &'static mut SyncPool<Buffer> {
+    unsafe { (*ptr::addr_of_mut!(POOL)).as_mut().unwrap() }
+}
+
+struct Buffer {
+    id: usize,
+    buf: [u8; 1024],
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Buffer {
+            id: 0,
+            buf: [0u8; 1024],
+        }
+    }
+}
+
+/// A skewed workload for the `get` scan of each cursor policy: a large pool is mostly checked out,
+/// such that the returns cluster in a few buckets, and every thread keeps checking out a small batch
+/// of elements and then returning them. Run with `--features bucket-stats` to also print the average
+/// number of denied bucket accesses per `get`.
+fn main() {
+    for &policy in [CursorPolicy::Sticky, CursorPolicy::Advancing].iter() {
+        for &threads in [1usize, 2, 4].iter() {
+            let mut sum = 0;
+            let mut denied = 0;
+
+            for _ in 0..TRIAL_RUNS {
+                pool_setup(policy);
+                sum += run(threads);
+                denied += denied_probes();
+            }
+
+            println!(
+                "policy: {:?}, threads: {} -> average: {} us, denied probes per get: {:.3}",
+                policy,
+                threads,
+                sum / (TRIAL_RUNS as u128),
+                denied as f64 / (TRIAL_RUNS * threads * TEST_SIZE) as f64
+            );
+        }
+    }
+}
+
+fn pool_setup(policy: CursorPolicy) {
+    let mut pool = SyncPool::with_size(POOL_ELEMS);
+    pool.set_cursor_policy(policy);
+
+    // check out the pool, then return a few elements, which will cluster in the first buckets; the
+    // rest are dropped without being returned, so they stay checked out as far as the pool knows
+    let mut held: Vec<_> = (0..POOL_ELEMS).map(|_| pool.get()).collect();
+    for data in held.drain(POOL_ELEMS - IDLE_ELEMS..) {
+        pool.put(data);
+    }
+
+    unsafe {
+        (*ptr::addr_of_mut!(POOL)).replace(pool);
+    }
+}
+
+fn run(threads: usize) -> u128 {
+    let now = Instant::now();
+
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            thread::spawn(|| {
+                let mut batch = Vec::with_capacity(BATCH);
+
+                for i in 0..TEST_SIZE {
+                    let mut data = pool().get();
+                    data.id = i;
+                    data.buf[0] = 42;
+                    batch.push(data);
+
+                    if batch.len() == BATCH {
+                        for data in batch.drain(..) {
+                            pool().put(data);
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        worker.join().unwrap_or_default();
+    }
+
+    now.elapsed().as_micros()
+}
+
+#[cfg(feature = "bucket-stats")]
+fn denied_probes() -> usize {
+    let pool = pool();
+    pool.hot_buckets().iter().map(|(_, denied)| denied).sum()
+}
+
+#[cfg(not(feature = "bucket-stats"))]
+fn denied_probes() -> usize {
+    0
+}
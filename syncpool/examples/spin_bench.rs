@@ -0,0 +1,105 @@
+extern crate syncpool;
+
+use std::ptr;
+use std::thread;
+use std::time::Instant;
+use syncpool::prelude::*;
+
+const TRIAL_RUNS: usize = 16;
+const TEST_SIZE: usize = 1024;
+const WORKERS: usize = 4;
+
+/// The spin settings to compare, the first one being the default configuration.
+const CONFIGS: [SpinConfig; 5] = [
+    SpinConfig { offset: 8, cap: 4 },
+    SpinConfig { offset: 2, cap: 0 },
+    SpinConfig { offset: 4, cap: 2 },
+    SpinConfig { offset: 6, cap: 6 },
+    SpinConfig { offset: 10, cap: 6 },
+];
+
+static mut POOL: Option<SyncPool<Buffer>> = None;
+
+/// The shared pool, which must have been set up by `pool_setup`.
+fn pool() -> &'static mut SyncPool<Buffer> {
+    unsafe { (*ptr::addr_of_mut!(POOL)).as_mut().unwrap() }
+}
+
+struct Buffer {
+    id: usize,
+    buf: [u8; 1024],
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Buffer {
+            id: 0,
+            buf: [0u8; 1024],
+        }
+    }
+}
+
+fn main() {
+    for config in CONFIGS.iter() {
+        let mut sum = 0;
+
+        for _ in 0..TRIAL_RUNS {
+            pool_setup(*config);
+            sum += run();
+        }
+
+        println!(
+            "offset: {:>2}, cap: {:>2} -> average: {} us",
+            config.offset,
+            config.cap,
+            sum / (TRIAL_RUNS as u128)
+        );
+    }
+}
+
+fn pool_setup(config: SpinConfig) {
+    let mut pool = SyncPool::with_size(64);
+    pool.spin_config(config);
+
+    unsafe {
+        (*ptr::addr_of_mut!(POOL)).replace(pool);
+    }
+}
+
+fn sanitizer(data: &mut Buffer) {
+    data.id = 0;
+    data.buf[0] = 0;
+}
+
+fn run() -> u128 {
+    let now = Instant::now();
+
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|_| {
+            thread::spawn(|| {
+                for i in 0..TEST_SIZE {
+                    let mut data = pool().get();
+                    data.id = i;
+                    data.buf[0] = 42;
+
+                    pool().put(data);
+                }
+            })
+        })
+        .collect();
+
+    // keep raising the write barrier, such that the `put` calls will have to busy-wait
+    let blocker = thread::spawn(|| {
+        for _ in 0..TEST_SIZE / 4 {
+            pool().reset_handle(sanitizer);
+        }
+    });
+
+    for worker in workers {
+        worker.join().unwrap_or_default();
+    }
+
+    blocker.join().unwrap_or_default();
+
+    now.elapsed().as_micros()
+}
@@ -0,0 +1,191 @@
+//! This module contains the pool of aligned byte buffers, e.g. for SIMD or DMA, where the buffers
+//! must be aligned to a boundary (say 64 bytes) larger than the alignment of `u8`. The buffers are
+//! allocated once with the requested alignment and never reallocated, so the alignment is preserved
+//! across the reuse. Returned buffers are cleared (i.e. zeroed) before going back to the pool.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::AlignedBytesPool;
+//!
+//! let mut pool = AlignedBytesPool::new(4096, 16, 64);
+//!
+//! let mut buf = pool.get();
+//! assert_eq!(buf.len(), 4096);
+//! assert_eq!(buf.as_ptr() as usize % pool.align(), 0);
+//!
+//! buf[0] = 42;
+//! pool.put(buf);
+//!
+//! assert_eq!(pool.get()[0], 0);
+//! ```
+
+use crate::pool::{PoolManager, SyncPool};
+use std::alloc::{alloc_zeroed, dealloc, handle_alloc_error, Layout};
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+use std::slice;
+
+/// A zero-initialized, heap-allocated byte buffer with a custom alignment.
+pub struct AlignedBytes {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl AlignedBytes {
+    /// Allocate a zeroed buffer of `size` bytes, aligned to `align` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two, or if the size overflows when rounded up to the
+    /// alignment.
+    pub fn new(size: usize, align: usize) -> Self {
+        let layout = Layout::from_size_align(size, align).expect("invalid buffer alignment");
+
+        let ptr = if size == 0 {
+            // no allocation for an empty buffer, but the pointer shall still be aligned
+            NonNull::new(align as *mut u8).expect("the alignment is never 0")
+        } else {
+            // the layout is not zero-sized, so it's safe to allocate with it
+            NonNull::new(unsafe { alloc_zeroed(layout) })
+                .unwrap_or_else(|| handle_alloc_error(layout))
+        };
+
+        AlignedBytes { ptr, layout }
+    }
+
+    /// The alignment of the buffer in bytes.
+    pub fn align(&self) -> usize {
+        self.layout.align()
+    }
+
+    fn clear(buf: &mut AlignedBytes) {
+        buf.fill(0);
+    }
+}
+
+impl Default for AlignedBytes {
+    fn default() -> Self {
+        AlignedBytes::new(0, 1)
+    }
+}
+
+impl Deref for AlignedBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // the pointer is valid for `size` initialized bytes since it's allocated zeroed
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.layout.size()) }
+    }
+}
+
+impl DerefMut for AlignedBytes {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.layout.size()) }
+    }
+}
+
+impl Drop for AlignedBytes {
+    fn drop(&mut self) {
+        if self.layout.size() > 0 {
+            unsafe { dealloc(self.ptr.as_ptr(), self.layout) };
+        }
+    }
+}
+
+// the buffer exclusively owns its memory, same as a `Box<[u8]>`
+unsafe impl Send for AlignedBytes {}
+unsafe impl Sync for AlignedBytes {}
+
+/// A `SyncPool` of `AlignedBytes` buffers, which all have the same size and alignment.
+pub struct AlignedBytesPool {
+    pool: SyncPool<AlignedBytes>,
+    size: usize,
+    align: usize,
+}
+
+impl AlignedBytesPool {
+    /// Create a pool of `capacity` buffers (rounded the same way as `SyncPool::with_size`), each of
+    /// `size` bytes and aligned to `align` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two.
+    pub fn new(size: usize, capacity: usize, align: usize) -> Self {
+        let mut pool = SyncPool::with_size(capacity);
+
+        pool.with_exclusive(|buckets| {
+            for buf in buckets.iter_mut().flat_map(|bucket| bucket.iter_mut()) {
+                *buf = AlignedBytes::new(size, align);
+            }
+        });
+
+        pool.reset_handle(AlignedBytes::clear);
+
+        AlignedBytesPool { pool, size, align }
+    }
+
+    /// The alignment of every buffer from this pool in bytes.
+    pub fn align(&self) -> usize {
+        self.align
+    }
+
+    /// The size of every buffer from this pool in bytes.
+    pub fn buffer_size(&self) -> usize {
+        self.size
+    }
+
+    /// Obtain a zeroed buffer from the pool, or allocate a new one if the pool can't offer one.
+    pub fn get(&mut self) -> Box<AlignedBytes> {
+        self.pool
+            .try_get()
+            .unwrap_or_else(|| Box::new(AlignedBytes::new(self.size, self.align)))
+    }
+
+    /// Clear the buffer and return it to the pool. Same as `SyncPool::put`, return `None` if the
+    /// buffer has been placed in the pool; otherwise, i.e. if the pool is full, or if the buffer's
+    /// size or alignment doesn't match the pool's, the buffer is handed back to the caller.
+    pub fn put(&mut self, buf: Box<AlignedBytes>) -> Option<Box<AlignedBytes>> {
+        if buf.len() != self.size || buf.align() != self.align {
+            return Some(buf);
+        }
+
+        self.pool.put(buf)
+    }
+
+    /// The underlying pool, e.g. to query its `PoolState`.
+    pub fn pool(&self) -> &SyncPool<AlignedBytes> {
+        &self.pool
+    }
+}
+
+#[cfg(test)]
+mod aligned_tests {
+    use super::*;
+    use crate::PoolState;
+
+    #[test]
+    fn alignment_across_reuse() {
+        let mut pool = AlignedBytesPool::new(100, 8, 64);
+        assert_eq!(pool.pool().len(), 8);
+
+        let held: Vec<_> = (0..10).map(|_| pool.get()).collect();
+        for buf in held.iter() {
+            assert_eq!(buf.len(), 100);
+            assert_eq!(buf.as_ptr() as usize % 64, 0);
+        }
+
+        for mut buf in held {
+            buf[99] = 1;
+            pool.put(buf);
+        }
+
+        let buf = pool.get();
+        assert_eq!(buf.as_ptr() as usize % 64, 0);
+        assert!(buf.iter().all(|&b| b == 0));
+
+        // buffers of other shapes are rejected
+        assert!(pool.put(Box::new(AlignedBytes::new(100, 32))).is_some());
+        assert!(AlignedBytes::default().is_empty());
+    }
+}
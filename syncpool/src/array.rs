@@ -0,0 +1,207 @@
+//! This module contains the fixed-capacity pool for the small pools whose size is known at compile
+//! time, e.g. in embedded or latency-critical code. An `ArrayPool` keeps its buckets in an inline
+//! array instead of the `Vec` of a `SyncPool`, so there's no heap indirection to reach a bucket, and
+//! no allocation for the bucket storage beyond the pool itself. The elements are still boxed.
+//!
+//! The capacity is set by the number of buckets `B`, i.e. the pool holds `B * SLOT_CAP` elements;
+//! e.g. `ArrayPool<T, 4>` holds 32 elements. Since the buckets can't be reallocated, the pool can't
+//! be expanded, and it needs no write barrier: `get` and `put` go straight to the buckets, by the
+//! same scans `SyncPool` uses once it's past the barrier.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::{ArrayPool, SLOT_CAP};
+//!
+//! let mut pool: ArrayPool<[u8; 32], 2> = ArrayPool::new();
+//! assert_eq!(pool.capacity(), 2 * SLOT_CAP);
+//!
+//! let buf = pool.get();
+//! assert_eq!(pool.len(), 2 * SLOT_CAP - 1);
+//!
+//! pool.put(buf);
+//! assert_eq!(pool.len(), 2 * SLOT_CAP);
+//! ```
+
+use crate::bucket::{Bucket2, SLOT_CAP};
+use crate::pool::{ElemBuilder, ObjectPool, Scan};
+use crate::utils::make_elem;
+use std::array;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A pool of `B` buckets, i.e. `B * SLOT_CAP` elements, see the module documentation.
+pub struct ArrayPool<T, const B: usize> {
+    /// the buckets, all of them filled when the pool is created
+    slots: [Bucket2<T>; B],
+
+    /// the next bucket to try
+    curr: AtomicUsize,
+
+    /// the handle to be invoked before putting the element back
+    reset_handle: Option<fn(&mut T)>,
+
+    /// the builder creating the elements when the pool can't offer one
+    builder: ElemBuilder<T>,
+
+    /// the number of checkouts the pool couldn't serve, see `miss_count`
+    misses: AtomicUsize,
+
+    /// the number of elements created by the builder, see `fallback_count`
+    fallbacks: AtomicUsize,
+}
+
+impl<T: Default, const B: usize> ArrayPool<T, B> {
+    /// Create the pool with all of its `B * SLOT_CAP` elements created by `Default`.
+    pub fn new() -> Self {
+        Self::make_pool(ElemBuilder::Default(Default::default))
+    }
+}
+
+impl<T: Default, const B: usize> Default for ArrayPool<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const B: usize> ArrayPool<T, B> {
+    /// Create the pool with all of its `B * SLOT_CAP` elements created by `builder`, same as
+    /// `SyncPool::with_builder`.
+    pub fn with_builder(builder: fn() -> T) -> Self {
+        Self::make_pool(ElemBuilder::Builder(builder))
+    }
+
+    fn make_pool(builder: ElemBuilder<T>) -> Self {
+        assert!(B > 0, "ArrayPool needs at least 1 bucket");
+
+        ArrayPool {
+            slots: array::from_fn(|_| Bucket2::new(Some(&builder))),
+            curr: AtomicUsize::new(0),
+            reset_handle: None,
+            builder,
+            misses: AtomicUsize::new(0),
+            fallbacks: AtomicUsize::new(0),
+        }
+    }
+
+    /// Set or update the reset handle, same as `PoolManager::reset_handle`.
+    pub fn reset_handle(&mut self, handle: fn(&mut T)) -> &mut Self {
+        self.reset_handle.replace(handle);
+        self
+    }
+
+    /// Check out an element from the pool, or create a new one with the pool's builder if the pool
+    /// can't offer one, same as `SyncPool::get`.
+    pub fn get(&mut self) -> Box<T> {
+        if let Some(val) = self.try_get() {
+            return val;
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.fallbacks.fetch_add(1, Ordering::Relaxed);
+        make_elem(&self.builder)
+    }
+
+    /// Check out an element from the pool, or return `None` if the pool can't offer one. Nothing is
+    /// allocated either way.
+    pub fn try_get(&mut self) -> Option<Box<T>> {
+        let scan = Self::scan(&self.curr);
+        scan.get(&mut self.slots).map(|(_, val, _)| val)
+    }
+
+    /// Return the element to the pool, same as `SyncPool::put`: return `None` if the element has
+    /// been placed in the pool, or hand it back if the pool is full.
+    pub fn put(&mut self, val: Box<T>) -> Option<Box<T>> {
+        let scan = Self::scan(&self.curr);
+        let reset = self.reset_handle.as_mut();
+        let reset = reset.map(|handle| handle as &mut dyn FnMut(&mut T));
+
+        scan.put(&mut self.slots, val, 0, reset, false).err()
+    }
+
+    /// The number of `get` calls the pool couldn't serve from its buckets, same as
+    /// `PoolState::miss_count`.
+    pub fn miss_count(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// The number of elements created by the pool's builder because the pool couldn't offer one,
+    /// same as `PoolSnapshot::fallback_count`.
+    pub fn fallback_count(&self) -> usize {
+        self.fallbacks.load(Ordering::Relaxed)
+    }
+
+    /// The scan of `get` and `put`, shared with `SyncPool`: every bucket is probed once, from the
+    /// cursor on, and the cursor stays at the bucket that served the call.
+    fn scan(cursor: &AtomicUsize) -> Scan<'_> {
+        Scan {
+            cursor,
+            sticky: true,
+            trials: B,
+            #[cfg(feature = "deterministic")]
+            sequence: &None,
+        }
+    }
+
+    /// The number of elements the pool can hold, i.e. `B * SLOT_CAP`.
+    pub const fn capacity(&self) -> usize {
+        B * SLOT_CAP
+    }
+
+    /// The number of elements in the pool.
+    pub fn len(&self) -> usize {
+        self.slots.iter().map(Bucket2::len).sum()
+    }
+
+    /// If the pool holds no element.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T, const B: usize> ObjectPool<T> for ArrayPool<T, B> {
+    /// Same as `ArrayPool::get`.
+    fn acquire(&mut self) -> Box<T> {
+        self.get()
+    }
+
+    /// Same as `ArrayPool::put`.
+    fn release(&mut self, val: Box<T>) -> Option<Box<T>> {
+        self.put(val)
+    }
+}
+
+#[cfg(test)]
+mod array_tests {
+    use super::*;
+
+    #[test]
+    fn get_and_put() {
+        let mut pool: ArrayPool<usize, 2> = ArrayPool::new();
+        pool.reset_handle(|val| *val = 7);
+        assert_eq!(pool.capacity(), 2 * SLOT_CAP);
+
+        let held: Vec<_> = (0..=2 * SLOT_CAP).map(|_| pool.get()).collect();
+        assert!(pool.is_empty());
+        assert!(pool.try_get().is_none());
+
+        let mut rejected = 0;
+        for val in held {
+            if pool.put(val).is_some() {
+                rejected += 1;
+            }
+        }
+
+        assert_eq!(rejected, 1);
+        assert_eq!(pool.len(), 2 * SLOT_CAP);
+        assert_eq!(*pool.get(), 7);
+
+        // only the checkout past the capacity has been created by the builder
+        assert_eq!((pool.miss_count(), pool.fallback_count()), (1, 1));
+    }
+
+    #[test]
+    fn with_builder() {
+        let mut pool: ArrayPool<Vec<u8>, 1> = ArrayPool::with_builder(|| vec![1; 4]);
+        assert_eq!(*pool.get(), vec![1; 4]);
+    }
+}
@@ -67,6 +67,11 @@ use std::ptr;
 /// after being created by the API is *extremely* dangerous and will almost certainly lead to undefined
 /// behaviors.
 ///
+/// # Safety
+///
+/// The returned box points to uninitialized memory. The caller must initialize every field before
+/// reading from it or dropping it.
+///
 /// # Examples
 ///
 /// Create a boxed `BigStruct`
@@ -108,6 +113,11 @@ pub unsafe fn raw_box<T>() -> Box<T> {
 /// undefined since they're pointing to the `null` pointer (i.e. default pointer created by
 /// `std::ptr::null_mut()`).
 ///
+/// # Safety
+///
+/// The caller must make sure the all-zero bit pattern is a valid value for `T`, or otherwise
+/// overwrite the invalid fields before reading from the object or dropping it.
+///
 /// # Examples
 ///
 /// Create a boxed `DangerousStruct`
@@ -364,7 +374,7 @@ mod boxed_tests {
         assert_eq!(big_ref.c[4200], 125);
 
         let atomic = unsafe { &*boxed.b.as_ptr() };
-        assert_eq!(atomic.load(Ordering::Acquire), false);
+        assert!(!atomic.load(Ordering::Acquire));
     }
 
     #[test]
@@ -1,16 +1,61 @@
 #![allow(unused)]
 
 use crate::make_box;
-use crate::pool::ElemBuilder;
+use crate::pool::{notify_evict, ElemBuilder, EvictHook};
 use crate::utils::{check_len, cpu_relax, enter, make_elem};
+use std::hint;
 use std::mem;
 use std::ptr;
-use std::sync::atomic::{AtomicBool, AtomicU16, AtomicUsize, AtomicPtr, Ordering};
+use std::sync::atomic::{
+    AtomicBool, AtomicPtr, AtomicU16, AtomicU32, AtomicU64, AtomicUsize, Ordering,
+};
+use std::time::Instant;
+
+#[cfg(feature = "max-lifetime")]
+use std::time::Duration;
 
 /// Constants
-pub(crate) const SLOT_CAP: usize = 8;
+/// The number of elements a bucket can hold. The pool is made up of buckets, so its capacity is
+/// always a multiple of this number.
+pub const SLOT_CAP: usize = 8;
 const TRIALS_COUNT: usize = 4;
 
+/// The state of a position in a bucket, as told by the bucket's bitmap, see `SyncPool::slot_state`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotState {
+    /// The position holds an idle element.
+    Occupied,
+
+    /// The position is vacant.
+    Empty,
+
+    /// Someone is checking out or returning an element at the position right now.
+    Locked,
+}
+
+/// How an element checked out of a bucket is to be handed out, see `Bucket2::mark`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Mark {
+    /// The element is ready to use.
+    Clean,
+
+    /// The element has been stored without running the reset handle, see `Bucket2::mark_dirty`.
+    Dirty,
+
+    /// The element has outlived the maximum lifetime, and is to be dropped instead.
+    #[cfg(feature = "max-lifetime")]
+    Expired,
+}
+
+/// The creation times of the elements at the positions of a bucket, in nanoseconds since `epoch`,
+/// along with the maximum lifetime they're held to, see `SyncPool::set_max_lifetime`.
+#[cfg(feature = "max-lifetime")]
+struct Births {
+    epoch: Instant,
+    max: Duration,
+    times: [AtomicU64; SLOT_CAP],
+}
+
 pub(crate) struct Bucket<T> {
     /// the actual data store
     slot: [Option<T>; SLOT_CAP],
@@ -148,6 +193,12 @@ impl<T> Bucket<T> {
     */
 }
 
+/// A bucket of the pool, holding up to `SLOT_CAP` elements. The callers of
+/// `SyncPool::with_exclusive` only reach a bucket through a `BucketView`.
+///
+/// The elements are kept in the heap and the slots only hold their pointers, so the footprint of a
+/// bucket doesn't depend on the size of `T`: the empty slots of a pool of large elements cost no
+/// more than those of a pool of small ones, and there's no need to pool a large `T` as `Box<T>`.
 pub(crate) struct Bucket2<T> {
     /// The actual data store. Data are stored in heap and not managed by the runtime, so we must
     /// restore them and drop the data when the bucket is dropped.
@@ -166,6 +217,34 @@ pub(crate) struct Bucket2<T> {
     /// the bit at (2 * pos + 1) indicates if someone is operating at the slot, and hence everyone
     /// else shall avoid using the position, otherwise we may corrupt the underlying data structure.
     bitmap: AtomicU16,
+
+    /// The number of read guards pinning each position. The first reader takes the lock bit of the
+    /// position on behalf of all readers, and the last reader to leave releases it.
+    readers: [AtomicU32; SLOT_CAP],
+
+    /// The sequence number of the `put` that placed the element at each position, 0 for the
+    /// elements the bucket has been created with. Only allocated (and stamped) for
+    /// `FullPolicy::EvictOldest`, see `track_stamps`.
+    stamps: Option<Box<[AtomicU64; SLOT_CAP]>>,
+
+    /// The positions holding an element that has been stored without running the reset handle
+    /// (i.e. under `ResetTiming::OnCheckout`), one bit per position, such that only those are reset
+    /// when they're checked out.
+    dirty: AtomicU16,
+
+    /// The creation time of the element at each position, which moves along with the element
+    /// between the positions, and stays behind for the element returned into a vacated position.
+    /// Only allocated once the pool holds its elements to a maximum lifetime, see `track_births`.
+    #[cfg(feature = "max-lifetime")]
+    births: Option<Box<Births>>,
+
+    /// The number of times an access to this bucket has been denied.
+    #[cfg(feature = "bucket-stats")]
+    denied: AtomicUsize,
+
+    /// The number of accesses to this bucket to be denied on purpose, `usize::MAX` for all of them.
+    #[cfg(feature = "test-hooks")]
+    injected_failures: AtomicUsize,
 }
 
 impl<T> Bucket2<T> {
@@ -175,6 +254,7 @@ impl<T> Bucket2<T> {
         // create the placeholder
         let mut slice: [*mut T; SLOT_CAP] = [ptr::null_mut(); SLOT_CAP];
         let mut bitmap: u16 = 0;
+        let mut len = 0;
 
         // fill the slots and update the bitmap
         if let Some(handle) = filler {
@@ -182,13 +262,69 @@ impl<T> Bucket2<T> {
                 *item = Box::into_raw(make_elem(handle));
                 bitmap |= 1 << (2 * i as u16);
             }
+
+            len = SLOT_CAP;
         }
 
         // done
         Bucket2 {
             slot: slice,
-            len: AtomicUsize::new(SLOT_CAP),
+            len: AtomicUsize::new(len),
             bitmap: AtomicU16::new(bitmap),
+            readers: Default::default(),
+            stamps: None,
+            dirty: AtomicU16::new(0),
+            #[cfg(feature = "max-lifetime")]
+            births: None,
+            #[cfg(feature = "bucket-stats")]
+            denied: AtomicUsize::new(0),
+            #[cfg(feature = "test-hooks")]
+            injected_failures: AtomicUsize::new(0),
+        }
+    }
+
+    /// Create a new bucket with the clones of the elements currently in this bucket. The caller
+    /// must have obtained an exclusive access to the bucket, such that no one is checking out or
+    /// releasing the elements simultaneously.
+    pub(crate) fn duplicate(&self) -> Self
+    where
+        T: Clone,
+    {
+        let mut slice: [*mut T; SLOT_CAP] = [ptr::null_mut(); SLOT_CAP];
+        let mut bitmap: u16 = 0;
+        let mut len = 0;
+
+        for (i, item) in self.slot.iter().enumerate() {
+            if item.is_null() {
+                continue;
+            }
+
+            // the pointer is valid since it was boxed by `new` or `release`
+            slice[i] = Box::into_raw(Box::new(unsafe { (**item).clone() }));
+            bitmap |= 1 << (2 * i as u16);
+            len += 1;
+        }
+
+        Bucket2 {
+            slot: slice,
+            len: AtomicUsize::new(len),
+            bitmap: AtomicU16::new(bitmap),
+            readers: Default::default(),
+            stamps: self.stamps.as_ref().map(|_| Default::default()),
+            dirty: AtomicU16::new(self.dirty.load(Ordering::Relaxed)),
+            #[cfg(feature = "max-lifetime")]
+            // the clones are new elements, timed from now
+            births: self.births.as_ref().map(|births| {
+                Box::new(Births {
+                    epoch: Instant::now(),
+                    max: births.max,
+                    times: Default::default(),
+                })
+            }),
+            #[cfg(feature = "bucket-stats")]
+            denied: AtomicUsize::new(0),
+            #[cfg(feature = "test-hooks")]
+            injected_failures: AtomicUsize::new(0),
         }
     }
 
@@ -203,6 +339,50 @@ impl<T> Bucket2<T> {
     /// bucket, or put an element back into the bucket. If such a request can't be done, we will
     /// return error.
     pub(crate) fn access(&self, get: bool) -> Result<usize, ()> {
+        self.access_with(get, TRIALS_COUNT)
+    }
+
+    /// Same as `access`, but only make a single attempt to lodge the position, such that the caller
+    /// can quickly move on if the bucket is contended.
+    pub(crate) fn try_access(&self, get: bool) -> Result<usize, ()> {
+        self.access_with(get, 1)
+    }
+
+    /// Same as `try_access` for a `get`, but only lodge the given position, e.g. the position an
+    /// element has been recently returned to. Fail if the position is empty or contended.
+    pub(crate) fn try_access_at(&self, pos: usize) -> Result<usize, ()> {
+        if pos >= SLOT_CAP {
+            return Err(());
+        }
+
+        let curr_len = self.len.fetch_sub(1, Ordering::Relaxed);
+        if curr_len > SLOT_CAP || curr_len == 0 {
+            return self.access_failure(true);
+        }
+
+        #[cfg(feature = "test-hooks")]
+        if self.take_injected_failure() {
+            return self.access_failure(true);
+        }
+
+        let lock_bit = 0b10 << (2 * pos);
+        let old = self.bitmap.fetch_or(lock_bit, Ordering::AcqRel);
+
+        if old & lock_bit != 0 {
+            // someone else is operating at the position
+            return self.access_failure(true);
+        }
+
+        if old & (0b01 << (2 * pos)) == 0 {
+            // nothing to check out, free the position
+            self.bitmap.fetch_and(!lock_bit, Ordering::Release);
+            return self.access_failure(true);
+        }
+
+        Ok(pos)
+    }
+
+    fn access_with(&self, get: bool, trials: usize) -> Result<usize, ()> {
         // register intentions first, make sure the len is in post-action state so it can reject
         // future or concurrent attempts if it's unlikely to succeed in this slot.
         let curr_len = if get {
@@ -217,8 +397,13 @@ impl<T> Bucket2<T> {
             return self.access_failure(get);
         }
 
-        // try 2 times on this slot if the desired slot happens to be taken ...
-        let mut trials: usize = TRIALS_COUNT;
+        #[cfg(feature = "test-hooks")]
+        if self.take_injected_failure() {
+            return self.access_failure(get);
+        }
+
+        // try a few times on this slot if the desired slot happens to be taken ...
+        let mut trials = trials;
         while trials > 0 {
             trials -= 1;
 
@@ -231,9 +416,15 @@ impl<T> Bucket2<T> {
             // main loop to try to update the bitmap
             let old = self.bitmap.fetch_or(mask, Ordering::AcqRel);
 
-            // if the lock bit we replaced was not yet marked at the atomic op, we're good
+            // if the lock bit we replaced was not yet marked at the atomic op, we're good, unless the
+            // position has been filled (or emptied) by someone else since we picked it
             if old & mask == 0 {
-                return Ok(pos as usize);
+                let elem_bit = 0b01 << (2 * pos);
+                if (old & elem_bit != 0) == get {
+                    return Ok(pos as usize);
+                }
+
+                self.bitmap.fetch_and(!mask, Ordering::Release);
             }
 
             // otherwise, try again after some wait. The earliest registered gets some favor by
@@ -260,6 +451,211 @@ impl<T> Bucket2<T> {
         }
     }
 
+    /// Pin the element at `pos` for shared reads, such that it can't be checked out until `unpin` has
+    /// been called for every successful `pin`. Return `None` if the position holds no element.
+    pub(crate) fn pin(&self, pos: usize) -> Option<&T> {
+        let lock_bit = 0b10 << (2 * pos);
+        let elem_bit = 0b01 << (2 * pos);
+
+        loop {
+            // join the readers who are holding the lock bit already
+            let readers = self.readers[pos].load(Ordering::Acquire);
+            if readers > 0 {
+                if self.readers[pos]
+                    .compare_exchange(readers, readers + 1, Ordering::AcqRel, Ordering::Relaxed)
+                    .is_ok()
+                {
+                    return Some(unsafe { &*self.slot[pos] });
+                }
+
+                continue;
+            }
+
+            // otherwise, take the lock bit as the first reader
+            let old = self.bitmap.fetch_or(lock_bit, Ordering::AcqRel);
+            if old & lock_bit != 0 {
+                // someone else is operating at the position, or the last readers are leaving
+                hint::spin_loop();
+                continue;
+            }
+
+            if old & elem_bit == 0 {
+                // nothing to read, release the lock bit
+                self.bitmap.fetch_and(!lock_bit, Ordering::AcqRel);
+                return None;
+            }
+
+            self.readers[pos].store(1, Ordering::Release);
+            return Some(unsafe { &*self.slot[pos] });
+        }
+    }
+
+    /// Release a pin acquired by `pin`, the last reader to leave will release the lock bit of `pos`.
+    pub(crate) fn unpin(&self, pos: usize) {
+        if self.readers[pos].fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.bitmap
+                .fetch_and(!(0b10 << (2 * pos)), Ordering::Release);
+        }
+    }
+
+    /// Record the sequence number of the `put` that placed the element at `pos`. The caller must be
+    /// holding the lock bit of the position.
+    pub(crate) fn stamp(&self, pos: usize, seq: u64) {
+        if let Some(stamps) = self.stamps.as_ref() {
+            stamps[pos].store(seq, Ordering::Relaxed);
+        }
+    }
+
+    /// Mark the element at `pos` as stored without (or with) running the reset handle. The caller
+    /// must be holding the lock bit of the position, or an exclusive access to the bucket.
+    pub(crate) fn mark_dirty(&self, pos: usize, dirty: bool) {
+        if dirty {
+            self.dirty.fetch_or(1 << pos, Ordering::Relaxed);
+        } else {
+            self.dirty.fetch_and(!(1 << pos), Ordering::Relaxed);
+        }
+    }
+
+    /// If the element at `pos` has been stored without running the reset handle, see `mark_dirty`.
+    /// Same as `mark_dirty`, the caller must be holding the lock bit of the position.
+    pub(crate) fn is_dirty(&self, pos: usize) -> bool {
+        self.dirty.load(Ordering::Relaxed) & (1 << pos) != 0
+    }
+
+    /// How the element at `pos` is to be handed out once checked out: `Mark::Expired` if it has
+    /// outlived the maximum lifetime, in which case the position is timed afresh for the element
+    /// returned into it next, or else by its dirty mark. Same as `mark_dirty`, the caller must be
+    /// holding the lock bit of the position.
+    pub(crate) fn mark(&self, pos: usize) -> Mark {
+        #[cfg(feature = "max-lifetime")]
+        if let Some(births) = self.births.as_ref() {
+            let born =
+                births.epoch + Duration::from_nanos(births.times[pos].load(Ordering::Relaxed));
+            if born.elapsed() >= births.max {
+                self.set_born(pos, None);
+                return Mark::Expired;
+            }
+        }
+
+        if self.is_dirty(pos) {
+            Mark::Dirty
+        } else {
+            Mark::Clean
+        }
+    }
+
+    /// Hold the elements to the lifetime `max` (see `SyncPool::set_max_lifetime`), or stop timing
+    /// them if `None`. The positions timed for the first time start from now. The caller must have
+    /// obtained an exclusive access to the bucket, same as `track_stamps`.
+    #[cfg(feature = "max-lifetime")]
+    pub(crate) fn track_births(&mut self, max: Option<Duration>) {
+        match max {
+            None => self.births = None,
+            Some(max) => match self.births.as_mut() {
+                Some(births) => births.max = max,
+                None => {
+                    self.births = Some(Box::new(Births {
+                        epoch: Instant::now(),
+                        max,
+                        times: Default::default(),
+                    }))
+                }
+            },
+        }
+    }
+
+    /// When the element at `pos` has been created, or `None` if the births aren't tracked, see
+    /// `track_births`. The caller must be holding the lock bit of the position, or an exclusive
+    /// access to the bucket.
+    pub(crate) fn born_at(&self, pos: usize) -> Option<Instant> {
+        #[cfg(feature = "max-lifetime")]
+        if let Some(births) = self.births.as_ref() {
+            let nanos = births.times[pos].load(Ordering::Relaxed);
+            return Some(births.epoch + Duration::from_nanos(nanos));
+        }
+
+        None
+    }
+
+    /// Record the creation time of the element at `pos`, now if `None`. Same as `born_at`, the
+    /// caller must be holding the lock bit of the position, or an exclusive access to the bucket.
+    pub(crate) fn set_born(&self, pos: usize, born: Option<Instant>) {
+        #[cfg(feature = "max-lifetime")]
+        if let Some(births) = self.births.as_ref() {
+            let since = born
+                .unwrap_or_else(Instant::now)
+                .saturating_duration_since(births.epoch);
+            births.times[pos].store(since.as_nanos() as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Allocate the stamps of the positions (all 0, i.e. the oldest), or drop them, as the pool
+    /// switches to or from `FullPolicy::EvictOldest`. The caller must have obtained an exclusive
+    /// access to the bucket, such that no one is stamping the positions simultaneously.
+    pub(crate) fn track_stamps(&mut self, enable: bool) {
+        if !enable {
+            self.stamps = None;
+        } else if self.stamps.is_none() {
+            self.stamps = Some(Default::default());
+        }
+    }
+
+    /// If the stamps of the positions are being recorded, see `track_stamps`.
+    pub(crate) fn is_stamped(&self) -> bool {
+        self.stamps.is_some()
+    }
+
+    /// The position holding the oldest element by the recorded sequence numbers and its stamp, or
+    /// `None` if the bucket is empty or not stamped. This is a best-effort scan without locking the
+    /// positions.
+    pub(crate) fn oldest(&self) -> Option<(usize, u64)> {
+        let stamps = self.stamps.as_ref()?;
+        let bitmap = self.bitmap.load(Ordering::Acquire);
+
+        (0..SLOT_CAP)
+            .filter(|pos| bitmap & (0b01 << (2 * pos)) != 0)
+            .map(|pos| (pos, stamps[pos].load(Ordering::Relaxed)))
+            .min_by_key(|&(_, seq)| seq)
+    }
+
+    /// Replace the element at `pos` with `val` in place (after resetting `val`, or else marking it
+    /// as `dirty`), and return the replaced element. The position shall be occupied and free to
+    /// lock; otherwise `val` is handed back. The bucket's length is unchanged.
+    pub(crate) fn swap(
+        &mut self,
+        pos: usize,
+        mut val: Box<T>,
+        seq: u64,
+        reset: Option<&mut dyn FnMut(&mut T)>,
+        dirty: bool,
+    ) -> Result<Box<T>, Box<T>> {
+        let lock_bit = 0b10 << (2 * pos);
+        let elem_bit = 0b01 << (2 * pos);
+
+        let old = self.bitmap.fetch_or(lock_bit, Ordering::AcqRel);
+        if old & lock_bit != 0 {
+            // someone else is operating at the position
+            return Err(val);
+        }
+
+        if old & elem_bit == 0 || self.slot[pos].is_null() {
+            self.bitmap.fetch_and(!lock_bit, Ordering::Release);
+            return Err(val);
+        }
+
+        if let Some(handle) = reset {
+            handle(&mut val);
+        }
+
+        let evicted = mem::replace(&mut self.slot[pos], Box::into_raw(val));
+        self.stamp(pos, seq);
+        self.mark_dirty(pos, dirty);
+        self.bitmap.fetch_and(!lock_bit, Ordering::Release);
+
+        // same as `checkout`, the pointer has been knocked out from its boxed version
+        Ok(unsafe { Box::from_raw(evicted) })
+    }
+
     /// Locate the element from the desired position. The API will return an error if such operation
     /// can't be accomplished, such as the destination doesn't contain a element, or the desired position
     /// is OOB.
@@ -291,7 +687,21 @@ impl<T> Bucket2<T> {
     ///
     /// The function is safe because it's used internally, and each time it's guaranteed an exclusive
     /// access has been acquired previously
-    pub(crate) fn release(&mut self, pos: usize, mut val: Box<T>, reset: Option<fn(&mut T)>) {
+    pub(crate) fn release(
+        &mut self,
+        pos: usize,
+        mut val: Box<T>,
+        reset: Option<&mut dyn FnMut(&mut T)>,
+    ) {
+        // an occupied slot at this point means the bitmap has gone out of sync with the slots, most
+        // likely because the same element has been returned twice; fail loudly in debug builds.
+        debug_assert!(
+            pos >= SLOT_CAP || self.slot[pos].is_null(),
+            "SyncPool corrupted: putting an element into the occupied slot {}, is the same element \
+             returned to the pool twice?",
+            pos
+        );
+
         // check if the slot has already been occupied (unlikely but still)
         if pos >= SLOT_CAP || !self.slot[pos].is_null() {
             return;
@@ -306,8 +716,161 @@ impl<T> Bucket2<T> {
         self.slot[pos] = Box::into_raw(val);
     }
 
+    /// The state of the position as told by the bitmap, or `None` if `pos` is out of bounds. Same as
+    /// `size_hint`, the state is volatile if the bucket is accessed concurrently.
+    pub(crate) fn state_at(&self, pos: usize) -> Option<SlotState> {
+        if pos >= SLOT_CAP {
+            return None;
+        }
+
+        let bits = (self.bitmap.load(Ordering::Acquire) >> (2 * pos)) & 0b11;
+        Some(if bits & 0b10 != 0 {
+            SlotState::Locked
+        } else if bits & 0b01 != 0 {
+            SlotState::Occupied
+        } else {
+            SlotState::Empty
+        })
+    }
+
+    /// Move the element at `pos` out of the bucket along with its stamp (0 if not stamped) and its
+    /// creation time (see `born_at`), and update the bitmap and the count accordingly. The caller
+    /// must have obtained an exclusive access to the pool, such that no one is holding a lock or a
+    /// pin in the bucket.
+    pub(crate) fn take(&mut self, pos: usize) -> Option<(Box<T>, u64, Option<Instant>)> {
+        let val = self.checkout(pos).ok()?;
+
+        self.bitmap
+            .fetch_and(!(0b11 << (2 * pos as u16)), Ordering::AcqRel);
+        self.len.fetch_sub(1, Ordering::AcqRel);
+
+        let seq = self
+            .stamps
+            .as_ref()
+            .map_or(0, |stamps| stamps[pos].load(Ordering::Relaxed));
+
+        Some((val, seq, self.born_at(pos)))
+    }
+
+    /// Move the element into the first vacant position with the stamp, the creation time (now if
+    /// `None`) and the dirty mark (see `mark_dirty`), the reverse of `take`, or hand it back if the
+    /// bucket is full. Same as `take`, this requires an exclusive access.
+    pub(crate) fn place(
+        &mut self,
+        val: Box<T>,
+        seq: u64,
+        born: Option<Instant>,
+        dirty: bool,
+    ) -> Result<(), Box<T>> {
+        let pos = match self.slot.iter().position(|item| item.is_null()) {
+            Some(pos) => pos,
+            None => return Err(val),
+        };
+
+        self.slot[pos] = Box::into_raw(val);
+        self.stamp(pos, seq);
+        self.set_born(pos, born);
+        self.mark_dirty(pos, dirty);
+        self.bitmap
+            .fetch_or(0b01 << (2 * pos as u16), Ordering::AcqRel);
+        self.len.fetch_add(1, Ordering::AcqRel);
+
+        Ok(())
+    }
+
+    /// The number of elements in this bucket.
+    pub(crate) fn len(&self) -> usize {
+        self.slot.iter().filter(|item| !item.is_null()).count()
+    }
+
+    /// If this bucket holds no elements.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the elements in this bucket by shared references.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> {
+        self.slot
+            .iter()
+            .filter(|item| !item.is_null())
+            .map(|item| unsafe { &**item })
+    }
+
+    /// Iterate over the elements in this bucket.
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slot
+            .iter_mut()
+            .filter(|item| !item.is_null())
+            .map(|item| unsafe { &mut **item })
+    }
+
+    /// Only keep the elements for which `f` returns `true`, and drop the others. Return the number of
+    /// elements dropped.
+    pub(crate) fn retain<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let mut dropped = 0;
+
+        for (i, item) in self.slot.iter_mut().enumerate() {
+            // the pointer is valid since it was boxed by `new` or `release`
+            if item.is_null() || f(unsafe { &mut **item }) {
+                continue;
+            }
+
+            drop(unsafe { Box::from_raw(mem::replace(item, ptr::null_mut())) });
+            self.bitmap
+                .fetch_and(!(0b11 << (2 * i as u16)), Ordering::AcqRel);
+            self.len.fetch_sub(1, Ordering::AcqRel);
+            dropped += 1;
+        }
+
+        dropped
+    }
+
+    /// Take the element out of the slot without updating the bitmap, which simulates a transient
+    /// checkout failure for the next access to this position.
+    #[cfg(test)]
+    pub(crate) fn take_behind(&mut self, pos: usize) -> Option<Box<T>> {
+        let val = mem::replace(&mut self.slot[pos], ptr::null_mut());
+
+        if val.is_null() {
+            None
+        } else {
+            Some(unsafe { Box::from_raw(val) })
+        }
+    }
+
+    /// The number of times an access to this bucket has been denied.
+    #[cfg(feature = "bucket-stats")]
+    pub(crate) fn denied_count(&self) -> usize {
+        self.denied.load(Ordering::Relaxed)
+    }
+
+    /// Deny the next `count` accesses to this bucket as if it were contended, or all of them with
+    /// `usize::MAX`; 0 lifts the injected failures.
+    #[cfg(feature = "test-hooks")]
+    pub(crate) fn inject_failures(&self, count: usize) {
+        self.injected_failures.store(count, Ordering::Release);
+    }
+
+    /// Consume one of the injected failures, if any is left.
+    #[cfg(feature = "test-hooks")]
+    fn take_injected_failure(&self) -> bool {
+        self.injected_failures
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |left| match left {
+                0 => None,
+                usize::MAX => Some(left),
+                _ => Some(left - 1),
+            })
+            .is_ok()
+    }
+
     #[inline]
     fn access_failure(&self, get: bool) -> Result<usize, ()> {
+        #[cfg(feature = "bucket-stats")]
+        self.denied.fetch_add(1, Ordering::Relaxed);
+
         if get {
             self.len.fetch_add(1, Ordering::AcqRel);
         } else {
@@ -318,6 +881,59 @@ impl<T> Bucket2<T> {
     }
 }
 
+/// A bucket of the pool as handed out by `SyncPool::with_exclusive`, where no one else can access
+/// the pool in the meantime. The view offers the elements the bucket holds, but none of how the
+/// bucket keeps them.
+pub struct BucketView<'a, T> {
+    bucket: &'a mut Bucket2<T>,
+
+    /// the pool's observer of the elements it drops, see `SyncPool::set_on_evict`
+    on_evict: &'a Option<EvictHook<T>>,
+}
+
+impl<'a, T> BucketView<'a, T> {
+    pub(crate) fn new(bucket: &'a mut Bucket2<T>, on_evict: &'a Option<EvictHook<T>>) -> Self {
+        BucketView { bucket, on_evict }
+    }
+
+    /// The number of elements in this bucket.
+    pub fn len(&self) -> usize {
+        self.bucket.len()
+    }
+
+    /// If this bucket holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.bucket.is_empty()
+    }
+
+    /// Iterate over the elements in this bucket by shared references.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.bucket.iter()
+    }
+
+    /// Iterate over the elements in this bucket.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.bucket.iter_mut()
+    }
+
+    /// Only keep the elements for which `f` returns `true`, and drop the others, notifying the
+    /// observer set via `set_on_evict` of each. Return the number of elements dropped.
+    pub fn retain<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let on_evict = self.on_evict;
+        self.bucket.retain(|val| {
+            let keep = f(val);
+            if !keep {
+                notify_evict(on_evict, val);
+            }
+
+            keep
+        })
+    }
+}
+
 impl<T> Drop for Bucket2<T> {
     fn drop(&mut self) {
         for item in self.slot.iter_mut() {
@@ -333,7 +949,7 @@ impl<T> Drop for Bucket2<T> {
     }
 }
 
-unsafe impl<T> Send for Bucket2<T> {}
+unsafe impl<T: Send> Send for Bucket2<T> {}
 
 pub(crate) struct RingBucket<T> {
     /// The actual data store. Data are stored in heap and not managed by the runtime, so we must
@@ -359,7 +975,7 @@ impl<T> RingBucket<T> {
 
         // fill the slots and update the bitmap
         if let Some(handle) = filler {
-            for (_, item) in slice.iter_mut().enumerate() {
+            for item in slice.iter_mut() {
                 item.swap(Box::into_raw(make_elem(handle)), Ordering::SeqCst);
             }
         }
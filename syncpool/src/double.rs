@@ -0,0 +1,130 @@
+//! This module contains the double buffering on top of the `SyncPool`, i.e. a pair of elements
+//! rotated between the front (e.g. the frame being presented) and the back (e.g. the frame being
+//! drawn). Both elements are checked out when the `DoubleBuffer` is created, and stay with it for
+//! as long as it lives: `swap` only exchanges the roles of the two elements, and none of them goes
+//! back to the pool until the `DoubleBuffer` drops, when both are returned at once. Since the
+//! elements never leave the `DoubleBuffer`, neither of them can be returned twice by accident.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::prelude::*;
+//!
+//! #[derive(Default)]
+//! struct Frame {
+//!     pixels: Vec<u32>,
+//! }
+//!
+//! let mut pool: SyncPool<Frame> = SyncPool::with_size(8);
+//!
+//! {
+//!     let mut frames = pool.double_buffer();
+//!
+//!     for tick in 0..3 {
+//!         // draw the next frame into the back buffer ...
+//!         let back = frames.back_mut();
+//!         back.pixels.clear();
+//!         back.pixels.push(tick);
+//!
+//!         // ... then flip, and present the front
+//!         frames.swap();
+//!         assert_eq!(frames.front().pixels, vec![tick]);
+//!     }
+//! }
+//!
+//! // both frames are back in the pool
+//! assert_eq!(pool.len(), 8);
+//! ```
+
+use crate::pool::SyncPool;
+use std::mem;
+
+/// A front and a back element checked out from a `SyncPool`, see the module documentation.
+pub struct DoubleBuffer<'a, T> {
+    pool: &'a mut SyncPool<T>,
+    front: Option<Box<T>>,
+    back: Option<Box<T>>,
+}
+
+impl<'a, T> DoubleBuffer<'a, T> {
+    /// The front element, e.g. the last frame completed.
+    pub fn front(&self) -> &T {
+        self.front
+            .as_ref()
+            .expect("the front buffer is only taken when dropped")
+    }
+
+    /// The front element.
+    pub fn front_mut(&mut self) -> &mut T {
+        self.front
+            .as_mut()
+            .expect("the front buffer is only taken when dropped")
+    }
+
+    /// The back element, e.g. the frame being drawn.
+    pub fn back(&self) -> &T {
+        self.back
+            .as_ref()
+            .expect("the back buffer is only taken when dropped")
+    }
+
+    /// The back element.
+    pub fn back_mut(&mut self) -> &mut T {
+        self.back
+            .as_mut()
+            .expect("the back buffer is only taken when dropped")
+    }
+
+    /// Exchange the front and the back elements. Nothing is returned to the pool, and the elements
+    /// are not reset.
+    pub fn swap(&mut self) {
+        mem::swap(&mut self.front, &mut self.back);
+    }
+}
+
+impl<'a, T> Drop for DoubleBuffer<'a, T> {
+    fn drop(&mut self) {
+        for val in self.front.take().into_iter().chain(self.back.take()) {
+            // if the pool is full, the element will just be dropped here
+            self.pool.reclaim(val);
+        }
+    }
+}
+
+impl<T> SyncPool<T> {
+    /// Check out a pair of elements as a `DoubleBuffer`, which returns both of them to the pool when
+    /// it drops, including when the thread is unwinding from a panic. Same as `get`, new elements
+    /// will be created if the pool can't offer them.
+    pub fn double_buffer(&mut self) -> DoubleBuffer<'_, T> {
+        let front = self.get();
+        let back = self.get();
+
+        DoubleBuffer {
+            pool: self,
+            front: Some(front),
+            back: Some(back),
+        }
+    }
+}
+
+#[cfg(test)]
+mod double_tests {
+    use super::*;
+    use crate::PoolState;
+
+    #[test]
+    fn swap_and_return() {
+        let mut pool: SyncPool<usize> = SyncPool::with_size(8);
+
+        {
+            let mut buffers = pool.double_buffer();
+            *buffers.front_mut() = 1;
+            *buffers.back_mut() = 2;
+
+            buffers.swap();
+            assert_eq!((*buffers.front(), *buffers.back()), (2, 1));
+        }
+
+        assert_eq!(pool.len(), 8);
+    }
+}
@@ -0,0 +1,474 @@
+//! This module contains the asynchronous ways of using the `SyncPool`. `PoolHandle::put_async`
+//! returns an element without busy-waiting while the write barrier is raised: the returned future
+//! yields back to the executor, and is woken to try the return again once the barrier is lowered.
+//! The future goes through the handle, such that the pool stays reachable by the other clones (and
+//! the writers among them) while it waits; each attempt only holds the handle's lock briefly. With the `async` feature, `into_stream` turns the pool into a stream of the
+//! elements as they are returned by other tasks.
+//! An async reset (see `set_async_reset`) lets `put_async` await the clean-up of an element, e.g. a
+//! connection sending a quit frame, before the element is stored.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::future::Future;
+//! use std::pin::Pin;
+//! use std::sync::Arc;
+//! use std::task::{Context, Poll, Wake};
+//! use syncpool::prelude::*;
+//!
+//! struct Noop;
+//!
+//! impl Wake for Noop {
+//!     fn wake(self: Arc<Self>) {}
+//! }
+//!
+//! let handle: PoolHandle<Vec<u8>> = SyncPool::with_size(8).into_handle();
+//! let val = handle.get();
+//!
+//! let waker = Arc::new(Noop).into();
+//! let mut cx = Context::from_waker(&waker);
+//! let mut fut = handle.put_async(val);
+//!
+//! // a real executor would do the polling
+//! let res = loop {
+//!     if let Poll::Ready(res) = Pin::new(&mut fut).poll(&mut cx) {
+//!         break res;
+//!     }
+//! };
+//!
+//! assert!(res.is_none());
+//! ```
+
+use crate::handle::PoolHandle;
+use crate::pool::SyncPool;
+use std::future::Future;
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+/// The future an `AsyncResetHandle` returns, which resolves to the reset element, or hands the
+/// element back as `Err` if the reset has failed and the element shall not be reused.
+pub type ResetFuture<T> = Pin<Box<dyn Future<Output = Result<Box<T>, Box<T>>> + Send>>;
+
+/// The async reset handle awaited by `put_async`, see `SyncPool::set_async_reset`.
+pub type AsyncResetHandle<T> = Box<dyn FnMut(Box<T>) -> ResetFuture<T> + Send>;
+
+/// The wakers of the pending streams and `put_async` futures, which are woken once an element has
+/// been returned to the pool, the write barrier has been lowered, or the streams are closed.
+#[derive(Default)]
+pub(crate) struct Waiters {
+    count: AtomicUsize,
+    #[cfg(feature = "async")]
+    closed: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Waiters {
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut wakers = self.wakers.lock().unwrap_or_else(PoisonError::into_inner);
+        wakers.push(waker.clone());
+        self.count.fetch_add(1, Ordering::SeqCst);
+
+        // pairs with the fence in `wake_all`: either the stream's next check sees the returned
+        // element, or the returning side sees the registered waker
+        #[cfg(feature = "async")]
+        atomic::fence(Ordering::SeqCst);
+    }
+
+    /// Wake all pending streams and `put_async` futures, this is cheap if none is waiting.
+    pub(crate) fn wake_all(&self) {
+        // without the streams, the only waiters are registered by `PoolHandle::get_with_policy`
+        // and `put_async`, both under the handle's lock which the returning side holds as well, so
+        // the lock orders the two and the plain check suffices
+        #[cfg(feature = "async")]
+        atomic::fence(Ordering::SeqCst);
+        if self.count.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+
+        let wakers: Vec<Waker> = {
+            let mut wakers = self.wakers.lock().unwrap_or_else(PoisonError::into_inner);
+            self.count.store(0, Ordering::SeqCst);
+            wakers.drain(..).collect()
+        };
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// The future returned by `PoolHandle::put_async`, which resolves to the same value as `put` would
+/// return, i.e. `None` if the element has been placed in the pool, or the element itself if the
+/// pool is full.
+///
+/// If the future is dropped before it completes, e.g. because the executor is shutting down, the
+/// drop makes one more attempt to return the element, without waiting for the barrier: if the
+/// barrier is still raised (or the pool is full), the element is dropped, since blocking the
+/// executor's thread in a drop could stall every other task on it. Call `into_inner` to take the
+/// element back from a pending future instead.
+///
+/// The exception is the element held by a pending async reset (see `SyncPool::set_async_reset`),
+/// which is owned by the reset future until it resolves: dropping the `PutFuture` in the meantime
+/// drops the reset future along with the element, and `into_inner` has nothing to hand back.
+pub struct PutFuture<T> {
+    handle: PoolHandle<T>,
+    val: Option<Box<T>>,
+    reset: Option<ResetFuture<T>>,
+}
+
+impl<T> PutFuture<T> {
+    /// Cancel the return and hand the element back, or `None` if the future has completed, or if
+    /// the element is still held by the async reset.
+    pub fn into_inner(mut self) -> Option<Box<T>> {
+        self.val.take()
+    }
+}
+
+impl<T> Future for PutFuture<T> {
+    type Output = Option<Box<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Some(reset) = this.reset.as_mut() {
+            match reset.as_mut().poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok(val)) => {
+                    this.reset = None;
+                    this.val.replace(val);
+                }
+                Poll::Ready(Err(val)) => {
+                    // the element is dropped by the pool instead of being stored
+                    this.reset = None;
+                    this.handle.with(|pool| pool.drop_reset_failure(val));
+                    return Poll::Ready(None);
+                }
+            }
+        }
+
+        let val = this.val.take().expect("PutFuture polled after completion");
+        let slot = &mut this.val;
+
+        this.handle.with(|pool| {
+            let val = match pool.try_put(val) {
+                Ok(res) => return Poll::Ready(res),
+                Err(val) => val,
+            };
+
+            // the barrier is raised, wait for it to be lowered; check again after the registration,
+            // in case the barrier is lowered in the meantime
+            pool.waiters().register(cx.waker());
+            match pool.try_put(val) {
+                Ok(res) => Poll::Ready(res),
+                Err(val) => {
+                    slot.replace(val);
+                    Poll::Pending
+                }
+            }
+        })
+    }
+}
+
+impl<T> Drop for PutFuture<T> {
+    fn drop(&mut self) {
+        if let Some(val) = self.val.take() {
+            // don't block the executor, if the barrier is raised or the pool is full, the element
+            // will just be dropped here
+            let _ = self.handle.with(|pool| pool.try_put(val));
+        }
+    }
+}
+
+/// The stream of the pool's elements returned by `SyncPool::into_stream`, which yields the elements
+/// as they become available in the pool, e.g. when they're `put` back by other tasks through the
+/// `PoolHandle` of the stream, effectively turning the pool into a multi-producer, multi-consumer
+/// channel of recyclable elements with the pool's buckets as the buffer. Clone the stream for more
+/// consumers, and the handle (see `handle`) for more producers.
+///
+/// The crate doesn't depend on any async runtime, so the stream offers `poll_next` (with the same
+/// signature as `futures::Stream::poll_next`, so an adapter is trivial) and `next` for `.await`.
+///
+/// - Backpressure: the stream never creates new elements, and the buffer is bounded by the pool's
+///   capacity; once it's full, `put` hands the elements back to the producers as usual.
+/// - Shutdown: after `close` (or `SyncPool::close_streams`), the streams keep yielding the elements
+///   still in the pool, and then end (i.e. yield `None`) instead of waiting for more elements.
+#[cfg(feature = "async")]
+pub struct PoolStream<T> {
+    handle: PoolHandle<T>,
+}
+
+#[cfg(feature = "async")]
+impl<T> PoolStream<T> {
+    /// Poll for the next element. Yield `Pending` (and wake the task once an element is returned to
+    /// the pool) if the pool is empty, or `None` if the streams are closed and the pool is drained.
+    pub fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Box<T>>> {
+        self.handle.with(|pool| {
+            if let Some(val) = pool.try_get() {
+                return Poll::Ready(Some(val));
+            }
+
+            if pool.waiters().closed.load(Ordering::Acquire) {
+                return Poll::Ready(None);
+            }
+
+            // the returns go through the same lock, so no element can slip in before the waker is
+            // registered
+            pool.waiters().register(cx.waker());
+            Poll::Pending
+        })
+    }
+
+    /// The future of the next element, see `poll_next`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Next<'_, T> {
+        Next { stream: self }
+    }
+
+    /// The handle of the pool behind the stream, clone it for the producers returning the elements.
+    pub fn handle(&self) -> &PoolHandle<T> {
+        &self.handle
+    }
+
+    /// Same as `SyncPool::close_streams`.
+    pub fn close(&self) {
+        self.handle.with(SyncPool::close_streams);
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> Clone for PoolStream<T> {
+    fn clone(&self) -> Self {
+        PoolStream {
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+/// The future returned by `PoolStream::next`.
+#[cfg(feature = "async")]
+pub struct Next<'s, T> {
+    stream: &'s mut PoolStream<T>,
+}
+
+#[cfg(feature = "async")]
+impl<'s, T> Future for Next<'s, T> {
+    type Output = Option<Box<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.get_mut().stream).poll_next(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> PoolHandle<T> {
+    /// A stream of the pool's elements, see `PoolStream`.
+    pub fn stream(&self) -> PoolStream<T> {
+        PoolStream {
+            handle: self.clone(),
+        }
+    }
+}
+
+impl<T> SyncPool<T> {
+    /// Move the pool behind a `PoolHandle`, and turn it into a stream of its elements, see
+    /// `PoolStream` for the backpressure and the shutdown semantics.
+    #[cfg(feature = "async")]
+    pub fn into_stream(self) -> PoolStream<T> {
+        self.into_handle().stream()
+    }
+
+    /// Close the streams of the pool: the pending streams are woken, and all streams (including the
+    /// ones created afterwards) will end once the pool has been drained.
+    #[cfg(feature = "async")]
+    pub fn close_streams(&mut self) {
+        self.waiters().closed.store(true, Ordering::Release);
+        self.waiters().count.fetch_add(1, Ordering::SeqCst);
+        self.waiters().wake_all();
+    }
+
+    /// Set or update the async reset handle awaited by `PoolHandle::put_async` before the returned elements are
+    /// stored, e.g. for a pooled connection that shall send a quit frame before it's reused. The
+    /// handle takes the element, and returns a future resolving to the reset element, or handing it
+    /// back as `Err` if the reset has failed (e.g. the connection is broken), in which case the pool
+    /// drops the element instead of storing it, same as a `PutOutcome::Dropped` return.
+    ///
+    /// Only `put_async` awaits the async reset; the blocking `put` (and the return attempted by a
+    /// dropped `PutFuture`) can't, and only runs the `reset_handle`. Same as `set_reset`, the handle
+    /// is owned by the pool, and won't be carried over by `duplicate`.
+    pub fn set_async_reset<F, Fut>(&mut self, mut handle: F)
+    where
+        F: FnMut(Box<T>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Box<T>, Box<T>>> + Send + 'static,
+    {
+        self.async_reset()
+            .replace(Box::new(move |val| Box::pin(handle(val))));
+    }
+}
+
+impl<T> PoolHandle<T> {
+    /// Return an element to the pool from an async context. Same as `put`, but the returned future
+    /// yields to the executor instead of busy-waiting while the write barrier is raised, and will
+    /// perform the return once the barrier is lowered. The handle's lock is only held by each
+    /// attempt, not while the future is pending. See `PutFuture` for what happens if the future is
+    /// dropped before completion.
+    ///
+    /// If an async reset has been set, the element is handed to it right away, and the future awaits
+    /// the reset before storing the reset element (which then goes through the `reset_handle` too,
+    /// same as `put`). If the reset fails, the element is dropped, the failure is counted by
+    /// `SyncPool::reset_failure_count`, and the future resolves to `None`.
+    pub fn put_async(&self, val: Box<T>) -> PutFuture<T> {
+        let (val, reset) = self.with(|pool| match pool.async_reset().as_mut() {
+            Some(handle) => (None, Some(handle(val))),
+            None => (Some(val), None),
+        });
+
+        PutFuture {
+            handle: self.clone(),
+            val,
+            reset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod future_tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::task::Wake;
+
+    struct Counter(AtomicUsize);
+
+    impl Wake for Counter {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn stream() {
+        use std::thread;
+
+        let counter = Arc::new(Counter(AtomicUsize::new(0)));
+        let waker = Arc::clone(&counter).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut stream = SyncPool::<[u8; 32]>::with_size(8).into_stream();
+
+        let mut held = Vec::new();
+        while let Poll::Ready(Some(val)) = Pin::new(&mut stream).poll_next(&mut cx) {
+            held.push(val);
+        }
+
+        // the drained pool keeps the stream pending, until an element is returned
+        assert_eq!(held.len(), 8);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+
+        // the producers return the elements while the stream is alive
+        let producers: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = stream.handle().clone();
+                let vals: Vec<_> = held.drain(..2).collect();
+
+                thread::spawn(move || {
+                    for val in vals {
+                        handle.put(val);
+                    }
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+
+        // once closed, the streams (including the clones) drain the pool and then end
+        let mut consumer = stream.clone();
+        stream.close();
+
+        let mut count = 0;
+        while let Poll::Ready(Some(_)) = Pin::new(&mut consumer).poll_next(&mut cx) {
+            count += 1;
+        }
+
+        assert_eq!(count, 8);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert!(Pin::new(&mut stream).poll_next(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn async_reset() {
+        use crate::PoolState;
+
+        struct YieldOnce(bool);
+
+        impl Future for YieldOnce {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                if self.0 {
+                    return Poll::Ready(());
+                }
+
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+
+        let counter = Arc::new(Counter(AtomicUsize::new(0)));
+        let waker = Arc::clone(&counter).into();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.set_async_reset(|mut val: Box<[u8; 32]>| async move {
+            YieldOnce(false).await;
+
+            // a "broken" element fails the reset
+            if val[0] == 0xff {
+                return Err(val);
+            }
+
+            val[0] = 1;
+            Ok(val)
+        });
+
+        let evicted = Arc::new(AtomicUsize::new(0));
+        let seen = Arc::clone(&evicted);
+        pool.set_on_evict(move |val: &[u8; 32]| {
+            assert_eq!(val[0], 0xff);
+            seen.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let handle = pool.into_handle();
+        let val = handle.get();
+        let mut fut = handle.put_async(val);
+        assert!(Pin::new(&mut fut).poll(&mut cx).is_pending());
+        assert!(Pin::new(&mut fut).poll(&mut cx).is_ready());
+        drop(fut);
+        assert_eq!(handle.with(|pool| pool.len()), 8);
+
+        let mut val = handle.get();
+        assert_eq!(val[0], 1);
+        val[0] = 0xff;
+
+        let mut fut = handle.put_async(val);
+        let res = loop {
+            if let Poll::Ready(res) = Pin::new(&mut fut).poll(&mut cx) {
+                break res;
+            }
+        };
+        drop(fut);
+
+        assert!(res.is_none());
+        assert_eq!(handle.with(|pool| pool.len()), 7);
+        assert_eq!(handle.with(|pool| pool.reset_failure_count()), 1);
+        assert_eq!(handle.with(|pool| pool.outstanding_count()), 0);
+        assert_eq!(evicted.load(Ordering::SeqCst), 1);
+    }
+}
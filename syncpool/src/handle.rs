@@ -0,0 +1,232 @@
+//! This module contains the cloneable handle to a `SyncPool`, which can return elements to the
+//! pool through a shared reference, e.g. from the `Drop` of a user type wrapping a pooled element,
+//! where the `&mut SyncPool` required by `put` is out of reach.
+//!
+//! The handle owns the pool behind a mutex, so every call through the handle is serialized. This
+//! trades the pool's lock-free access for a return path that is safe to reach from anywhere; keep
+//! sharing the pool the usual way on the hot paths where the mutex would be the bottleneck.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::prelude::*;
+//!
+//! struct Pooled {
+//!     buf: Option<Box<Vec<u8>>>,
+//!     handle: PoolHandle<Vec<u8>>,
+//! }
+//!
+//! impl Drop for Pooled {
+//!     fn drop(&mut self) {
+//!         if let Some(buf) = self.buf.take() {
+//!             self.handle.put(buf);
+//!         }
+//!     }
+//! }
+//!
+//! let handle = SyncPool::with_size(8).into_handle();
+//!
+//! let pooled = Pooled {
+//!     buf: Some(handle.get()),
+//!     handle: handle.clone(),
+//! };
+//!
+//! assert_eq!(handle.with(|pool| pool.len()), 7);
+//!
+//! drop(pooled);
+//! assert_eq!(handle.with(|pool| pool.len()), 8);
+//! ```
+
+use crate::pool::{FallbackPolicy, ObjectPool, SyncPool};
+use std::ops::Add;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Instant;
+
+/// The cloneable handle to a pool created by `SyncPool::into_handle`. All the clones refer to the
+/// same pool, which is dropped along with the last clone.
+///
+/// The handle is `Send + Sync` only if `T: Send`, since the elements go wherever the clones go:
+///
+/// ```compile_fail
+/// use std::rc::Rc;
+/// use std::thread;
+/// use syncpool::prelude::*;
+///
+/// let handle = SyncPool::with_builder(|| Rc::new(0u8)).into_handle();
+/// thread::spawn(move || drop(handle.get()));
+/// ```
+pub struct PoolHandle<T> {
+    pool: Arc<Mutex<SyncPool<T>>>,
+}
+
+impl<T> PoolHandle<T> {
+    /// Same as `SyncPool::get`.
+    pub fn get(&self) -> Box<T> {
+        self.lock().get()
+    }
+
+    /// Same as `SyncPool::get_with_policy`, except that `FallbackPolicy::Wait` does wait here: the
+    /// pool is unlocked while the thread is parked, such that the other clones can return elements,
+    /// and the first return wakes the thread up to try again. The miss is counted once, when the
+    /// timeout expires.
+    pub fn get_with_policy(&self, policy: FallbackPolicy) -> Option<Box<T>> {
+        let deadline = match policy {
+            FallbackPolicy::Wait(timeout) => Instant::now().add(timeout),
+            _ => return self.lock().get_with_policy(policy),
+        };
+
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+
+        loop {
+            // the returns go through the same lock, so no element can slip in before the waker is
+            // registered
+            if let Some(val) = self.lock().try_get_or_register(&waker) {
+                return Some(val);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+
+            thread::park_timeout(deadline - now);
+        }
+
+        self.lock().count_miss();
+        None
+    }
+
+    /// Same as `SyncPool::put`, which is safe to call from a `Drop` implementation. A panic while
+    /// the pool is locked (e.g. from the reset handle) won't lock the other clones out of the pool.
+    pub fn put(&self, val: Box<T>) -> Option<Box<T>> {
+        self.lock().put(val)
+    }
+
+    /// Run `f` with the exclusive access to the pool, e.g. to configure it or to query its state.
+    pub fn with<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut SyncPool<T>) -> R,
+    {
+        f(&mut self.lock())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SyncPool<T>> {
+        self.pool.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Wakes a thread parked by `PoolHandle::get_with_policy`.
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+impl<T> Clone for PoolHandle<T> {
+    fn clone(&self) -> Self {
+        PoolHandle {
+            pool: Arc::clone(&self.pool),
+        }
+    }
+}
+
+impl<T> ObjectPool<T> for PoolHandle<T> {
+    /// Same as `PoolHandle::get`.
+    fn acquire(&mut self) -> Box<T> {
+        self.get()
+    }
+
+    /// Same as `PoolHandle::put`.
+    fn release(&mut self, val: Box<T>) -> Option<Box<T>> {
+        self.put(val)
+    }
+}
+
+impl<T> SyncPool<T> {
+    /// Move the pool into a cloneable handle, see `PoolHandle`.
+    pub fn into_handle(self) -> PoolHandle<T> {
+        PoolHandle {
+            pool: Arc::new(Mutex::new(self)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod handle_tests {
+    use super::*;
+    use crate::PoolState;
+    use std::thread;
+    use std::time::Duration;
+
+    struct Pooled {
+        val: Option<Box<[u8; 32]>>,
+        handle: PoolHandle<[u8; 32]>,
+    }
+
+    impl Drop for Pooled {
+        fn drop(&mut self) {
+            if let Some(val) = self.val.take() {
+                self.handle.put(val);
+            }
+        }
+    }
+
+    #[test]
+    fn return_on_drop() {
+        let handle = SyncPool::with_size(8).into_handle();
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = handle.clone();
+                thread::spawn(move || {
+                    for _ in 0..64 {
+                        let pooled = Pooled {
+                            val: Some(handle.get()),
+                            handle: handle.clone(),
+                        };
+
+                        drop(pooled);
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert_eq!(handle.with(|pool| pool.len()), 8);
+    }
+
+    #[test]
+    fn wait_for_return() {
+        let handle: PoolHandle<[u8; 32]> = SyncPool::with_size(8).into_handle();
+        let held: Vec<_> = (0..8).map(|_| handle.get()).collect();
+
+        // nothing comes back in time
+        let wait = FallbackPolicy::Wait(Duration::from_millis(10));
+        assert!(handle.get_with_policy(wait).is_none());
+        assert_eq!(handle.with(|pool| pool.miss_count()), 1);
+
+        let producer = {
+            let handle = handle.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                for val in held {
+                    handle.put(val);
+                }
+            })
+        };
+
+        // woken by the first return, long before the timeout
+        let wait = FallbackPolicy::Wait(Duration::from_secs(10));
+        assert!(handle.get_with_policy(wait).is_some());
+        assert_eq!(handle.with(|pool| pool.miss_count()), 1);
+
+        producer.join().unwrap();
+    }
+}
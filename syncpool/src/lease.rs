@@ -0,0 +1,174 @@
+//! This module contains the all-or-nothing checkout of a batch of elements, e.g. for a transaction
+//! that needs `k` elements for its whole duration. `SyncPool::lease` either checks out all the `k`
+//! elements, or none of them: if the pool runs out midway, the elements taken so far are returned
+//! before the call fails, so a failed lease never leaves the pool partially drained. The elements
+//! of a `Lease` are returned to the pool together when the lease drops.
+//!
+//! Since a lease never allocates, and holds the pool exclusively for as long as it lives, two
+//! transactions can't end up each holding a part of the elements the other one waits for.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::prelude::*;
+//!
+//! let mut pool: SyncPool<Vec<u8>> = SyncPool::with_size(8);
+//!
+//! // more than the pool holds: nothing is taken
+//! assert!(pool.lease(9).is_none());
+//! assert_eq!(pool.len(), 8);
+//!
+//! {
+//!     let mut lease = pool.lease(3).unwrap();
+//!     for (i, buf) in lease.iter_mut().enumerate() {
+//!         buf.push(i as u8);
+//!     }
+//!
+//!     assert_eq!(lease.len(), 3);
+//! }
+//!
+//! // all 3 are back in the pool
+//! assert_eq!(pool.len(), 8);
+//! ```
+
+use crate::pool::{PoolState, SyncPool};
+use std::ops::{Deref, DerefMut};
+
+/// A batch of elements checked out together from a `SyncPool`, see the module documentation. The
+/// lease derefs to the slice of the elements.
+pub struct Lease<'a, T> {
+    pool: &'a mut SyncPool<T>,
+    held: Vec<Box<T>>,
+}
+
+impl<'a, T> Deref for Lease<'a, T> {
+    type Target = [Box<T>];
+
+    fn deref(&self) -> &[Box<T>] {
+        &self.held
+    }
+}
+
+impl<'a, T> DerefMut for Lease<'a, T> {
+    fn deref_mut(&mut self) -> &mut [Box<T>] {
+        &mut self.held
+    }
+}
+
+impl<'a, T> Drop for Lease<'a, T> {
+    fn drop(&mut self) {
+        for val in self.held.drain(..) {
+            // if the pool is full, the element will just be dropped here
+            self.pool.reclaim(val);
+        }
+    }
+}
+
+impl<T> SyncPool<T> {
+    /// Check out `k` elements as a `Lease`, which returns all of them to the pool when it drops,
+    /// including when the thread is unwinding from a panic. Unlike `get`, no element is created on
+    /// the fly: yield `None` if the pool can't offer all the `k` elements (e.g. it holds fewer, or
+    /// some of them are too contended to reach), in which case the elements checked out so far are
+    /// put back into the buckets they came from without being reset, since they haven't been used,
+    /// and counted neither as checkouts nor as returns, so the pool is left as it was.
+    pub fn lease(&mut self, k: usize) -> Option<Lease<'_, T>> {
+        // don't churn through the pool if it can't make it anyway
+        if !self.can_satisfy(k) {
+            return None;
+        }
+
+        // the elements along with the buckets they came from
+        let mut held = Vec::with_capacity(k);
+        while held.len() < k {
+            match self.try_get_at() {
+                Some(taken) => held.push(taken),
+                None => {
+                    // roll back, each element to the bucket it came from
+                    for (idx, val) in held {
+                        self.undo_checkout(idx, val);
+                    }
+
+                    return None;
+                }
+            }
+        }
+
+        let held = held.into_iter().map(|(_, val)| val).collect();
+        Some(Lease { pool: self, held })
+    }
+}
+
+#[cfg(test)]
+mod lease_tests {
+    use super::*;
+    use crate::bucket::SLOT_CAP;
+    use crate::CursorPolicy;
+
+    #[test]
+    fn all_or_nothing() {
+        let mut pool: SyncPool<usize> = SyncPool::with_exact_buckets(2);
+        assert!(pool.lease(2 * SLOT_CAP + 1).is_none());
+
+        // leave 2 elements in the bucket the checkouts start from ...
+        let held: Vec<_> = (0..SLOT_CAP).map(|_| pool.get_bucket(0).unwrap()).collect();
+        let mut held = held.into_iter();
+        held.by_ref().take(2).for_each(|val| {
+            pool.put_bucket(0, val);
+        });
+
+        // ... and keep the checkouts from reaching the other one, so the lease fails midway
+        pool.set_max_probe(1);
+        assert!(pool.lease(3).is_none());
+        assert_eq!(pool.len(), SLOT_CAP + 2);
+        assert_eq!(pool.with_exclusive(|slots| slots[0].len()), 2);
+
+        pool.set_max_probe(usize::MAX);
+        {
+            let lease = pool.lease(3).unwrap();
+            assert_eq!(lease.len(), 3);
+        }
+
+        assert_eq!(pool.len(), SLOT_CAP + 2);
+        assert_eq!(pool.lease(0).map(|lease| lease.len()), Some(0));
+    }
+
+    #[test]
+    fn rollback_into_origin() {
+        let mut pool: SyncPool<usize> = SyncPool::with_exact_buckets(3);
+        pool.set_cursor_policy(CursorPolicy::Advancing);
+
+        // part the cursors: the checkouts start from the bucket 0, the returns from the bucket 2
+        pool.set_cursor(2);
+        let val = pool.get();
+        pool.put_bucket(2, val);
+
+        // leave 1 element in the bucket 0, none in the bucket 1, and keep the bucket 2 full
+        let mut held: Vec<_> = (0..SLOT_CAP - 1)
+            .filter_map(|_| pool.get_bucket(0))
+            .collect();
+        held.extend((0..SLOT_CAP).filter_map(|_| pool.get_bucket(1)));
+        assert_eq!(held.len(), 2 * SLOT_CAP - 1);
+
+        // the lease fails at the bucket 1, and the element goes back to the bucket 0, even though
+        // a return would have hit the full bucket 2
+        pool.set_max_probe(1);
+        let before = pool.snapshot();
+        assert!(pool.lease(2).is_none());
+
+        let after = pool.snapshot();
+        assert_eq!(after.len, SLOT_CAP + 1);
+        assert_eq!(pool.with_exclusive(|slots| slots[0].len()), 1);
+        assert_eq!(
+            (
+                after.checkout_count,
+                after.return_count,
+                after.outstanding_count
+            ),
+            (
+                before.checkout_count,
+                before.return_count,
+                before.outstanding_count
+            )
+        );
+    }
+}
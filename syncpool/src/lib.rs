@@ -190,19 +190,69 @@
 //! folder.
 //!
 
+mod aligned;
+mod array;
 mod boxed;
 mod bucket;
+mod double;
+mod future;
+mod handle;
+mod lease;
+mod orderings;
 mod pool;
+mod read;
+pub mod registry;
+mod scope;
+mod sharded;
+mod slice;
+mod strict;
+mod tiered;
+mod uninit;
 mod utils;
 
 pub use crate::{
+    aligned::{AlignedBytes, AlignedBytesPool},
+    array::ArrayPool,
     boxed::{default_box, make_box, raw_box, raw_box_zeroed},
-    pool::{PoolManager, PoolState, SyncPool},
+    bucket::{BucketView, SlotState, SLOT_CAP},
+    double::DoubleBuffer,
+    future::{AsyncResetHandle, PutFuture, ResetFuture},
+    handle::PoolHandle,
+    lease::Lease,
+    pool::{
+        buckets_for, buckets_to_objects, objects_to_buckets, AllocEvent, BuilderPanic,
+        CheckoutOrder, CursorPolicy, ExpandResult, FallbackPolicy, FullPolicy, ObjectPool, PoolId,
+        PoolManager, PoolSnapshot, PoolState, PoolStorage, PutOutcome, ResetHandle, ResetTiming,
+        SpinConfig, SyncPool, MAX_BUCKETS, POOL_SIZE,
+    },
+    read::ReadGuard,
+    scope::Scope,
+    sharded::ShardedPool,
+    slice::SliceBufferPool,
+    strict::StrictPool,
+    tiered::TieredGuard,
+    uninit::UninitGuard,
 };
 
+#[cfg(feature = "async")]
+pub use crate::future::{Next, PoolStream};
+#[cfg(feature = "debug-leaks")]
+pub use crate::pool::CheckoutInfo;
+#[cfg(feature = "fault-window")]
+pub use crate::pool::FAULT_WINDOW;
+#[cfg(feature = "reset-timing")]
+pub use crate::pool::RESET_HISTOGRAM;
+
 pub mod prelude {
     pub use crate::boxed::*;
-    pub use crate::{PoolManager, PoolState, SyncPool};
+    pub use crate::{
+        CheckoutOrder, CursorPolicy, DoubleBuffer, ExpandResult, FallbackPolicy, FullPolicy, Lease,
+        ObjectPool, PoolHandle, PoolManager, PoolState, PutFuture, PutOutcome, ReadGuard,
+        ResetTiming, Scope, SpinConfig, SyncPool, TieredGuard, UninitGuard,
+    };
+
+    #[cfg(feature = "async")]
+    pub use crate::PoolStream;
 }
 
 #[cfg(test)]
@@ -0,0 +1,172 @@
+//! This module contains the atomics of the handshake between the visitors (i.e. `get` and `put`)
+//! and the writer (e.g. `expand`): the visitor counter and the write barrier, see `VisitorGuard`
+//! for why the handshake needs the orderings it uses. The handshake is the part of the pool where
+//! a weakened ordering won't fail any functional test, but may let a visitor and the writer into
+//! the pool at the same time on some hardware.
+//!
+//! Normally, `HandshakeUsize` and `HandshakeBool` are just the std atomics. With the
+//! `debug-orderings` feature, they become thin wrappers recording every operation and its ordering
+//! on the current thread, such that the tests can assert that the orderings actually used by
+//! `get`, `put` and `expand` match the contract below, and a refactoring can't silently weaken (or
+//! change) them:
+//!
+//! | Call site                         | Operation                         | Ordering            |
+//! |-----------------------------------|-----------------------------------|---------------------|
+//! | visitor registers                 | counter `fetch_add`               | `SeqCst`            |
+//! | visitor checks the barrier        | barrier `load`                    | `SeqCst`            |
+//! | visitor leaves (or backs off)     | counter `fetch_sub`               | `Release`           |
+//! | writer raises the barrier         | barrier `compare_exchange(_weak)` | `SeqCst`, `Relaxed` |
+//! | writer waits for the visitors     | counter `compare_exchange`        | `SeqCst`, `Relaxed` |
+//! | writer restores the counter       | counter `fetch_add`               | `Release`           |
+//! | writer lowers the barrier         | barrier `store`                   | `Release`           |
+
+#[cfg(not(feature = "debug-orderings"))]
+pub(crate) use std::sync::atomic::{AtomicBool as HandshakeBool, AtomicUsize as HandshakeUsize};
+
+#[cfg(feature = "debug-orderings")]
+pub(crate) use recorded::{HandshakeBool, HandshakeUsize};
+
+#[cfg(feature = "debug-orderings")]
+mod recorded {
+    use std::cell::RefCell;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// A recorded operation on a handshake atomic: the atomic (`"counter"` or `"barrier"`), the
+    /// operation, and its ordering (or the success and failure orderings of an exchange).
+    pub(crate) type Record = (&'static str, &'static str, Ordering, Option<Ordering>);
+
+    thread_local! {
+        static RECORDS: RefCell<Vec<Record>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(atomic: &'static str, op: &'static str, order: Ordering, failure: Option<Ordering>) {
+        RECORDS.with(|records| records.borrow_mut().push((atomic, op, order, failure)));
+    }
+
+    /// Take the operations recorded on the current thread so far.
+    #[cfg(test)]
+    pub(crate) fn take_records() -> Vec<Record> {
+        RECORDS.with(|records| records.borrow_mut().drain(..).collect())
+    }
+
+    pub(crate) struct HandshakeUsize(AtomicUsize);
+
+    impl HandshakeUsize {
+        pub(crate) const fn new(val: usize) -> Self {
+            HandshakeUsize(AtomicUsize::new(val))
+        }
+
+        #[cfg(test)]
+        pub(crate) fn load(&self, order: Ordering) -> usize {
+            record("counter", "load", order, None);
+            self.0.load(order)
+        }
+
+        pub(crate) fn fetch_add(&self, val: usize, order: Ordering) -> usize {
+            record("counter", "fetch_add", order, None);
+            self.0.fetch_add(val, order)
+        }
+
+        pub(crate) fn fetch_sub(&self, val: usize, order: Ordering) -> usize {
+            record("counter", "fetch_sub", order, None);
+            self.0.fetch_sub(val, order)
+        }
+
+        pub(crate) fn compare_exchange(
+            &self,
+            current: usize,
+            new: usize,
+            success: Ordering,
+            failure: Ordering,
+        ) -> Result<usize, usize> {
+            record("counter", "compare_exchange", success, Some(failure));
+            self.0.compare_exchange(current, new, success, failure)
+        }
+    }
+
+    pub(crate) struct HandshakeBool(AtomicBool);
+
+    impl HandshakeBool {
+        pub(crate) const fn new(val: bool) -> Self {
+            HandshakeBool(AtomicBool::new(val))
+        }
+
+        pub(crate) fn load(&self, order: Ordering) -> bool {
+            record("barrier", "load", order, None);
+            self.0.load(order)
+        }
+
+        pub(crate) fn store(&self, val: bool, order: Ordering) {
+            record("barrier", "store", order, None);
+            self.0.store(val, order)
+        }
+
+        pub(crate) fn compare_exchange(
+            &self,
+            current: bool,
+            new: bool,
+            success: Ordering,
+            failure: Ordering,
+        ) -> Result<bool, bool> {
+            record("barrier", "compare_exchange", success, Some(failure));
+            self.0.compare_exchange(current, new, success, failure)
+        }
+
+        pub(crate) fn compare_exchange_weak(
+            &self,
+            current: bool,
+            new: bool,
+            success: Ordering,
+            failure: Ordering,
+        ) -> Result<bool, bool> {
+            // recorded as the strong exchange, the contract doesn't tell them apart
+            record("barrier", "compare_exchange", success, Some(failure));
+            self.0.compare_exchange_weak(current, new, success, failure)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "debug-orderings"))]
+mod orderings_tests {
+    use super::recorded::{take_records, Record};
+    use crate::{PoolManager, SyncPool};
+    use std::sync::atomic::Ordering::{Relaxed, Release, SeqCst};
+
+    const VISIT: [Record; 3] = [
+        ("counter", "fetch_add", SeqCst, None),
+        ("barrier", "load", SeqCst, None),
+        ("counter", "fetch_sub", Release, None),
+    ];
+
+    const WRITE: [Record; 4] = [
+        ("barrier", "compare_exchange", SeqCst, Some(Relaxed)),
+        ("counter", "compare_exchange", SeqCst, Some(Relaxed)),
+        ("counter", "fetch_add", Release, None),
+        ("barrier", "store", Release, None),
+    ];
+
+    #[test]
+    fn visitor_contract() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        take_records();
+
+        let val = pool.get();
+        assert_eq!(take_records(), VISIT);
+
+        pool.put(val);
+        assert_eq!(take_records(), VISIT);
+    }
+
+    #[test]
+    fn writer_contract() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.allow_expansion(true);
+        take_records();
+
+        assert!(pool.expand(1, true));
+        assert_eq!(take_records(), WRITE);
+
+        pool.with_exclusive(|_| {});
+        assert_eq!(take_records(), WRITE);
+    }
+}
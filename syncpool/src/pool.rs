@@ -1,17 +1,84 @@
 use crate::bucket::*;
+use crate::future::{AsyncResetHandle, Waiters};
+use crate::orderings::{HandshakeBool, HandshakeUsize};
+use crate::read::ReadGuard;
+#[cfg(feature = "deterministic")]
+use crate::utils::next_in_sequence;
 use crate::utils::{cpu_relax, make_elem};
+#[cfg(feature = "debug-leaks")]
+use std::backtrace::Backtrace;
+#[cfg(feature = "debug-leaks")]
+use std::collections::HashMap;
+use std::fmt;
+use std::mem;
 use std::ops::Add;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::thread;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{self, AtomicBool, AtomicIsize, AtomicU64, AtomicUsize, Ordering};
+#[cfg(feature = "debug-leaks")]
+use std::sync::Mutex;
+use std::sync::{Arc, Weak};
+use std::task::Waker;
+use std::thread::{self, ThreadId};
 use std::time::{Duration, Instant};
 
-const POOL_SIZE: usize = 8;
+/// The number of buckets in a pool created by the default constructors (e.g. `new`, `with_builder`
+/// or `with_packer`), i.e. such a pool holds `POOL_SIZE * SLOT_CAP` elements.
+pub const POOL_SIZE: usize = 8;
 const EXPANSION_CAP: usize = 512;
+
+/// The maximum number of buckets a pool can grow to via `expand`, i.e. a pool holds at most
+/// `MAX_BUCKETS * SLOT_CAP` elements unless it's created larger than that.
+pub const MAX_BUCKETS: usize = EXPANSION_CAP + 1;
 const SPIN_PERIOD: usize = 4;
+const SPIN_EXPONENT_CAP: usize = 16;
 
 /// Configuration flag (@ bit positions):
 /// 1 -> If the pool is allowed to expand when under pressure
+/// 2 -> If the bucket cursors keep advancing after a successful access
+/// 4 -> If the guards catch the reset handle's panic when returning elements during a panic
+/// 8 -> If `put` panics instead of handing the element back when no vacancy can be found
+/// 16 -> If `get` prefers the most recently returned elements
+/// 32 -> If the miss count survives the expansions
+/// 64 -> If the reset handle runs on the checkouts instead of the returns
+/// 128 -> If a panic of the element builder is caught and re-raised with the pool's identity
 const CONFIG_ALLOW_EXPANSION: usize = 1;
+const CONFIG_ADVANCING_CURSOR: usize = 2;
+const CONFIG_PANIC_RECLAIM: usize = 4;
+const CONFIG_STRICT_PUT: usize = 8;
+const CONFIG_LIFO: usize = 16;
+const CONFIG_KEEP_MISSES: usize = 32;
+const CONFIG_RESET_ON_CHECKOUT: usize = 64;
+const CONFIG_CATCH_BUILDER_PANIC: usize = 128;
+
+/// The number of the most recently returned positions tracked for `CheckoutOrder::Lifo`.
+const RECENT_CAP: usize = 8;
+
+/// The number of buckets needed to hold `objects` elements, i.e. `objects / SLOT_CAP` rounded up.
+/// This is the rounding policy of the pool sizing, e.g. of `with_size` (which keeps at least 1
+/// bucket, see `buckets_for`), and `expand_to`.
+pub const fn objects_to_buckets(objects: usize) -> usize {
+    objects.div_ceil(SLOT_CAP)
+}
+
+/// The number of elements `buckets` buckets can hold, i.e. `buckets * SLOT_CAP`, saturating at
+/// `usize::MAX`. This is the capacity of a pool with that many buckets, e.g. for `expand`, which is
+/// sized in buckets.
+pub const fn buckets_to_objects(buckets: usize) -> usize {
+    buckets.saturating_mul(SLOT_CAP)
+}
+
+/// The number of buckets a pool created with `size` elements (e.g. via `with_size`) will contain,
+/// i.e. `size / SLOT_CAP` rounded up, but at least 1 bucket. The capacity of such a pool will be
+/// `buckets_for(size) * SLOT_CAP`, which is never less than `size`.
+pub const fn buckets_for(size: usize) -> usize {
+    let buckets = objects_to_buckets(size);
+
+    if buckets < 1 {
+        1
+    } else {
+        buckets
+    }
+}
 
 pub(crate) enum ElemBuilder<T> {
     Default(fn() -> Box<T>),
@@ -19,44 +86,549 @@ pub(crate) enum ElemBuilder<T> {
     Packer(fn(Box<T>) -> Box<T>),
 }
 
-struct VisitorGuard<'a>(&'a AtomicUsize);
+impl<T> Clone for ElemBuilder<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ElemBuilder<T> {}
+
+/// The reset handle, either a plain function set via `reset_handle`, or a boxed closure set via
+/// `set_reset`. Both can be swapped in one go via `SyncPool::swap_reset_handle`.
+pub enum ResetHandle<T> {
+    /// A plain function, as set via `reset_handle`.
+    Func(fn(&mut T)),
+
+    /// A boxed closure, as set via `set_reset`.
+    Closure(Box<dyn FnMut(&mut T) + Send>),
+}
+
+impl<T> fmt::Debug for ResetHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResetHandle::Func(_) => f.write_str("Func(..)"),
+            ResetHandle::Closure(_) => f.write_str("Closure(..)"),
+        }
+    }
+}
+
+impl<T> ResetHandle<T> {
+    fn as_fn_mut(&mut self) -> &mut dyn FnMut(&mut T) {
+        match self {
+            ResetHandle::Func(handle) => handle,
+            ResetHandle::Closure(handle) => handle.as_mut(),
+        }
+    }
+}
+
+/// The tuning knobs of the busy-waiting loop a visitor runs while the write barrier is raised. In
+/// each round, the visitor spins `2^n` times, where `n` starts from `offset` and is decreased by 1
+/// after every round, until it reaches `cap`.
+///
+/// The default configuration is `offset = 8` and `cap = 4`. Both exponents are clamped to 16 when
+/// the configuration is applied, and `cap` is clamped to `offset`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpinConfig {
+    /// The spin exponent of the first round of the busy-waiting.
+    pub offset: usize,
+
+    /// The lowest spin exponent the busy-waiting will decay to.
+    pub cap: usize,
+}
+
+impl Default for SpinConfig {
+    fn default() -> Self {
+        SpinConfig { offset: 8, cap: 4 }
+    }
+}
+
+/// The number of ticks spanned by the rolling window of `SyncPool::fault_rate_window`.
+#[cfg(feature = "fault-window")]
+pub const FAULT_WINDOW: usize = 16;
+
+/// The number of buckets of `SyncPool::reset_duration_histogram`.
+#[cfg(feature = "reset-timing")]
+pub const RESET_HISTOGRAM: usize = 16;
+
+/// The durations of the reset handle invocations, and the hook to be warned of the slow ones, see
+/// `SyncPool::set_slow_reset_hook`.
+#[cfg(feature = "reset-timing")]
+#[derive(Default)]
+struct ResetDurations {
+    /// the number of resets by duration, where the bucket `i > 0` counts the ones taking from
+    /// `2^(i-1)` up to `2^i` microseconds, and the last bucket counts all the longer ones as well
+    histogram: [AtomicUsize; RESET_HISTOGRAM],
+
+    /// the duration a reset shall exceed for the hook to be invoked, and the hook
+    slow: Option<(Duration, fn(Duration))>,
+}
+
+#[cfg(feature = "reset-timing")]
+impl ResetDurations {
+    fn record(&self, elapsed: Duration) {
+        let micros = elapsed.as_micros();
+        let idx = ((u128::BITS - micros.leading_zeros()) as usize).min(RESET_HISTOGRAM - 1);
+        self.histogram[idx].fetch_add(1, Ordering::Relaxed);
+
+        if let Some((threshold, hook)) = self.slow {
+            if elapsed > threshold {
+                hook(elapsed);
+            }
+        }
+    }
+}
+
+/// The edge-triggered hooks of the pool running empty and being refilled, see `SyncPool::on_empty`.
+#[cfg(feature = "empty-events")]
+#[derive(Default)]
+struct EmptyEvents {
+    /// the live count of the idle elements, moved by the checkouts and the returns, and recounted
+    /// after the buckets have been changed under the exclusive access, see `recount_idle`
+    idle: AtomicIsize,
+
+    /// if the pool has been found empty after a checkout, and not been returned to since
+    empty: AtomicBool,
+    on_empty: Option<fn()>,
+    on_non_empty: Option<fn()>,
+}
+
+/// An element checked out of the pool and not yet returned, see `SyncPool::outstanding_checkouts`.
+#[cfg(feature = "debug-leaks")]
+#[derive(Clone, Debug)]
+pub struct CheckoutInfo {
+    /// the label passed to `get_labeled`, if the element has been checked out that way
+    pub label: Option<&'static str>,
+
+    /// when the element has been checked out
+    pub since: Instant,
+
+    /// where the element has been checked out, if captured; see `std::backtrace::Backtrace::capture`
+    /// for the environment variables enabling the capture
+    pub backtrace: Arc<Backtrace>,
+}
+
+/// The ring of the `get` calls and the faults (i.e. fallback allocations) counted per tick, see
+/// `SyncPool::tick`.
+#[cfg(feature = "fault-window")]
+#[derive(Default)]
+struct FaultWindow {
+    /// the (gets, faults) counted in each tick
+    ticks: [(AtomicUsize, AtomicUsize); FAULT_WINDOW],
+
+    /// the number of ticks so far, and the current tick is counted at `curr % FAULT_WINDOW`
+    curr: AtomicUsize,
+}
+
+/// The state of the tick-driven auto shrinking, see `SyncPool::set_auto_shrink`.
+struct AutoShrink {
+    idle_threshold: Duration,
+    check_interval: Duration,
+    last_check: Instant,
+
+    /// since when each bucket has been found full (i.e. all of its elements idle) by the ticks
+    idle_since: Vec<Option<Instant>>,
+}
+
+/// How the pool moves its bucket cursors after `get` or `put` succeeds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CursorPolicy {
+    /// Park the cursor at the bucket that just served the call, such that the next call will reuse
+    /// the warm bucket first. This is the default policy and favors locality.
+    Sticky,
+
+    /// Move the cursor past the bucket that just served the call, such that consecutive calls
+    /// spread over the buckets, and return the elements without preferring the bucket they came
+    /// from. This policy favors fairness among the buckets.
+    Advancing,
+}
+
+/// The identity of a pool, which is unique among all the pools created in the process, see
+/// `SyncPool::id`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PoolId(u64);
+
+/// The source of the pool identities, starting from 1.
+static NEXT_POOL_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The order in which `get` hands out the idle elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckoutOrder {
+    /// Scan the buckets from the cursor (see `CursorPolicy`), which hands out the elements roughly
+    /// in the order they have been returned. This is the default order.
+    RoundRobin,
+
+    /// Prefer the most recently returned elements, which are likely still warm in the cache, and
+    /// fall back to the scan if none of them is available.
+    Lifo,
+}
+
+/// When the reset handle runs on the pooled elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResetTiming {
+    /// Reset the element as it's returned via `put`, such that the pool only holds clean elements.
+    /// This is the default timing.
+    OnReturn,
 
+    /// Store the returned element as is, and reset it on the checkout that hands it out next,
+    /// before it's handed out. This moves the cost of the reset off the return path.
+    OnCheckout,
+}
+
+/// What `put` does with the returned element when it can't find a vacancy in the pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FullPolicy {
+    /// Hand the element back to the caller. This is the default policy.
+    Reject,
+
+    /// Drop the element, such that the caller doesn't need to handle the full pool.
+    DropIncoming,
+
+    /// Drop the element that has been idle in the pool for the longest time, and place the returned
+    /// element in its position instead, such that the pool keeps the most recently used elements.
+    EvictOldest,
+}
+
+/// What a checkout via `SyncPool::get_with_policy` does when the pool can't offer an element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FallbackPolicy {
+    /// Create a new element with the pool's builder, same as `get`.
+    Allocate,
+
+    /// Give up right away and yield `None`, e.g. on a request path that must not allocate.
+    Fail,
+
+    /// Wait until an element is returned to the pool or the timeout expires, and then yield `None`,
+    /// e.g. on a background task that can block. Only `PoolHandle::get_with_policy` waits, with the
+    /// pool unlocked in the meantime; `SyncPool::get_with_policy` holds the pool borrowed, such that
+    /// nothing can be returned while it waited, so it gives up right away same as `Fail`.
+    Wait(Duration),
+}
+
+/// The element builder panicked while creating an element on the fly, see `SyncPool::get_checked`
+/// and `SyncPool::set_catch_builder_panic`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BuilderPanic {
+    /// the pool whose builder panicked
+    pub pool: PoolId,
+
+    /// the message of the panic, if it carried a string
+    pub message: Option<String>,
+}
+
+impl fmt::Display for BuilderPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the element builder of pool {:?} panicked", self.pool)?;
+
+        match &self.message {
+            Some(message) => write!(f, ": {}", message),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for BuilderPanic {}
+
+/// An allocation made by the pool, reported to the hook set via `SyncPool::alloc_hook` along
+/// with the pool's tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AllocEvent {
+    /// `get` is about to create a new element since the pool can't offer one, with the label passed
+    /// to `get_labeled`, if any.
+    Fallback(Option<&'static str>),
+
+    /// `expand` has created the number of elements to fill the new buckets.
+    Expand(usize),
+
+    /// `refill` is about to create a new element to put into the pool.
+    Refill,
+}
+
+/// The hook receiving the pool's tag and each `AllocEvent`.
+type AllocHook = fn(&'static str, AllocEvent);
+
+/// The observer of the elements the pool drops, see `set_on_evict`.
+pub(crate) type EvictHook<T> = Box<dyn Fn(&T) + Send>;
+
+/// The outcome of a `put_tracked` call.
+pub enum PutOutcome<T> {
+    /// The element has been placed in the bucket at the index, possibly in place of an evicted
+    /// element under `FullPolicy::EvictOldest`.
+    Stored { bucket: usize },
+
+    /// The pool has dropped the element instead of storing it, i.e. the pool already held the
+    /// maximum number of idle elements (see `set_max_idle`), or it's full under
+    /// `FullPolicy::DropIncoming`.
+    Dropped,
+
+    /// The pool has refused the element, which is handed back, i.e. the pool is full under the
+    /// default `FullPolicy::Reject`, or the element is returned on a thread the pool isn't bound to
+    /// in the thread-affine mode.
+    Rejected(Box<T>),
+}
+
+impl<T> PutOutcome<T> {
+    /// The element handed back by the pool, i.e. what `put` returns.
+    fn into_rejected(self) -> Option<Box<T>> {
+        match self {
+            PutOutcome::Rejected(val) => Some(val),
+            _ => None,
+        }
+    }
+}
+
+impl<T> fmt::Debug for PutOutcome<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PutOutcome::Stored { bucket } => {
+                f.debug_struct("Stored").field("bucket", bucket).finish()
+            }
+            PutOutcome::Dropped => f.write_str("Dropped"),
+            PutOutcome::Rejected(_) => f.write_str("Rejected(..)"),
+        }
+    }
+}
+
+/// The outcome of a `try_expand` call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpandResult {
+    /// The pool has been expanded, and the number of elements added to the pool.
+    Grew(usize),
+
+    /// The pool is not allowed to expand, see `allow_expansion`.
+    Disabled,
+
+    /// The pool has already reached its expansion cap.
+    AtCap,
+
+    /// The expanded pool would exceed the memory budget by its `approx_bytes` estimate, see
+    /// `set_memory_budget`.
+    OverBudget,
+
+    /// Someone else is holding the write barrier, e.g. another thread is expanding the pool. It's up
+    /// to the caller to try again later, e.g. once the expansion has made room.
+    InProgress,
+
+    /// There are visitors that haven't left the pool yet. It's up to the caller to try again later.
+    Contended,
+}
+
+/// The snapshot of a pool's figures taken by `SyncPool::snapshot`. See the getters of `SyncPool` (and
+/// `PoolState`) for the meaning of each figure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolSnapshot {
+    pub capacity: usize,
+    /// The number of elements in the pool, i.e. `PoolState::len`.
+    pub len: usize,
+    pub outstanding_count: usize,
+    pub peak_outstanding: usize,
+    pub miss_count: usize,
+    pub fallback_count: usize,
+    pub misconfig_count: usize,
+    pub at_cap_count: usize,
+    pub idle_drop_count: usize,
+    pub eviction_count: usize,
+    pub reset_failure_count: usize,
+    pub checkout_count: usize,
+    pub return_count: usize,
+}
+
+pub(crate) struct VisitorGuard<'a>(&'a HandshakeUsize);
+
+/// The visitor counter and the write barrier form a handshake between the visitors and the writer
+/// (i.e. `expand`), where a visitor increments the counter and then checks the barrier, while the
+/// writer raises the barrier and then checks the counter. This is a store-then-load pattern on both
+/// sides, which is only guaranteed to have at least one side observe the other's store if all four
+/// operations are `SeqCst`; with weaker orderings, both sides could read the stale values and enter
+/// the pool at the same time. Leaving the pool (i.e. decrementing the counter) only needs `Release`,
+/// such that everything the visitor did happens-before the writer's acquiring `SeqCst` exchange on
+/// the counter.
 impl<'a> VisitorGuard<'a> {
-    fn register(base: &'a (AtomicUsize, AtomicBool), get: bool) -> Option<Self> {
-        let mut count = 8;
+    fn register(
+        base: &'a (HandshakeUsize, HandshakeBool),
+        get: bool,
+        spin: SpinConfig,
+    ) -> Option<Self> {
+        let mut count = spin.offset;
 
-        // wait if the underlying storage is in protection mode
-        while base.1.load(Ordering::Relaxed) {
+        loop {
+            // register first, then check if the underlying storage is in protection mode
+            base.0.fetch_add(1, Ordering::SeqCst);
+            if !base.1.load(Ordering::SeqCst) {
+                return Some(VisitorGuard(&base.0));
+            }
+
+            // the barrier is raised, back off
+            base.0.fetch_sub(1, Ordering::Release);
             if get {
                 return None;
             }
 
-            cpu_relax(count);
+            // wait for the barrier to be lowered
+            while base.1.load(Ordering::Relaxed) {
+                cpu_relax(count);
 
-            if count > 4 {
-                count -= 1;
+                if count > spin.cap {
+                    count -= 1;
+                }
             }
         }
+    }
+}
+
+impl<'a> Drop for VisitorGuard<'a> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Release);
+    }
+}
+
+/// The exclusive access to the pool, where the write barrier is raised and all visitors have left.
+/// Dropping the guard restores the visitor counter and lowers the barrier, also when unwinding, and
+/// wakes the tasks waiting for the barrier to be lowered (e.g. a pending `PoolHandle::put_async`).
+struct ExclusiveGuard<'a>(&'a (HandshakeUsize, HandshakeBool), &'a Waiters);
+
+impl<'a> ExclusiveGuard<'a> {
+    fn acquire(base: &'a (HandshakeUsize, HandshakeBool), waiters: &'a Waiters) -> Self {
+        // wait for the barrier owned by someone else to be lowered, then raise it ourselves
+        while base
+            .1
+            .compare_exchange_weak(false, true, Ordering::SeqCst, Ordering::Relaxed)
+            .is_err()
+        {
+            thread::yield_now();
+        }
+
+        // then wait for all visitors to leave
+        while base
+            .0
+            .compare_exchange(1, 0, Ordering::SeqCst, Ordering::Relaxed)
+            .is_err()
+        {
+            cpu_relax(2);
+            thread::yield_now();
+        }
+
+        ExclusiveGuard(base, waiters)
+    }
+
+    /// Same as `acquire`, but give up with `InProgress` if someone else is holding the barrier, or
+    /// with `Contended` if the visitors don't leave right away and we shall not `block`.
+    fn try_acquire(
+        base: &'a (HandshakeUsize, HandshakeBool),
+        waiters: &'a Waiters,
+        block: bool,
+    ) -> Result<Self, ExpandResult> {
+        // raise the write barrier now, if someone has already raised the flag to indicate the
+        // intention to write, let me go away.
+        if base
+            .1
+            .compare_exchange_weak(false, true, Ordering::SeqCst, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(ExpandResult::InProgress);
+        }
 
-        base.0.fetch_add(1, Ordering::AcqRel);
+        // busy waiting ... for all visitors to leave
+        let mut count: usize = 8;
+        loop {
+            match base
+                .0
+                .compare_exchange(1, 0, Ordering::SeqCst, Ordering::Relaxed)
+            {
+                Ok(_) => return Ok(ExclusiveGuard(base, waiters)),
+                Err(_) => {
+                    cpu_relax(2);
+                    count = count.saturating_sub(1);
 
-        Some(VisitorGuard(&base.0))
+                    if count < 4 {
+                        thread::yield_now();
+                    } else if !block {
+                        // the visitors are still in, lower the barrier we've raised and leave
+                        base.1.store(false, Ordering::Release);
+                        waiters.wake_all();
+                        return Err(ExpandResult::Contended);
+                    }
+                }
+            }
+        }
     }
 }
 
-impl<'a> Drop for VisitorGuard<'a> {
+impl<'a> Drop for ExclusiveGuard<'a> {
     fn drop(&mut self) {
-        self.0.fetch_sub(1, Ordering::AcqRel);
+        // a visitor may have briefly registered (and will back off) in the meantime, so don't
+        // overwrite its count.
+        self.0 .0.fetch_add(1, Ordering::Release);
+        self.0 .1.store(false, Ordering::Release);
+        self.1.wake_all();
     }
 }
 
+/// The statistics counters of a pool. They are kept behind an `Arc`, such that the pool registry
+/// can read them through a weak reference without keeping the pool alive.
+#[derive(Default)]
+pub(crate) struct PoolCounters {
+    /// the number of elements the pool can hold, mirrored here for the registry
+    pub(crate) capacity: AtomicUsize,
+
+    /// the number of times `get` failed to find an in-store struct to offer and created a new one,
+    /// reset after each expansion unless configured otherwise
+    pub(crate) miss_count: AtomicUsize,
+
+    /// the number of elements created on the fly because the pool couldn't offer one, this counter
+    /// is never reset
+    pub(crate) fallback_count: AtomicUsize,
+
+    /// the number of times we were asked to expand the pool while the expansion is disabled
+    pub(crate) misconfig_count: AtomicUsize,
+
+    /// the number of times we were asked to expand the pool while it has already reached the cap
+    pub(crate) at_cap_count: AtomicUsize,
+
+    /// the live `outstanding_count`, i.e. up by every checkout and down by every return, starting
+    /// from the vacancies of the pool; it may be driven below 0 by the returns of foreign elements
+    pub(crate) outstanding: AtomicIsize,
+
+    /// the highest `outstanding_count` observed at a checkout, since creation or `reset_peak`
+    pub(crate) peak_outstanding: AtomicUsize,
+
+    /// the number of returned elements dropped because the pool already held `max_idle` elements
+    pub(crate) idle_drop_count: AtomicUsize,
+
+    /// the number of idle elements dropped to make room for the returned ones, see `FullPolicy`
+    pub(crate) eviction_count: AtomicUsize,
+
+    /// the number of returned elements dropped because their async reset has failed
+    pub(crate) reset_failure_count: AtomicUsize,
+
+    /// the number of elements handed out, including the ones created on the fly
+    pub(crate) checkout_count: AtomicUsize,
+
+    /// the number of elements taken back into the pool
+    pub(crate) return_count: AtomicUsize,
+
+    /// the number of panics of the element builder caught while creating an element on the fly
+    pub(crate) builder_panic_count: AtomicUsize,
+}
+
 pub struct SyncPool<T> {
     /// The slots storage
     slots: Vec<Bucket2<T>>,
 
+    /// the identity of the pool
+    id: PoolId,
+
     /// the next bucket to try
     curr: (AtomicUsize, AtomicUsize),
 
+    /// the bucket where the last successful `get` checked out an element, which is likely to have
+    /// a vacancy for the next `put`
+    last_checkout: AtomicUsize,
+
+    /// the most recently returned positions as `bucket * SLOT_CAP + pos + 1` (0 for none), and the
+    /// number of positions ever recorded, for `CheckoutOrder::Lifo`
+    recent: ([AtomicUsize; RECENT_CAP], AtomicUsize),
+
     /// First node: how many threads are concurrently accessing the struct:
     ///   0   -> updating the `slots` field;
     ///   1   -> no one is using the pool;
@@ -64,37 +636,172 @@ pub struct SyncPool<T> {
     /// Second node: write barrier:
     ///   true  -> write barrier raised
     ///   false -> no write barrier
-    visitor_counter: (AtomicUsize, AtomicBool),
+    visitor_counter: (HandshakeUsize, HandshakeBool),
 
-    /// the number of times we failed to find an in-store struct to offer
-    miss_count: AtomicUsize,
+    /// if the pool has been poisoned, i.e. it shall neither hand out nor take back any element, see
+    /// `poison`
+    poisoned: AtomicBool,
+
+    /// if the next `get` that has to fall back shall fill up the pool instead, see
+    /// `set_prefill_on_first_fault`
+    prefill_armed: AtomicBool,
+
+    /// the statistics counters, shared with the weak handle held by the pool registry
+    stats: Arc<PoolCounters>,
+
+    /// the maximum number of idle elements the pool will hold, `usize::MAX` for no limit
+    max_idle: usize,
+
+    /// the maximum number of buckets `get` or `put` will probe before giving up, at least 1
+    max_probe: usize,
+
+    /// the `approx_bytes` an expansion may not push the pool past, `usize::MAX` for no limit
+    memory_budget: usize,
+
+    /// the number of buckets the auto shrinking won't release the pool below, at least 1
+    min_buckets: usize,
+
+    /// what `put` does when it can't find a vacancy
+    full_policy: FullPolicy,
+
+    /// the sequence number of the last `put`, which stamps the positions for `FullPolicy::EvictOldest`
+    put_seq: AtomicU64,
 
     /// if we allow expansion of the pool
     configure: AtomicUsize,
 
-    /// the handle to be invoked before putting the struct back
-    reset_handle: Option<fn(&mut T)>,
+    /// the busy-waiting settings when a visitor runs into the write barrier
+    spin: SpinConfig,
+
+    /// the handle to be invoked before putting the struct back, only replaced under `&mut self`
+    reset_handle: Option<ResetHandle<T>>,
+
+    /// the hook to be invoked when `get` falls back to creating a new element
+    fallback_hook: Option<fn(Option<&'static str>)>,
+
+    /// the tag of the pool and the hook to report the allocations to, see `alloc_hook`
+    alloc_hook: Option<(&'static str, AllocHook)>,
+
+    /// the observer to be invoked right before the pool drops an element, see `set_on_evict`
+    on_evict: Option<EvictHook<T>>,
+
+    /// the handle to be awaited by `put_async` before putting the struct back, see
+    /// `set_async_reset`
+    async_reset: Option<AsyncResetHandle<T>>,
+
+    /// the pending streams waiting for elements to be returned
+    waiters: Waiters,
+
+    /// the thread the pooled elements are bound to, `None` unless in the thread-affine mode
+    owner: Option<ThreadId>,
+
+    /// the auto shrinking policy driven by `maybe_shrink`, `None` if not enabled
+    auto_shrink: Option<AutoShrink>,
+
+    /// the state of the reproducible bucket sequence, `None` unless in the deterministic mode
+    #[cfg(feature = "deterministic")]
+    sequence: Option<AtomicU64>,
+
+    /// the rolling window of the faults, driven by `tick`
+    #[cfg(feature = "fault-window")]
+    fault_window: FaultWindow,
+
+    /// the durations of the reset handle invocations, see `reset_duration_histogram`
+    #[cfg(feature = "reset-timing")]
+    reset_durations: ResetDurations,
+
+    /// the hooks of the pool running empty and being refilled, see `on_empty`
+    #[cfg(feature = "empty-events")]
+    empty_events: EmptyEvents,
+
+    /// the maximum lifetime of the elements, whose creation times are kept by the buckets, see
+    /// `set_max_lifetime`
+    #[cfg(feature = "max-lifetime")]
+    max_lifetime: Option<Duration>,
+
+    /// the elements checked out and not yet returned, by the address of their box, see
+    /// `outstanding_checkouts`
+    #[cfg(feature = "debug-leaks")]
+    checkouts: Mutex<HashMap<usize, CheckoutInfo>>,
 
     /// The builder that will be tasked to create a new instance of the data when the pool is unable
     /// to render one.
     builder: ElemBuilder<T>,
 }
 
+/// The bucket storage of a dropped pool, obtained by `SyncPool::into_storage`, which keeps the
+/// allocation of the buckets (but none of the elements), such that a new pool can be built in it by
+/// `SyncPool::from_storage` instead of allocating the storage all over again.
+pub struct PoolStorage<T> {
+    slots: Vec<Bucket2<T>>,
+}
+
+impl<T> PoolStorage<T> {
+    /// The number of buckets the storage can hold without reallocating.
+    pub fn bucket_capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+}
+
 impl<T: Default> SyncPool<T> {
     /// Create a pool with default size of 64 pre-allocated elements in it.
     pub fn new() -> Self {
         Self::make_pool(POOL_SIZE, ElemBuilder::Default(Default::default))
     }
 
-    /// Create a `SyncPool` with pre-defined number of elements. Note that we will round-up
-    /// the size such that the total number of elements in the pool will mod to 8.
+    /// Create a `SyncPool` with pre-defined number of elements. Note that the pool is made of
+    /// buckets of `SLOT_CAP` elements, so we will round up the size to a multiple of `SLOT_CAP`,
+    /// such that the pool can hold at least `size` elements, e.g. a `size` of 12 will yield a pool
+    /// with 16 elements, and a `size` of 0 will yield a pool of 1 bucket, i.e. 8 elements.
     pub fn with_size(size: usize) -> Self {
-        let mut pool_size = size / SLOT_CAP;
-        if pool_size < 1 {
-            pool_size = 1
-        }
+        Self::make_pool(buckets_for(size), ElemBuilder::Default(Default::default))
+    }
+
+    /// Create a `SyncPool` with exactly `buckets` buckets, i.e. `buckets * SLOT_CAP` elements, which
+    /// bypasses the object-count rounding of `with_size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buckets` is 0, since a pool must contain at least 1 bucket.
+    pub fn with_exact_buckets(buckets: usize) -> Self {
+        assert!(buckets > 0, "a SyncPool must contain at least 1 bucket");
+        Self::make_pool(buckets, ElemBuilder::Default(Default::default))
+    }
+
+    /// Create a `SyncPool` the same way as `with_size`, but in the bucket storage of a previous pool
+    /// (see `into_storage`), e.g. when rotating to a newly configured pool that has grown to many
+    /// buckets. The storage is only reallocated if it can't hold the buckets of the new pool.
+    pub fn from_storage(storage: PoolStorage<T>, size: usize) -> Self {
+        Self::make_pool_in(
+            storage.slots,
+            buckets_for(size),
+            ElemBuilder::Default(Default::default),
+            true,
+        )
+    }
 
-        Self::make_pool(pool_size, ElemBuilder::Default(Default::default))
+    /// Create a `SyncPool` at its maximum size right away, i.e. `MAX_BUCKETS` buckets with every
+    /// slot pre-filled, for latency-critical services where the pool shall never grow at runtime.
+    /// The expansion is disabled as usual, and `capacity` tells the number of elements created.
+    pub fn with_max_capacity() -> Self {
+        Self::make_pool(MAX_BUCKETS, ElemBuilder::Default(Default::default))
+    }
+
+    /// Same as `with_size`, but the pool starts empty, and is filled up (once) by the first `get`
+    /// that can't be served, see `SyncPool::set_prefill_on_first_fault`. This saves the startup
+    /// cost of the pools that may never be used, while the pools that are warm up once the traffic
+    /// arrives.
+    pub fn with_size_lazy(size: usize) -> Self {
+        let buckets = buckets_for(size);
+        let mut pool = Self::make_pool_in(
+            Vec::with_capacity(buckets),
+            buckets,
+            ElemBuilder::Default(Default::default),
+            false,
+        );
+
+        pool.set_prefill_on_first_fault(true);
+        pool
     }
 }
 
@@ -142,15 +849,10 @@ impl<T> SyncPool<T> {
     /// Create a `SyncPool` with pre-defined number of elements and a packer handler. The `builder`
     /// handler shall essentially function the same way as in the `with_builder`, that it shall take
     /// the responsibility to create and initialize the element, and return the instance at the end
-    /// of the `builder` closure. Note that we will round-up the size such that the total number of
-    /// elements in the pool will mod to 8.
+    /// of the `builder` closure. Same as `with_size`, we will round up the size to a multiple of
+    /// `SLOT_CAP`, with at least 1 bucket in the pool.
     pub fn with_builder_and_size(size: usize, builder: fn() -> T) -> Self {
-        let mut pool_size = size / SLOT_CAP;
-        if pool_size < 1 {
-            pool_size = 1
-        }
-
-        Self::make_pool(pool_size, ElemBuilder::Builder(builder))
+        Self::make_pool(buckets_for(size), ElemBuilder::Builder(builder))
     }
 
     /// Create a pool with default size of 64 pre-allocated elements in it, which will use the `packer`
@@ -196,433 +898,2886 @@ impl<T> SyncPool<T> {
     /// handler shall essentially function the same way as in `with_packer`, that it shall take the
     /// responsibility to initialize all the fields of a placeholder struct on the heap, otherwise
     /// the element returned by the pool will be essentially undefined, unless all the struct's
-    /// fields can be represented by a 0 value. In addition, we will round-up the size such that
-    /// the total number of elements in the pool will mod to 8.
+    /// fields can be represented by a 0 value. In addition, same as `with_size`, we will round up
+    /// the size to a multiple of `SLOT_CAP`, with at least 1 bucket in the pool.
     pub fn with_packer_and_size(size: usize, packer: fn(Box<T>) -> Box<T>) -> Self {
-        let mut pool_size = size / SLOT_CAP;
-        if pool_size < 1 {
-            pool_size = 1
-        }
-
-        Self::make_pool(pool_size, ElemBuilder::Packer(packer))
+        Self::make_pool(buckets_for(size), ElemBuilder::Packer(packer))
     }
 
     /// Try to obtain a pre-allocated element from the pool. This method will always succeed even if
     /// the pool is empty or not available for anyone to access, and in this case, a new boxed-element
     /// will be created.
     pub fn get(&mut self) -> Box<T> {
-        // update user count
-        let guard = VisitorGuard::register(&self.visitor_counter, true);
-        if guard.is_none() {
-            return make_elem(&self.builder);
-        }
+        self.get_with(None)
+    }
 
-        // start from where we're left
-        let cap = self.slots.len();
-        let mut trials = cap;
-        let mut pos: usize = self.curr.0.load(Ordering::Acquire) % cap;
+    /// Same as `get`, but the work is bounded: every bucket is visited at most once, by a single
+    /// attempt each, before falling back to creating a new element. The buckets are visited from
+    /// the cursor on by a local index, instead of advancing the shared cursor on every probe as
+    /// `get` does, so concurrent callers may revisit a bucket under `get`, but never here.
+    ///
+    /// The trade-off is fairness: `get` spreads the concurrent callers over the buckets via the
+    /// shared cursor and retries a contended bucket a few times, while the callers here start from
+    /// the same bucket and give up on the contended buckets right away, so they may fall back more
+    /// often under contention. Use it where a predictable worst case (at most `bucket_count`, or
+    /// `set_max_probe`, probes) matters more than the hit rate.
+    pub fn get_wait_free(&mut self) -> Box<T> {
+        let val = self.try_get_wait_free();
 
-        loop {
-            // check this slot
-            let slot = &mut self.slots[pos];
+        if let Some(val) = self.hand_out(val, None) {
+            #[cfg(feature = "fault-window")]
+            self.record_get(false);
 
-            // try the access or move on
-            if let Ok(i) = slot.access(true) {
-                // try to checkout one slot
-                let checkout = slot.checkout(i);
-                slot.leave(i as u16);
+            return val;
+        }
 
-                /*            if slot.access(true) {
-                // try to checkout one slot
-                let checkout = slot.checkout();
-                slot.leave();*/
+        self.make_fallback(None)
+    }
 
-                if let Ok(val) = checkout {
-                    // now we're locked, get the val and update internal states
-                    self.curr.0.store(pos, Ordering::Release);
+    /// Same as `get`, but what happens when the pool can't offer an element is decided by the
+    /// `policy` for this call alone, see `FallbackPolicy`, such that the call sites with different
+    /// needs can share a pool. `get` and the other checkouts always fall back to creating a new
+    /// element; the `policy` passed here takes precedence over that for this call only.
+    ///
+    /// Yield `None` if no element can be obtained under the `policy`, which counts as a miss, but
+    /// not as a fallback allocation (nor does it invoke the `fallback_hook`).
+    pub fn get_with_policy(&mut self, policy: FallbackPolicy) -> Option<Box<T>> {
+        if let Some(val) = self.try_get() {
+            #[cfg(feature = "fault-window")]
+            self.record_get(false);
 
-                    // done
-                    return val;
-                }
+            return Some(val);
+        }
 
-                // failed to checkout, break and let the remainder logic to handle the rest
-                break;
-            }
+        // no one can return an element while the pool is borrowed here, see `FallbackPolicy::Wait`
+        if policy == FallbackPolicy::Allocate {
+            return Some(self.make_fallback(None));
+        }
 
-            // hold off a bit to reduce contentions
-            cpu_relax(SPIN_PERIOD);
+        self.count_miss();
+        None
+    }
 
-            // update to the next position now.
-            pos = self.curr.0.fetch_add(1, Ordering::AcqRel) % cap;
-            trials -= 1;
+    /// The checkout of `PoolHandle::get_with_policy` waiting for a return: yield an element if the
+    /// pool can offer one, or else register the `waker` to be woken by the next return.
+    pub(crate) fn try_get_or_register(&mut self, waker: &Waker) -> Option<Box<T>> {
+        if let Some(val) = self.try_get() {
+            #[cfg(feature = "fault-window")]
+            self.record_get(false);
 
-            // we've finished 1 loop but not finding a value to extract, quit
-            if trials == 0 {
-                break;
-            }
+            return Some(val);
         }
 
-        // make sure our guard has been returned if we want the correct visitor count
-        drop(guard);
-        self.miss_count.fetch_add(1, Ordering::Relaxed);
-
-        // create a new object
-        make_elem(&self.builder)
+        self.waiters.register(waker);
+        None
     }
 
-    /// Try to return an element to the `SyncPool`. If succeed, we will return `None` to indicate that
-    /// the value has been placed in an empty slot; otherwise, we will return `Option<Box<T>>` such
-    /// that the caller can decide if the element shall be just discarded, or try put it back again.
-    pub fn put(&mut self, val: Box<T>) -> Option<Box<T>> {
-        // update user count
-        let _guard = VisitorGuard::register(&self.visitor_counter, false);
+    /// Account for a checkout that yields `None` under its `FallbackPolicy`.
+    pub(crate) fn count_miss(&mut self) {
+        self.stats.miss_count.fetch_add(1, Ordering::Relaxed);
 
-        // start from where we're left
-        let cap = self.slots.len();
-        let mut trials = 2 * cap;
-        let mut pos: usize = self.curr.1.load(Ordering::Acquire) % cap;
+        #[cfg(feature = "fault-window")]
+        self.record_get(true);
+    }
 
-        loop {
-            // check this slot
-            let slot = &mut self.slots[pos];
+    /// Same as `get`, but if the element builder panics while creating a new element, the panic is
+    /// caught and handed back as a `BuilderPanic` carrying the pool's identity and the panic message,
+    /// instead of unwinding through the caller. The caught panics are counted by
+    /// `builder_panic_count`. The panic hook still runs (and prints the panic by default), since the
+    /// panic is caught only after it has been raised.
+    ///
+    /// Catching costs a `catch_unwind` around the builder, which is paid on the fallback path only;
+    /// the checkouts served from the pool are not affected.
+    pub fn get_checked(&mut self) -> Result<Box<T>, BuilderPanic> {
+        if let Some(val) = self.try_get() {
+            #[cfg(feature = "fault-window")]
+            self.record_get(false);
 
-            // try the access or move on
-            if let Ok(i) = slot.access(false) {
-                // now we're locked, get the val and update internal states
-                self.curr.1.store(pos, Ordering::Release);
+            return Ok(val);
+        }
 
-                // put the value back and reset
-                slot.release(i, val, self.reset_handle);
-                slot.leave(i as u16);
+        self.try_make_fallback(None, true)
+    }
 
-                return None;
-            }
+    /// Same as `get`, but if the pool can't offer an element and a new one has to be created, the
+    /// fallback hook set via `fallback_hook` will receive the `label`, e.g. the name of the call
+    /// site, such that the call sites starving the pool can be found.
+    pub fn get_labeled(&mut self, label: &'static str) -> Box<T> {
+        self.get_with(Some(label))
+    }
 
-            /*            if slot.access(false) {
-                // now we're locked, get the val and update internal states
-                self.curr.1.store(pos, Ordering::Release);
+    /// Same as `get`, but if the pool can't offer an element, the new one is created by `make`
+    /// instead of the pool's builder, such that the construction can vary per checkout, e.g. by a
+    /// key the caller has at hand. Elements obtained this way can be `put` back as usual.
+    pub fn get_or_else<F>(&mut self, make: F) -> Box<T>
+    where
+        F: FnOnce() -> T,
+    {
+        if let Some(val) = self.try_get() {
+            #[cfg(feature = "fault-window")]
+            self.record_get(false);
 
-                // put the value back into the slot
-                slot.release(val, self.reset_handle.load(Ordering::Acquire));
-                slot.leave();
+            return val;
+        }
 
-                return true;
-            }*/
+        if let Some(val) = self.prefill_on_fault(None) {
+            return val;
+        }
 
-            // hold off a bit to reduce contentions
-            if trials < cap {
-                cpu_relax(SPIN_PERIOD);
-            } else {
-                thread::yield_now();
-            }
+        self.fall_back(None);
+        let val = Box::new(make());
 
-            // update states
-            pos = self.curr.1.fetch_add(1, Ordering::AcqRel) % cap;
-            trials -= 1;
+        #[cfg(feature = "debug-leaks")]
+        self.track_checkout(&val, None);
 
-            // we've finished 1 loop but not finding a value to extract, quit
-            if trials == 0 {
-                return Some(val);
-            }
-        }
+        val
     }
 
-    fn make_pool(size: usize, builder: ElemBuilder<T>) -> Self {
-        let mut pool = SyncPool {
-            slots: Vec::with_capacity(size),
-            curr: (AtomicUsize::new(0), AtomicUsize::new(0)),
-            visitor_counter: (AtomicUsize::new(1), AtomicBool::new(false)),
-            miss_count: AtomicUsize::new(0),
-            configure: AtomicUsize::new(0),
-            reset_handle: None,
-            builder,
-        };
+    fn get_with(&mut self, label: Option<&'static str>) -> Box<T> {
+        let val = self.checkout_slots().map(|(_, val, mark)| (val, mark));
+        if let Some(val) = self.hand_out(val, label) {
+            #[cfg(feature = "fault-window")]
+            self.record_get(false);
 
-        pool.add_slots(size, true);
-        pool
+            return val;
+        }
+
+        // create a new object
+        self.make_fallback(label)
     }
 
-    #[inline]
-    fn add_slots(&mut self, count: usize, fill: bool) {
-        let filler = if fill { Some(&self.builder) } else { None };
+    /// Create a new element with the pool's builder, since the pool can't offer one. If the builder
+    /// panics and `set_catch_builder_panic` is on, the panic is re-raised with the pool's identity.
+    fn make_fallback(&mut self, label: Option<&'static str>) -> Box<T> {
+        let catch = self.configure.load(Ordering::Relaxed) & CONFIG_CATCH_BUILDER_PANIC > 0;
 
-        for _ in 0..count {
-            // self.slots.push(Bucket::new(fill));
-            self.slots.push(Bucket2::new(filler));
+        match self.try_make_fallback(label, catch) {
+            Ok(val) => val,
+            Err(err) => panic!("{}", err),
         }
     }
 
-    fn update_config(&mut self, mask: usize, target: bool) {
-        let mut config = self.configure.load(Ordering::SeqCst);
+    /// Same as `make_fallback`, but if `catch` is set, a panic of the builder is caught and handed
+    /// back as `Err`. The failed fallback still counts as a miss, but not as a fallback allocation
+    /// nor a checkout, though the `fallback_hook` and the `alloc_hook` have been invoked by then.
+    fn try_make_fallback(
+        &mut self,
+        label: Option<&'static str>,
+        catch: bool,
+    ) -> Result<Box<T>, BuilderPanic> {
+        if let Some(val) = self.prefill_on_fault(label) {
+            return Ok(val);
+        }
 
-        while let Err(old) = self.configure.compare_exchange(
-            config,
-            config ^ mask,
-            Ordering::SeqCst,
-            Ordering::Relaxed,
-        ) {
-            if !((old & mask > 0) ^ target) {
-                // the configure already matches, we're done
-                return;
-            }
+        self.fall_back(label);
+        let val = if catch {
+            let builder = &self.builder;
 
-            config = old;
-        }
-    }
-}
+            panic::catch_unwind(AssertUnwindSafe(|| make_elem(builder))).map_err(|payload| {
+                self.stats.fallback_count.fetch_sub(1, Ordering::Relaxed);
+                self.stats.checkout_count.fetch_sub(1, Ordering::Relaxed);
+                self.stats.outstanding.fetch_sub(1, Ordering::Relaxed);
+                self.stats
+                    .builder_panic_count
+                    .fetch_add(1, Ordering::Relaxed);
 
-impl<T> Default for SyncPool<T>
-where
-    T: Default,
-{
-    fn default() -> Self {
-        SyncPool::new()
-    }
-}
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|msg| msg.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned());
 
-impl<T> Drop for SyncPool<T> {
-    fn drop(&mut self) {
-        self.slots.clear();
+                BuilderPanic {
+                    pool: self.id,
+                    message,
+                }
+            })?
+        } else {
+            make_elem(&self.builder)
+        };
 
-        // now drop the reset handle if it's not null
-        self.reset_handle.take();
+        #[cfg(feature = "debug-leaks")]
+        self.track_checkout(&val, label);
+
+        Ok(val)
     }
-}
 
-pub trait PoolState {
-    fn expansion_enabled(&self) -> bool;
+    /// Fill up the pool if `set_prefill_on_first_fault` has armed it and this is the first fault
+    /// since, and then check out one of the new elements.
+    fn prefill_on_fault(&mut self, label: Option<&'static str>) -> Option<Box<T>> {
+        if !self.prefill_armed.swap(false, Ordering::AcqRel) {
+            return None;
+        }
 
-    fn miss_count(&self) -> usize;
+        {
+            let _guard = ExclusiveGuard::acquire(&self.visitor_counter, &self.waiters);
+            let (builder, alloc_hook) = (&self.builder, self.alloc_hook);
 
-    fn capacity(&self) -> usize;
+            for bucket in self.slots.iter_mut() {
+                while bucket.len() < SLOT_CAP {
+                    if let Some((tag, hook)) = alloc_hook {
+                        hook(tag, AllocEvent::Refill);
+                    }
 
-    fn len(&self) -> usize;
+                    if bucket.place(make_elem(builder), 0, None, false).is_err() {
+                        break;
+                    }
 
-    fn is_empty(&self) -> bool {
-        self.len() == 0
+                    // the vacancy is no longer counted as checked out
+                    self.stats.outstanding.fetch_sub(1, Ordering::Relaxed);
+                }
+            }
+
+            #[cfg(feature = "empty-events")]
+            self.recount_idle();
+        }
+
+        let val = self.checkout_slots().map(|(_, val, mark)| (val, mark));
+        self.hand_out(val, label)
     }
-}
 
-impl<T> PoolState for SyncPool<T> {
-    fn expansion_enabled(&self) -> bool {
-        let configure = self.configure.load(Ordering::SeqCst);
-        configure & CONFIG_ALLOW_EXPANSION > 0
+    /// Account for an element about to be created on the fly because the pool can't offer one.
+    fn fall_back(&mut self, label: Option<&'static str>) {
+        self.stats.miss_count.fetch_add(1, Ordering::Relaxed);
+        self.stats.fallback_count.fetch_add(1, Ordering::Relaxed);
+        self.stats.checkout_count.fetch_add(1, Ordering::Relaxed);
+        self.update_peak();
+
+        if let Some(hook) = self.fallback_hook {
+            hook(label);
+        }
+
+        self.report_alloc(AllocEvent::Fallback(label));
+
+        #[cfg(feature = "fault-window")]
+        self.record_get(true);
     }
 
-    fn miss_count(&self) -> usize {
-        self.miss_count.load(Ordering::Acquire)
+    /// Count a `get` call in the current tick of the fault window.
+    #[cfg(feature = "fault-window")]
+    #[inline]
+    fn record_get(&self, fault: bool) {
+        let window = &self.fault_window;
+        let (gets, faults) = &window.ticks[window.curr.load(Ordering::Relaxed) % FAULT_WINDOW];
+
+        gets.fetch_add(1, Ordering::Relaxed);
+        if fault {
+            faults.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
-    fn capacity(&self) -> usize {
-        self.slots.len() * SLOT_CAP
+    /// Report an allocation to the hook set via `alloc_hook`, if any.
+    #[inline]
+    fn report_alloc(&self, event: AllocEvent) {
+        if let Some((tag, hook)) = self.alloc_hook {
+            hook(tag, event);
+        }
     }
 
-    fn len(&self) -> usize {
-        self.slots
-            .iter()
-            .fold(0, |sum, item| sum + item.size_hint())
+    /// Try to obtain a pre-allocated element from the pool, and return `None` if the pool is empty
+    /// or not available for anyone to access.
+    ///
+    /// The `slots` (including `cap`) are only read after the visitor has registered and found the
+    /// barrier lowered. A writer resizing the `slots` (e.g. `expand`) raises the barrier first and
+    /// then waits for the visitor count to drop to none, and by the `SeqCst` handshake (see
+    /// `VisitorGuard`), either the writer waits for us, or we see the barrier and back off. Hence
+    /// the `slots` can't be reallocated while we hold `cap` or index into it, and the same holds
+    /// for every other access to the `slots` made under a `VisitorGuard`.
+    pub(crate) fn try_get(&mut self) -> Option<Box<T>> {
+        self.try_get_at().map(|(_, val)| val)
     }
-}
 
-pub trait PoolManager<T> {
-    fn reset_handle(&mut self, handle: fn(&mut T)) -> &mut Self;
-    fn allow_expansion(&mut self, allow: bool) -> &mut Self;
-    fn expand(&mut self, additional: usize, block: bool) -> bool;
-    fn refill(&mut self, count: usize) -> usize;
-}
+    /// Same as `try_get`, along with the bucket the element has been checked out from, such that an
+    /// unused element can be put back where it was, see `undo_checkout`.
+    pub(crate) fn try_get_at(&mut self) -> Option<(usize, Box<T>)> {
+        let val = self.checkout_slots();
+        let idx = val.as_ref().map_or(0, |&(idx, _, _)| idx);
 
-/// The pool manager that provide many useful utilities to keep the SyncPool close to the needs of
-/// the caller program.
-impl<T> PoolManager<T> for SyncPool<T> {
-    /// Set or update the reset handle. If set, the reset handle will be invoked every time an element
-    /// has been returned back to the pool (i.e. calling the `put` method), regardless of if the element
-    /// is created by the pool or not.
-    fn reset_handle(&mut self, handle: fn(&mut T)) -> &mut Self {
-        // busy waiting ... for the first chance a barrier owned by someone else is lowered
-        let mut count: usize = 8;
-        let timeout = Instant::now().add(Duration::from_millis(16));
+        self.hand_out(val.map(|(_, val, mark)| (val, mark)), None)
+            .map(|val| (idx, val))
+    }
 
-        loop {
-            match self.visitor_counter.1.compare_exchange(
-                false,
-                true,
-                Ordering::SeqCst,
-                Ordering::Relaxed,
-            ) {
-                Ok(_) => break,
-                Err(_) => {
-                    cpu_relax(count);
+    /// The common tail of the checkouts from the slots, past the slot locks: fire the events, and
+    /// prepare the element (if any) to be handed out, i.e. reset it if it has been stored dirty.
+    /// An element that has outlived the maximum lifetime is dropped instead, and no element is
+    /// handed out, such that the checkouts that can't allocate come back empty.
+    #[cfg_attr(not(feature = "debug-leaks"), allow(unused_variables))]
+    fn hand_out(
+        &mut self,
+        val: Option<(Box<T>, Mark)>,
+        label: Option<&'static str>,
+    ) -> Option<Box<T>> {
+        #[cfg(feature = "empty-events")]
+        self.note_checkout(val.is_some());
 
-                    // update the counter (and the busy wait period)
-                    count -= 1;
+        let (mut val, mark) = val?;
 
-                    if count < 4 {
-                        // yield the thread for later try
-                        thread::yield_now();
-                    } else if Instant::now() > timeout {
-                        // don't block for more than 16ms
-                        return self;
-                    }
-                }
-            }
+        #[cfg(feature = "max-lifetime")]
+        if mark == Mark::Expired {
+            notify_evict(&self.on_evict, &val);
+            self.stats.outstanding.fetch_sub(1, Ordering::Relaxed);
+            return None;
         }
 
-        self.reset_handle.replace(handle);
+        self.stats.checkout_count.fetch_add(1, Ordering::Relaxed);
 
-        self.visitor_counter.1.store(false, Ordering::SeqCst);
-        self
-    }
+        if mark == Mark::Dirty {
+            if let Some(handle) = self.reset_handle.as_mut() {
+                #[cfg(feature = "reset-timing")]
+                let start = Instant::now();
 
-    /// Set or update the settings that if we will allow the `SyncPool` to be expanded.
-    fn allow_expansion(&mut self, allow: bool) -> &mut Self {
-        if !(self.expansion_enabled() ^ allow) {
-            // not flipping the configuration, return
-            return self;
+                handle.as_fn_mut()(&mut val);
+
+                #[cfg(feature = "reset-timing")]
+                self.reset_durations.record(start.elapsed());
+            }
         }
 
-        self.update_config(CONFIG_ALLOW_EXPANSION, allow);
-        self
+        #[cfg(feature = "debug-leaks")]
+        self.track_checkout(&val, label);
+
+        Some(val)
     }
 
-    /// Try to expand the `SyncPool` and add more elements to it. Usually invoke this API only when
-    /// the caller is certain that the pool is under pressure, and that a short block to the access
-    /// of the pool won't cause serious issues, since the function will block the current caller's
-    /// thread until it's finished (i.e. get the opportunity to raise the writer's barrier and wait
-    /// everyone to leave).
-    ///
-    /// If we're unable to expand the pool, it's due to one of the following reasons: 1) someone has
-    /// already raised the writer's barrier and is likely modifying the pool, we will leave immediately,
-    /// and it's up to the caller if they want to try again; 2) we've waited too long but still couldn't
-    /// obtain an exclusive access to the pool, and similar to reason 1), we will quit now.
-    fn expand(&mut self, additional: usize, block: bool) -> bool {
-        // if the pool isn't allowed to expand, just return
-        if !self.expansion_enabled() {
-            return false;
+    fn checkout_slots(&mut self) -> Option<(usize, Box<T>, Mark)> {
+        if self.foreign_thread() || self.is_poisoned() {
+            return None;
         }
 
-        // if exceeding the upper limit, quit
-        if self.slots.len() > EXPANSION_CAP {
-            return false;
-        }
+        // update user count
+        let guard = VisitorGuard::register(&self.visitor_counter, true, self.spin)?;
 
-        // raise the write barrier now, if someone has already raised the flag to indicate the
-        // intention to write, let me go away.
-        if self
-            .visitor_counter
-            .1
-            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Acquire)
-            .is_err()
-        {
-            return false;
-        }
+        // start from where we're left
+        let cap = self.slots.len();
+        let sticky = self.cursor_policy() == CursorPolicy::Sticky;
 
-        // busy waiting ... for all visitors to leave
-        let mut count: usize = 8;
-        let safe = loop {
-            match self
-                .visitor_counter
-                .0
-                .compare_exchange(1, 0, Ordering::SeqCst, Ordering::Relaxed)
-            {
-                Ok(_) => break true,
-                Err(_) => {
-                    cpu_relax(2);
-                    count -= 1;
+        // the deterministic mode only visits the buckets in the reproducible sequence
+        #[cfg(feature = "deterministic")]
+        let fast_path = self.sequence.is_none();
+        #[cfg(not(feature = "deterministic"))]
+        let fast_path = true;
 
-                    if count < 4 {
-                        thread::yield_now();
-                    } else if !block {
-                        break false;
-                    }
-                }
+        // in the LIFO order, the most recently returned elements go first
+        if fast_path && self.configure.load(Ordering::Relaxed) & CONFIG_LIFO > 0 {
+            if let Some((idx, val, mark)) = pop_recent(&self.recent, &mut self.slots) {
+                self.last_checkout.store(idx, Ordering::Relaxed);
+                self.update_peak();
+
+                return Some((idx, val, mark));
             }
+        }
+
+        let scan = Scan {
+            cursor: &self.curr.0,
+            sticky,
+            trials: cap.min(self.max_probe),
+            #[cfg(feature = "deterministic")]
+            sequence: &self.sequence,
         };
 
-        if safe {
-            // update the slots by pushing `additional` slots
-            self.add_slots(additional, true);
-            self.miss_count.store(0, Ordering::Release);
+        if let Some((pos, val, mark)) = scan.get(&mut self.slots) {
+            self.last_checkout.store(pos, Ordering::Relaxed);
+            self.update_peak();
+
+            // done
+            return Some((pos, val, mark));
         }
 
-        // update the internal states
-        self.visitor_counter.0.store(1, Ordering::SeqCst);
-        self.visitor_counter.1.store(false, Ordering::Release);
+        // make sure our guard has been returned if we want the correct visitor count
+        drop(guard);
 
-        safe
+        None
     }
 
-    /// Due to contentious access to the pool, sometimes the `put` action could not finish and return
-    /// the element to the pool successfully. Overtime, this could cause the number of elements in the
-    /// pool to dwell. This would only happen slowly if we're running a very contentious multithreading
-    /// program, but it surely could happen. If the caller detects such situation, they can invoke the
-    /// `refill` API and try to refill the pool with elements.
-    ///
-    /// We will try to refill as many elements as requested
-    fn refill(&mut self, additional: usize) -> usize {
-        let cap = self.capacity();
-        let empty_slots = cap - self.len();
-
-        if empty_slots == 0 {
-            return 0;
+    /// The checkout of `get_wait_free`, which visits every bucket at most once. The same visitor
+    /// handshake as in `try_get` applies.
+    fn try_get_wait_free(&mut self) -> Option<(Box<T>, Mark)> {
+        if self.foreign_thread() || self.is_poisoned() {
+            return None;
         }
 
-        let quota = if additional > empty_slots {
-            empty_slots
-        } else {
-            additional
-        };
-
-        let mut count = 0;
-        let timeout = Instant::now().add(Duration::from_millis(16));
+        let _guard = VisitorGuard::register(&self.visitor_counter, true, self.spin)?;
 
-        // try to put `quota` number of elements into the pool
-        while count < quota {
-            let mut val = make_elem(&self.builder);
-            let mut runs = 0;
+        let cap = self.slots.len();
+        let sticky = self.cursor_policy() == CursorPolicy::Sticky;
+        let start = self.curr.0.load(Ordering::Acquire) % cap;
 
-            // retry to put the allocated element into the pool.
-            while let Some(ret) = self.put(val) {
-                val = ret;
-                runs += 1;
+        for step in 0..cap.min(self.max_probe) {
+            let pos = (start + step) % cap;
+            let slot = &mut self.slots[pos];
 
-                // timeout
-                if Instant::now() > timeout {
-                    return count;
-                }
+            if let Ok(i) = slot.try_access(true) {
+                let checkout = slot.checkout(i);
+                let mark = slot.mark(i);
+                slot.leave(i as u16);
 
-                // check the pool length for every 4 failed attempts to put the element into the pool.
-                if runs % 4 == 0 && self.len() == cap {
-                    return count;
-                }
+                if let Ok(val) = checkout {
+                    // move the shared cursor once, instead of on every probe
+                    let next = if sticky { pos } else { pos + 1 };
+                    self.curr.0.store(next, Ordering::Release);
+                    self.last_checkout.store(pos, Ordering::Relaxed);
+                    self.update_peak();
 
-                // relax a bit
-                if runs > 8 {
-                    thread::yield_now();
-                } else {
-                    cpu_relax(runs / 2);
+                    return Some((val, mark));
                 }
             }
-
-            count += 1;
         }
 
-        count
+        None
     }
-}
 
-#[cfg(test)]
-mod pool_tests {
-    use super::*;
-    use std::vec;
+    /// Try to return an element to the `SyncPool`. If succeed, we will return `None` to indicate that
+    /// the value has been placed in an empty slot; otherwise, we will return `Option<Box<T>>` such
+    /// that the caller can decide if the element shall be just discarded, or try put it back again.
+    ///
+    /// If a maximum idle count has been set via `set_max_idle` and the pool already holds that many
+    /// idle elements, the value will be reset and dropped, and we will return `None` as well.
+    pub fn put(&mut self, val: Box<T>) -> Option<Box<T>> {
+        self.put_with(val, None, true, true)
+            .map_or_else(Some, PutOutcome::into_rejected)
+    }
 
-    struct BigStruct {
-        a: u32,
-        b: u32,
-        c: Vec<u8>,
+    /// Same as `put`, but tell exactly what has become of the element, e.g. for the metrics of the
+    /// returns. See `PutOutcome` for the details; the element is only handed back if rejected.
+    pub fn put_tracked(&mut self, val: Box<T>) -> PutOutcome<T> {
+        self.put_with(val, None, true, true)
+            .unwrap_or_else(PutOutcome::Rejected)
     }
 
-    impl BigStruct {
-        fn new() -> Self {
+    /// Same as `put`, but try the bucket at `bucket_hint` first, e.g. the bucket the element has been
+    /// checked out from (see `PutOutcome::Stored` or `get_bucket`), such that the elements don't
+    /// migrate across the buckets and stay warm in the cache of the threads using that bucket. If the
+    /// bucket has no vacancy (or the hint is out of bounds), the element is returned by the normal
+    /// scan. Unlike `put_bucket`, the element is only handed back if the whole pool is full.
+    pub fn put_to(&mut self, val: Box<T>, bucket_hint: usize) -> Option<Box<T>> {
+        self.put_with(val, Some(bucket_hint), true, true)
+            .map_or_else(Some, PutOutcome::into_rejected)
+    }
+
+    /// Same as `put`, but the reset handle will not be invoked on the returned element, even if one
+    /// has been set via `reset_handle`. Use this API only if the element is known to be clean, such
+    /// that the (possibly expensive) reset work can be saved.
+    pub fn put_no_reset(&mut self, val: Box<T>) -> Option<Box<T>> {
+        self.put_with(val, None, false, true)
+            .map_or_else(Some, PutOutcome::into_rejected)
+    }
+
+    /// Same as `put`, but instead of busy-waiting while the write barrier is raised by someone else,
+    /// hand the value back as `Err` immediately, such that the caller can retry later.
+    pub(crate) fn try_put(&mut self, val: Box<T>) -> Result<Option<Box<T>>, Box<T>> {
+        self.put_with(val, None, true, false)
+            .map(PutOutcome::into_rejected)
+    }
+
+    /// Return an element held by a guard (e.g. `Scope` or `UninitGuard`) when the guard drops. If
+    /// the current thread is unwinding from a panic and the panic reclaim is on (see
+    /// `set_panic_reclaim`), the reset handle runs under `catch_unwind`, since a panicking handle
+    /// would abort the process; an element the handle has panicked on is dropped instead of being
+    /// stored in whatever state it was left.
+    pub(crate) fn reclaim(&mut self, mut val: Box<T>) -> Option<Box<T>> {
+        let reclaim = self.configure.load(Ordering::Relaxed) & CONFIG_PANIC_RECLAIM > 0;
+
+        if !reclaim || !thread::panicking() {
+            return self.put(val);
+        }
+
+        // the element is stored dirty, and reset by the checkout handing it out next
+        if self.reset_timing() == ResetTiming::OnCheckout {
+            return self.put(val);
+        }
+
+        if let Some(handle) = self.reset_handle.as_mut() {
+            let handle = handle.as_fn_mut();
+
+            if panic::catch_unwind(AssertUnwindSafe(|| handle(&mut val))).is_err() {
+                self.drop_reset_failure(val);
+                return None;
+            }
+        }
+
+        self.put_no_reset(val)
+    }
+
+    fn put_with(
+        &mut self,
+        val: Box<T>,
+        bucket_hint: Option<usize>,
+        reset: bool,
+        wait: bool,
+    ) -> Result<PutOutcome<T>, Box<T>> {
+        let addr = &*val as *const T as usize;
+
+        // the poisoned pool takes nothing back, the element is likely as invalid as the others
+        let res = if self.is_poisoned() {
+            notify_evict(&self.on_evict, &val);
+            Ok(PutOutcome::Dropped)
+        } else {
+            // the elements are reset on the way out instead, and stored dirty until then
+            let dirty = reset && self.reset_timing() == ResetTiming::OnCheckout;
+            self.release_slots(val, bucket_hint, reset && !dirty, dirty, wait, true)
+        };
+
+        match &res {
+            Ok(PutOutcome::Stored { .. }) => {
+                self.stats.outstanding.fetch_sub(1, Ordering::Relaxed);
+                self.stats.return_count.fetch_add(1, Ordering::Relaxed);
+
+                #[cfg(feature = "debug-leaks")]
+                self.untrack_checkout(addr);
+
+                #[cfg(feature = "empty-events")]
+                self.note_return();
+            }
+            Ok(PutOutcome::Dropped) => self.let_go(addr),
+            _ => {}
+        }
+
+        res
+    }
+
+    /// Place `val` in a vacancy of the pool. The `FullPolicy` (and the strict put) only applies to
+    /// a `returned` element, a new one (e.g. of `refill`) is handed back instead if no vacancy is
+    /// found, such that it never takes the place of an idle element.
+    fn release_slots(
+        &mut self,
+        mut val: Box<T>,
+        bucket_hint: Option<usize>,
+        reset: bool,
+        dirty: bool,
+        wait: bool,
+        returned: bool,
+    ) -> Result<PutOutcome<T>, Box<T>> {
+        // refuse the elements returned on a thread the pool is not bound to
+        if self.foreign_thread() {
+            return Ok(PutOutcome::Rejected(val));
+        }
+
+        // update user count, or hand the value back if we shall not wait for the barrier
+        let _guard = match VisitorGuard::register(&self.visitor_counter, !wait, self.spin) {
+            Some(guard) => guard,
+            None => return Err(val),
+        };
+
+        // already holding enough idle elements, drop the value; counting them scans the buckets,
+        // hence only after the registration
+        if self.max_idle < usize::MAX && self.len() >= self.max_idle {
+            if let (true, Some(handle)) = (reset, self.reset_handle.as_mut()) {
+                #[cfg(feature = "reset-timing")]
+                let start = Instant::now();
+
+                handle.as_fn_mut()(&mut val);
+
+                #[cfg(feature = "reset-timing")]
+                self.reset_durations.record(start.elapsed());
+            }
+
+            notify_evict(&self.on_evict, &val);
+            self.stats.idle_drop_count.fetch_add(1, Ordering::Relaxed);
+            return Ok(PutOutcome::Dropped);
+        }
+
+        let cap = self.slots.len();
+        let sticky = self.cursor_policy() == CursorPolicy::Sticky;
+        let seq = self.next_stamp();
+        let mut reset = match self.reset_handle.as_mut() {
+            Some(handle) if reset => Some(handle.as_fn_mut()),
+            _ => None,
+        };
+
+        #[cfg(feature = "reset-timing")]
+        let timing = &self.reset_durations;
+        #[cfg(feature = "reset-timing")]
+        let mut timed = reset.as_mut().map(|handle| {
+            move |val: &mut T| {
+                let start = Instant::now();
+                handle(val);
+                timing.record(start.elapsed());
+            }
+        });
+        #[cfg(feature = "reset-timing")]
+        let mut reset = timed
+            .as_mut()
+            .map(|handle| handle as &mut dyn FnMut(&mut T));
+
+        // the deterministic mode only visits the buckets in the reproducible sequence
+        #[cfg(feature = "deterministic")]
+        let fast_path = self.sequence.is_none();
+        #[cfg(not(feature = "deterministic"))]
+        let fast_path = true;
+
+        // fast path: the bucket asked for by the caller, or else the bucket we checked out from most
+        // recently, which likely has a vacancy; try it once. The latter favors locality, hence it's
+        // skipped under `CursorPolicy::Advancing`, which spreads the returns over the buckets.
+        let (hint, fast_path) = match bucket_hint {
+            Some(idx) if idx < cap => (idx, true),
+            _ => (
+                self.last_checkout.load(Ordering::Relaxed) % cap,
+                fast_path && sticky,
+            ),
+        };
+        let slot = &mut self.slots[hint];
+
+        if fast_path {
+            if let Ok(i) = slot.try_access(false) {
+                slot.release(i, val, reset);
+                slot.stamp(i, seq);
+                slot.mark_dirty(i, dirty);
+                slot.leave(i as u16);
+                self.push_recent(hint, i);
+                self.waiters.wake_all();
+
+                #[cfg(feature = "empty-events")]
+                self.note_placed();
+
+                return Ok(PutOutcome::Stored { bucket: hint });
+            }
+        }
+
+        // start from where we're left
+        let scan = Scan {
+            cursor: &self.curr.1,
+            sticky,
+            trials: (2 * cap).min(self.max_probe),
+            #[cfg(feature = "deterministic")]
+            sequence: &self.sequence,
+        };
+        let probes = scan.trials;
+
+        // reborrow the reset, which is still needed for the eviction
+        let scan_reset = reset
+            .as_mut()
+            .map(|handle| &mut **handle as &mut dyn FnMut(&mut T));
+
+        let mut val = match scan.put(&mut self.slots, val, seq, scan_reset, dirty) {
+            Ok((bucket, i)) => {
+                self.push_recent(bucket, i);
+                self.waiters.wake_all();
+
+                #[cfg(feature = "empty-events")]
+                self.note_placed();
+
+                return Ok(PutOutcome::Stored { bucket });
+            }
+            Err(val) => val,
+        };
+
+        // we've finished the probes but not finding a vacancy
+        if !returned {
+            return Ok(PutOutcome::Rejected(val));
+        }
+
+        if self.full_policy == FullPolicy::EvictOldest {
+            match evict_oldest(&mut self.slots, val, seq, reset, dirty) {
+                Ok((bucket, evicted)) => {
+                    notify_evict(&self.on_evict, &evicted);
+                    self.stats.eviction_count.fetch_add(1, Ordering::Relaxed);
+                    return Ok(PutOutcome::Stored { bucket });
+                }
+                Err(v) => val = v,
+            }
+        }
+
+        if self.configure.load(Ordering::Relaxed) & CONFIG_STRICT_PUT > 0 {
+            panic!(
+                "SyncPool put can't find a vacancy after probing {} buckets, with {} of \
+                 {} elements in the pool",
+                probes,
+                self.len(),
+                self.capacity()
+            );
+        }
+
+        if self.full_policy == FullPolicy::DropIncoming {
+            notify_evict(&self.on_evict, &val);
+            return Ok(PutOutcome::Dropped);
+        }
+
+        Ok(PutOutcome::Rejected(val))
+    }
+
+    /// The identity of the pool, e.g. to tell which pool a `ReadGuard` has been obtained from. The
+    /// identity never changes during the lifetime of the pool, and isn't shared with any other pool,
+    /// including the ones rebuilt with `from_storage`.
+    pub fn id(&self) -> PoolId {
+        self.id
+    }
+
+    /// The number of buckets in the pool, including the ones added by `expand`. Together with
+    /// `get_bucket` and `put_bucket`, each bucket can be addressed as a sub-pool holding up to
+    /// `SLOT_CAP` elements, e.g. to map keys to fixed buckets in a sharded design.
+    pub fn bucket_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The bucket the next `get` starts its scan from, see `CursorPolicy`. The cursor of `put` is
+    /// separate, but both are moved together by `set_cursor`.
+    pub fn cursor(&self) -> usize {
+        self.curr.0.load(Ordering::Acquire) % self.slots.len()
+    }
+
+    /// Move the cursors of `get` and `put` to the bucket at `pos`, wrapped around the bucket count,
+    /// e.g. to restart the round robin after a `rebalance`, or to bias the scans toward a region of
+    /// the pool. This is a low-level knob for the load distribution: it affects the fairness among
+    /// the buckets, not the correctness, so any position is safe, and the cursors keep moving by
+    /// the `CursorPolicy` afterwards.
+    pub fn set_cursor(&mut self, pos: usize) {
+        let pos = pos % self.slots.len();
+        self.curr.0.store(pos, Ordering::Release);
+        self.curr.1.store(pos, Ordering::Release);
+    }
+
+    /// The state of the position `slot` in the bucket at `bucket`, read from the bucket's bitmap
+    /// without checking anything out, or `None` if either index is out of bounds. The state may
+    /// have changed by the time the call returns if others are using the pool, so it's meant for
+    /// instrumentation and heuristics (e.g. a scheduler picking a likely-occupied bucket), not as a
+    /// guarantee that a following `get_bucket` will succeed.
+    pub fn slot_state(&self, bucket: usize, slot: usize) -> Option<SlotState> {
+        self.slots.get(bucket)?.state_at(slot)
+    }
+
+    /// Try to check out an element from the bucket at `idx` only, without moving the bucket cursors.
+    /// Return `None` if `idx` is out of bounds, if the bucket is empty or contended, or if the write
+    /// barrier is raised. Unlike `get`, no new element will be created in any of these cases.
+    pub fn get_bucket(&mut self, idx: usize) -> Option<Box<T>> {
+        if self.foreign_thread() || self.is_poisoned() {
+            return None;
+        }
+
+        let val = {
+            let _guard = VisitorGuard::register(&self.visitor_counter, true, self.spin)?;
+            let slot = self.slots.get_mut(idx)?;
+
+            let i = slot.access(true).ok()?;
+            let checkout = slot.checkout(i);
+            let mark = slot.mark(i);
+            slot.leave(i as u16);
+
+            let val = checkout.ok()?;
+            self.update_peak();
+            (val, mark)
+        };
+
+        self.hand_out(Some(val), None)
+    }
+
+    /// Try to return an element to the bucket at `idx` only, without moving the bucket cursors. The
+    /// reset handle will be invoked same as `put`. Return `None` if the element has been placed in
+    /// the bucket; otherwise, i.e. if `idx` is out of bounds or the bucket is full (or contended),
+    /// the element is handed back to the caller.
+    pub fn put_bucket(&mut self, idx: usize, val: Box<T>) -> Option<Box<T>> {
+        #[cfg(feature = "debug-leaks")]
+        let addr = &*val as *const T as usize;
+
+        let res = self.release_to_bucket(idx, val, true);
+
+        if res.is_none() {
+            self.stats.return_count.fetch_add(1, Ordering::Relaxed);
+            self.stats.outstanding.fetch_sub(1, Ordering::Relaxed);
+
+            #[cfg(feature = "debug-leaks")]
+            self.untrack_checkout(addr);
+        }
+
+        res
+    }
+
+    /// Put back an element checked out by `try_get_at` from the bucket `idx` and left unused, e.g. by
+    /// a failed `lease`, as if it had never been checked out: it isn't reset, and counts neither as
+    /// a checkout nor as a return. It goes back to `idx`, or else to any vacancy if others have
+    /// taken that one in the meantime; should they have filled up the pool, it's dropped as an
+    /// idle drop, same as by the idle cap.
+    pub(crate) fn undo_checkout(&mut self, idx: usize, val: Box<T>) {
+        let addr = &*val as *const T as usize;
+
+        let stored = match self.release_to_bucket(idx, val, false) {
+            None => true,
+            Some(val) => match self.release_slots(val, None, false, false, true, false) {
+                Ok(PutOutcome::Stored { .. }) => true,
+                Ok(PutOutcome::Dropped) => false,
+                Ok(PutOutcome::Rejected(val)) | Err(val) => {
+                    notify_evict(&self.on_evict, &val);
+                    self.stats.idle_drop_count.fetch_add(1, Ordering::Relaxed);
+                    false
+                }
+            },
+        };
+
+        if !stored {
+            self.let_go(addr);
+            return;
+        }
+
+        self.stats.checkout_count.fetch_sub(1, Ordering::Relaxed);
+        self.stats.outstanding.fetch_sub(1, Ordering::Relaxed);
+
+        #[cfg(feature = "debug-leaks")]
+        self.untrack_checkout(addr);
+    }
+
+    /// Place `val` in the bucket at `idx`, reset (or stored dirty) if `reset` is set, or else as is.
+    fn release_to_bucket(&mut self, idx: usize, val: Box<T>, reset: bool) -> Option<Box<T>> {
+        if self.foreign_thread() || self.is_poisoned() {
+            return Some(val);
+        }
+
+        let on_return = reset && self.reset_timing() == ResetTiming::OnReturn;
+        let dirty = reset && !on_return;
+        let _guard = VisitorGuard::register(&self.visitor_counter, false, self.spin);
+        let seq = self.next_stamp();
+        let slot = match self.slots.get_mut(idx) {
+            Some(slot) => slot,
+            None => return Some(val),
+        };
+
+        match slot.access(false) {
+            Ok(i) => {
+                let reset = match self.reset_handle.as_mut() {
+                    Some(handle) if on_return => Some(handle.as_fn_mut()),
+                    _ => None,
+                };
+                slot.release(i, val, reset);
+                slot.stamp(i, seq);
+                slot.mark_dirty(i, dirty);
+                slot.leave(i as u16);
+                self.push_recent(idx, i);
+                self.waiters.wake_all();
+
+                #[cfg(feature = "empty-events")]
+                self.note_placed();
+
+                None
+            }
+            Err(()) => Some(val),
+        }
+    }
+
+    /// Drop the pool, including its idle elements, but keep the allocation of its buckets, which
+    /// can be reused by `from_storage`. The storage doesn't expose the buckets. The elements still
+    /// checked out of this pool are unaffected, and can be returned to the new pool later.
+    pub fn into_storage(mut self) -> PoolStorage<T> {
+        let mut slots = mem::take(&mut self.slots);
+
+        if self.foreign_thread() {
+            // same as dropping the pool, the elements must not be destroyed on this thread
+            slots.drain(..).for_each(mem::forget);
+        }
+
+        // dropping the buckets drops the idle elements
+        slots
+            .iter()
+            .flat_map(Bucket2::iter)
+            .for_each(|val| notify_evict(&self.on_evict, val));
+        slots.clear();
+        PoolStorage { slots }
+    }
+
+    /// Move the idle elements of this pool into `dst`, e.g. when rotating to a newly configured pool,
+    /// such that the new pool won't need to allocate its working set all over again. Return the
+    /// number of elements that have been placed in `dst`; the ones `dst` can't hold (i.e. beyond its
+    /// capacity or idle cap) are dropped. Elements still checked out from this pool are unaffected,
+    /// and can be returned to either pool later.
+    ///
+    /// Both pools are held under their write barriers for the whole move, so no bucket is skipped
+    /// because of contention. The elements are taken bucket by bucket in the bucket order of this
+    /// pool, go through `dst`'s reset handle (unless it runs on the checkouts instead), and fill the
+    /// vacancies of `dst` in its bucket order. The moved elements are neither checkouts of this
+    /// pool nor returns to `dst`: they fill `dst`'s vacancies the same way `refill` does, and leave
+    /// the `outstanding_count` and the peak of this pool untouched.
+    pub fn transfer_into(&mut self, dst: &mut SyncPool<T>) -> usize {
+        if self.foreign_thread() || dst.foreign_thread() {
+            return 0;
+        }
+
+        let _src_guard = ExclusiveGuard::acquire(&self.visitor_counter, &self.waiters);
+        let _dst_guard = ExclusiveGuard::acquire(&dst.visitor_counter, &dst.waiters);
+
+        let mut room = dst.max_idle.saturating_sub(dst.len());
+        let mut target = 0;
+        let mut count = 0;
+        let on_return = dst.reset_timing() == ResetTiming::OnReturn;
+        let evict_oldest = dst.full_policy == FullPolicy::EvictOldest;
+        let mut reset = match dst.reset_handle.as_mut() {
+            Some(handle) if on_return => Some(handle.as_fn_mut()),
+            _ => None,
+        };
+
+        for bucket in self.slots.iter_mut() {
+            for pos in 0..SLOT_CAP {
+                let dirty = bucket.is_dirty(pos);
+                let (mut val, born) = match bucket.take(pos) {
+                    Some((val, _, born)) => (val, born),
+                    None => continue,
+                };
+
+                // `dst` is full, the element will just be dropped here
+                if room == 0 {
+                    notify_evict(&self.on_evict, &val);
+                    continue;
+                }
+
+                // an element `dst` doesn't reset stays as dirty as it was
+                let dirty = match reset.as_mut() {
+                    Some(handle) => {
+                        handle(&mut val);
+                        false
+                    }
+                    None => dirty,
+                };
+
+                // same stamps as `next_stamp`
+                let seq = if evict_oldest {
+                    dst.put_seq.fetch_add(1, Ordering::Relaxed) + 1
+                } else {
+                    0
+                };
+
+                let placed = loop {
+                    let slot = match dst.slots.get_mut(target) {
+                        Some(slot) => slot,
+                        None => {
+                            notify_evict(&self.on_evict, &val);
+                            break false;
+                        }
+                    };
+
+                    match slot.place(val, seq, born, dirty) {
+                        Ok(()) => break true,
+                        Err(back) => {
+                            val = back;
+                            target += 1;
+                        }
+                    }
+                };
+
+                // out of vacancies, same as a full `dst`
+                if !placed {
+                    room = 0;
+                    continue;
+                }
+
+                room -= 1;
+                count += 1;
+            }
+        }
+
+        // same as `refill`, the filled vacancies are no longer counted as checked out
+        dst.stats
+            .outstanding
+            .fetch_sub(count as isize, Ordering::Relaxed);
+
+        #[cfg(feature = "empty-events")]
+        {
+            self.recount_idle();
+            dst.recount_idle();
+        }
+        count
+    }
+
+    /// Create an independent pool with the clones of the elements currently in this pool, with the
+    /// same builder and settings (i.e. reset handle, expansion and cursor configurations, spinning
+    /// settings, the idle cap and the full policy), but with all statistics counters starting
+    /// fresh. A reset closure set via `set_reset` can't be cloned, and hence won't be carried over.
+    ///
+    /// Reading the pooled elements isn't safe while others are checking out or returning them, so
+    /// the API requires an exclusive `&mut self` borrow instead of implementing `Clone` with `&self`.
+    /// Make sure no one else can access the pool (e.g. via a shared `static mut`) during the call.
+    pub fn duplicate(&mut self) -> Self
+    where
+        T: Clone,
+    {
+        let mut pool = Self::make_pool(0, self.builder);
+
+        pool.slots = self.slots.iter().map(|bucket| bucket.duplicate()).collect();
+        pool.copy_config(self);
+
+        #[cfg(feature = "empty-events")]
+        pool.recount_idle();
+
+        // same as a partially filled pool, the vacancies count as checked out
+        let vacancies = pool.capacity() - pool.len();
+        pool.stats
+            .outstanding
+            .store(vacancies as isize, Ordering::Release);
+        pool
+    }
+
+    /// Create a fresh pool with `size` pre-allocated elements (rounded up to whole buckets, same as
+    /// `with_size`), created by the `template`'s builder, and carrying the same settings as
+    /// `duplicate` does, but none of the `template`'s elements. This saves configuring each of many
+    /// identical pools one by one.
+    ///
+    /// Same as `duplicate`, a reset closure set via `set_reset` can't be cloned and won't be carried
+    /// over; to share a stateful reset among the pools, keep a factory of the closures and install
+    /// one on each new pool, or use a plain `fn` via `reset_handle`, which is carried over.
+    pub fn with_config_of(template: &SyncPool<T>, size: usize) -> Self {
+        let mut pool = Self::make_pool(buckets_for(size), template.builder);
+        pool.copy_config(template);
+        pool
+    }
+
+    /// Copy the settings of the `other` pool, but none of its elements or statistics counters.
+    fn copy_config(&mut self, other: &SyncPool<T>) {
+        self.configure
+            .store(other.configure.load(Ordering::Acquire), Ordering::Release);
+        self.spin = other.spin;
+        self.max_idle = other.max_idle;
+        self.max_probe = other.max_probe;
+        self.set_full_policy(other.full_policy);
+        self.memory_budget = other.memory_budget;
+        self.min_buckets = other.min_buckets;
+        self.fallback_hook = other.fallback_hook;
+        self.alloc_hook = other.alloc_hook;
+
+        if let Some(ResetHandle::Func(handle)) = other.reset_handle {
+            self.reset_handle.replace(ResetHandle::Func(handle));
+        }
+
+        #[cfg(feature = "max-lifetime")]
+        if let Some(max) = other.max_lifetime {
+            self.set_max_lifetime(max);
+        }
+    }
+
+    fn make_pool(size: usize, builder: ElemBuilder<T>) -> Self {
+        Self::make_pool_in(Vec::with_capacity(size), size, builder, true)
+    }
+
+    fn make_pool_in(
+        mut slots: Vec<Bucket2<T>>,
+        size: usize,
+        builder: ElemBuilder<T>,
+        fill: bool,
+    ) -> Self {
+        slots.clear();
+        slots.reserve(size);
+
+        let mut pool = SyncPool {
+            slots,
+            id: PoolId(NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed)),
+            curr: (AtomicUsize::new(0), AtomicUsize::new(0)),
+            last_checkout: AtomicUsize::new(0),
+            recent: (Default::default(), AtomicUsize::new(0)),
+            visitor_counter: (HandshakeUsize::new(1), HandshakeBool::new(false)),
+            poisoned: AtomicBool::new(false),
+            prefill_armed: AtomicBool::new(false),
+            stats: Arc::new(PoolCounters::default()),
+            max_idle: usize::MAX,
+            max_probe: usize::MAX,
+            memory_budget: usize::MAX,
+            min_buckets: 1,
+            full_policy: FullPolicy::Reject,
+            put_seq: AtomicU64::new(0),
+            configure: AtomicUsize::new(0),
+            spin: SpinConfig::default(),
+            reset_handle: None,
+            fallback_hook: None,
+            alloc_hook: None,
+            on_evict: None,
+            async_reset: None,
+            waiters: Waiters::default(),
+            owner: None,
+            auto_shrink: None,
+            #[cfg(feature = "deterministic")]
+            sequence: None,
+            #[cfg(feature = "fault-window")]
+            fault_window: FaultWindow::default(),
+            #[cfg(feature = "reset-timing")]
+            reset_durations: ResetDurations::default(),
+            #[cfg(feature = "empty-events")]
+            empty_events: EmptyEvents::default(),
+            #[cfg(feature = "max-lifetime")]
+            max_lifetime: None,
+            #[cfg(feature = "debug-leaks")]
+            checkouts: Mutex::new(HashMap::new()),
+            builder,
+        };
+
+        pool.add_slots(size, fill);
+        pool
+    }
+
+    #[inline]
+    fn add_slots(&mut self, count: usize, fill: bool) {
+        let filler = if fill { Some(&self.builder) } else { None };
+        let stamped = self.full_policy == FullPolicy::EvictOldest;
+        push_buckets(&mut self.slots, filler, count, stamped);
+
+        #[cfg(feature = "max-lifetime")]
+        track_births(&mut self.slots, self.max_lifetime);
+
+        // the vacancies of a lazy pool count as checked out until they're filled up
+        if !fill {
+            self.stats
+                .outstanding
+                .fetch_add((count * SLOT_CAP) as isize, Ordering::Relaxed);
+        }
+
+        self.stats
+            .capacity
+            .store(self.slots.len() * SLOT_CAP, Ordering::Release);
+
+        #[cfg(feature = "empty-events")]
+        self.recount_idle();
+    }
+
+    /// Enter the deterministic mode for testing, where `get` and `put` pick the buckets following a
+    /// reproducible sequence generated from `seed`, instead of the shared bucket cursors, such that
+    /// tests can assert exactly which bucket an element lands in. Pools with the same size, seed and
+    /// sequence of single-threaded operations will always end up in the same state.
+    ///
+    /// Only available with the `deterministic` feature, which shall never be enabled in production
+    /// builds, e.g. by enabling it in `[dev-dependencies]` only.
+    #[cfg(feature = "deterministic")]
+    pub fn set_deterministic(&mut self, seed: u64) {
+        self.sequence.replace(AtomicU64::new(seed));
+    }
+
+    /// Deny the next `count` accesses to the bucket at `bucket` as if the bucket were contended, or
+    /// all of them with `usize::MAX`, such that tests can drive `get` and `put` down their contended
+    /// branches (e.g. the fallback, or the probing of the next bucket) without racing threads.
+    /// Passing 0 lifts the injected failures, and an out-of-bounds `bucket` is ignored.
+    ///
+    /// Only available with the `test-hooks` feature, which shall never be enabled in production
+    /// builds, e.g. by enabling it in `[dev-dependencies]` only.
+    #[cfg(feature = "test-hooks")]
+    pub fn inject_access_failures(&mut self, bucket: usize, count: usize) {
+        if let Some(slot) = self.slots.get(bucket) {
+            slot.inject_failures(count);
+        }
+    }
+
+    /// Take a snapshot of all the figures of the pool at once, e.g. for a monitoring thread, which
+    /// would otherwise read the figures one by one through the getters and get combinations torn
+    /// apart by the pool activities in between.
+    ///
+    /// The figures are read back to back between two `SeqCst` fences, as a visitor of the pool (so
+    /// the call waits for an expansion in progress, if any). This is not an atomic multi-read: an
+    /// operation racing with the snapshot may be reflected in some figures but not in the others.
+    /// The guarantee is that every update that happens-before the call is reflected in all figures,
+    /// and that none of the reads can be moved out of the window between the fences, so the skew
+    /// is limited to the operations overlapping the call.
+    pub fn snapshot(&self) -> PoolSnapshot {
+        let _guard = VisitorGuard::register(&self.visitor_counter, false, self.spin);
+        atomic::fence(Ordering::SeqCst);
+
+        let stats = &self.stats;
+        let capacity = self.capacity();
+        let len = self.len();
+        let snapshot = PoolSnapshot {
+            capacity,
+            len,
+            outstanding_count: stats.outstanding.load(Ordering::Relaxed).max(0) as usize,
+            peak_outstanding: stats.peak_outstanding.load(Ordering::Relaxed),
+            miss_count: stats.miss_count.load(Ordering::Relaxed),
+            fallback_count: stats.fallback_count.load(Ordering::Relaxed),
+            misconfig_count: stats.misconfig_count.load(Ordering::Relaxed),
+            at_cap_count: stats.at_cap_count.load(Ordering::Relaxed),
+            idle_drop_count: stats.idle_drop_count.load(Ordering::Relaxed),
+            eviction_count: stats.eviction_count.load(Ordering::Relaxed),
+            reset_failure_count: stats.reset_failure_count.load(Ordering::Relaxed),
+            checkout_count: stats.checkout_count.load(Ordering::Relaxed),
+            return_count: stats.return_count.load(Ordering::Relaxed),
+        };
+
+        atomic::fence(Ordering::SeqCst);
+        snapshot
+    }
+
+    /// Pin the element at `idx` (i.e. at position `idx % SLOT_CAP` of the bucket `idx / SLOT_CAP`)
+    /// for shared reads without checking it out, such that many guards can read the same element at
+    /// once. Return `None` if `idx` is out of bounds, if the position holds no element, or if the
+    /// write barrier is raised. See `ReadGuard` for how the pinned element coexists with the other
+    /// pool operations, and the `read` module for the threads the guards can be used from.
+    pub fn read(&self, idx: usize) -> Option<ReadGuard<'_, T>> {
+        // register first, such that the slots can't be reallocated under the bounds check, see
+        // `try_get`; an out-of-bounds `idx` unregisters on the way out
+        let visitor = VisitorGuard::register(&self.visitor_counter, true, self.spin)?;
+        let bucket = self.slots.get(idx / SLOT_CAP)?;
+        let pos = idx % SLOT_CAP;
+
+        bucket
+            .pin(pos)
+            .map(|val| ReadGuard::new(self.id, bucket, pos, val, visitor))
+    }
+
+    /// Advance the rolling window of `fault_rate_window` by one tick, i.e. start counting the `get`
+    /// calls in a new tick, and forget the ones counted `FAULT_WINDOW` ticks ago. Same as
+    /// `maybe_shrink`, the pool never spawns a thread for it, so the caller shall invoke it at a
+    /// steady pace (e.g. once per second from a housekeeping task), and the window spans the last
+    /// `FAULT_WINDOW` ticks of the caller's choice.
+    #[cfg(feature = "fault-window")]
+    pub fn tick(&mut self) {
+        let window = &self.fault_window;
+        let next = window.curr.load(Ordering::Relaxed).wrapping_add(1);
+        let (gets, faults) = &window.ticks[next % FAULT_WINDOW];
+
+        gets.store(0, Ordering::Relaxed);
+        faults.store(0, Ordering::Relaxed);
+        window.curr.store(next, Ordering::Relaxed);
+    }
+
+    /// The number of reset handle invocations by duration, in power-of-2 buckets of microseconds:
+    /// the bucket 0 counts the resets taking less than 1 us, the bucket `i` the ones taking from
+    /// `2^(i-1)` up to `2^i` us, and the last bucket all the ones taking longer. The resets run by
+    /// `put` while holding a slot, so a heavy tail here stalls the other callers of the pool.
+    ///
+    /// Only available with the `reset-timing` feature; without it, the resets aren't timed at all.
+    #[cfg(feature = "reset-timing")]
+    pub fn reset_duration_histogram(&self) -> [usize; RESET_HISTOGRAM] {
+        let mut histogram = [0; RESET_HISTOGRAM];
+        for (count, bucket) in histogram
+            .iter_mut()
+            .zip(self.reset_durations.histogram.iter())
+        {
+            *count = bucket.load(Ordering::Relaxed);
+        }
+
+        histogram
+    }
+
+    /// Set or update the hook to be invoked with the duration of every reset taking longer than
+    /// the `threshold`, e.g. to log the reset handles accidentally doing I/O. The hook runs right
+    /// after the slow reset, still holding the slot, so keep it cheap.
+    ///
+    /// Only available with the `reset-timing` feature.
+    #[cfg(feature = "reset-timing")]
+    pub fn set_slow_reset_hook(&mut self, threshold: Duration, hook: fn(Duration)) {
+        self.reset_durations.slow.replace((threshold, hook));
+    }
+
+    /// Poison the pool, e.g. when the pooled resources have become invalid altogether (say, the
+    /// database behind the pooled connections went away), such that the pool stops handing out
+    /// the stale elements until `unpoison`: the idle elements are dropped right away (notifying the
+    /// hook set via `set_on_evict`), the checkouts that can't allocate (e.g. `get_bucket`, or
+    /// `try_get` of a `StrictPool`) yield `None`, and the returned elements are dropped as well.
+    ///
+    /// `get` and the other checkouts that fall back to creating a new element still do so, since
+    /// they can't yield `None`; use `is_poisoned` to stop calling them, e.g. as a circuit breaker.
+    pub fn poison(&mut self) {
+        self.poisoned.store(true, Ordering::Release);
+
+        let _guard = ExclusiveGuard::acquire(&self.visitor_counter, &self.waiters);
+        for bucket in self.slots.iter_mut() {
+            for pos in 0..SLOT_CAP {
+                if let Some((val, _, _)) = bucket.take(pos) {
+                    notify_evict(&self.on_evict, &val);
+                }
+            }
+        }
+
+        #[cfg(feature = "empty-events")]
+        self.recount_idle();
+    }
+
+    /// Lift the poisoning, such that the pool hands out and takes back the elements again. The pool
+    /// stays empty until elements are returned to it (or it's `refill`ed).
+    pub fn unpoison(&mut self) {
+        self.poisoned.store(false, Ordering::Release);
+    }
+
+    /// If the pool has been poisoned, see `poison`.
+    #[inline]
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Acquire)
+    }
+
+    /// Install the new reset handle and return the old one, if any, for a hot swap of the reset
+    /// behavior. The handle is replaced in a single step, so every `put` runs either the old or the
+    /// new handle, and none of them finds the pool without one in between, as it could with a
+    /// clear-then-set. Unlike `reset_handle` and `set_reset`, this hands the old handle back, e.g.
+    /// to restore it later.
+    ///
+    /// Same as the other setters, the handle is replaced under the write barrier, but instead of
+    /// waiting for a barrier held by someone else, if the barrier can't be raised within about
+    /// 16ms, nothing is replaced and the new handle is handed back as the error.
+    pub fn swap_reset_handle(
+        &mut self,
+        new: ResetHandle<T>,
+    ) -> Result<Option<ResetHandle<T>>, ResetHandle<T>> {
+        self.replace_reset(new)
+    }
+
+    /// Set or update the hook to be invoked when a checkout (i.e. `get` and the APIs built on it)
+    /// leaves the pool empty, e.g. to tell the consumers to back off. The hook is edge-triggered: it
+    /// fires once when the pool runs empty, and not again until an element has been returned to the
+    /// pool (which fires the `on_non_empty` hook).
+    ///
+    /// The hooks run on the caller's thread after the slot has been released, so they can't stall
+    /// the other callers of the pool, but keep them cheap anyway. Elements added by other means than
+    /// `put` (e.g. `expand` or `refill`) don't fire `on_non_empty`.
+    ///
+    /// Only available with the `empty-events` feature, since the checkouts and the returns have to
+    /// keep a live count of the idle elements to find out if the pool is empty.
+    #[cfg(feature = "empty-events")]
+    pub fn on_empty(&mut self, hook: fn()) {
+        self.empty_events.on_empty.replace(hook);
+    }
+
+    /// Set or update the hook to be invoked when an element is returned to the pool that has run
+    /// empty, e.g. to wake the consumers, see `on_empty`.
+    ///
+    /// Only available with the `empty-events` feature.
+    #[cfg(feature = "empty-events")]
+    pub fn on_non_empty(&mut self, hook: fn()) {
+        self.empty_events.on_non_empty.replace(hook);
+    }
+
+    /// Set or update the maximum total lifetime of the elements, e.g. to rotate the connections
+    /// bearing credentials. Unlike an idle timeout, this counts from when the element was created,
+    /// however often it has been reused since: a checkout finding an element older than `max` drops
+    /// it (notifying the hook set via `set_on_evict`) and goes on as if the pool were empty, i.e.
+    /// `get` creates a new element from the pool's builder (counted as a fallback), while the
+    /// checkouts that can't allocate (e.g. `try_get` of a `StrictPool`, or `get_bucket`) yield
+    /// `None`.
+    ///
+    /// The creation times are kept by the slots along with the stamps of `FullPolicy::EvictOldest`,
+    /// so a checkout costs no more than a clock read. A time moves with its element between the
+    /// slots (e.g. by `rebalance` or `transfer_into`), and when the element is checked out, stays
+    /// behind for the element returned into the vacated slot; a slot whose element is dropped for
+    /// its age starts afresh. The elements in the pool by the time of the first call are timed from
+    /// then, and so are the slots added by `expand` from their creation.
+    ///
+    /// This waits for the ongoing checkouts and returns to finish, same as `expand`.
+    ///
+    /// Only available with the `max-lifetime` feature.
+    #[cfg(feature = "max-lifetime")]
+    pub fn set_max_lifetime(&mut self, max: Duration) {
+        self.max_lifetime.replace(max);
+
+        let _guard = ExclusiveGuard::acquire(&self.visitor_counter, &self.waiters);
+        track_births(&mut self.slots, self.max_lifetime);
+    }
+
+    /// The elements checked out of the pool and not yet returned, the oldest checkout first, e.g. to
+    /// find out where the elements of a mysteriously empty pool have gone. Each checkout records its
+    /// label (see `get_labeled`) and a backtrace, and the record is removed when the element is
+    /// returned via `put` (or any of its variants, including the guards returning the elements on
+    /// drop). The elements handed back by a full pool are still considered as checked out.
+    ///
+    /// Only available with the `debug-leaks` feature, since every checkout and return takes a lock
+    /// to update the records, and captures the backtrace if enabled by the environment.
+    #[cfg(feature = "debug-leaks")]
+    pub fn outstanding_checkouts(&self) -> Vec<CheckoutInfo> {
+        let checkouts = self
+            .checkouts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let mut infos: Vec<_> = checkouts.values().cloned().collect();
+        infos.sort_by_key(|info| info.since);
+        infos
+    }
+
+    /// Record the element about to be handed out, see `outstanding_checkouts`.
+    #[cfg(feature = "debug-leaks")]
+    fn track_checkout(&self, val: &T, label: Option<&'static str>) {
+        let info = CheckoutInfo {
+            label,
+            since: Instant::now(),
+            backtrace: Arc::new(Backtrace::capture()),
+        };
+
+        self.checkouts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(val as *const T as usize, info);
+    }
+
+    /// Forget the record of the element returned from the address `addr`, if any.
+    #[cfg(feature = "debug-leaks")]
+    fn untrack_checkout(&self, addr: usize) {
+        self.checkouts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&addr);
+    }
+
+    /// Fire the `on_empty` hook if the checkout has just left the pool empty, or has found it empty.
+    #[cfg(feature = "empty-events")]
+    fn note_checkout(&self, served: bool) {
+        let events = &self.empty_events;
+        let idle = if served {
+            events.idle.fetch_sub(1, Ordering::AcqRel) - 1
+        } else {
+            events.idle.load(Ordering::Acquire)
+        };
+
+        if events.on_empty.is_none() && events.on_non_empty.is_none() {
+            return;
+        }
+
+        // a return may be counted after its element has been checked out again, hence below 0
+        if idle <= 0 && !events.empty.swap(true, Ordering::AcqRel) {
+            if let Some(hook) = events.on_empty {
+                hook();
+            }
+        }
+    }
+
+    /// Count an element placed in the buckets by a return (or a `refill`), see `note_checkout`.
+    #[cfg(feature = "empty-events")]
+    #[inline]
+    fn note_placed(&self) {
+        self.empty_events.idle.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Recount the idle elements after the buckets have been changed by other means than the
+    /// checkouts and the returns. The caller must hold the exclusive access to the pool, such that
+    /// no checkout or return is counted in the meantime.
+    #[cfg(feature = "empty-events")]
+    fn recount_idle(&self) {
+        self.empty_events
+            .idle
+            .store(self.len() as isize, Ordering::Release);
+    }
+
+    /// Fire the `on_non_empty` hook if the return has just refilled the empty pool.
+    #[cfg(feature = "empty-events")]
+    fn note_return(&self) {
+        let events = &self.empty_events;
+        if events.empty.swap(false, Ordering::AcqRel) {
+            if let Some(hook) = events.on_non_empty {
+                hook();
+            }
+        }
+    }
+
+    /// The share of the `get` calls that couldn't be served from the pool (and hence created a new
+    /// element) within the rolling window of the last `FAULT_WINDOW` ticks, including the current
+    /// one, see `tick`. Return 0 if no `get` has been made in the window. Unlike `miss_count` and
+    /// `fallback_count`, the figure reflects the recent load only, e.g. for autoscaling decisions.
+    #[cfg(feature = "fault-window")]
+    pub fn fault_rate_window(&self) -> f32 {
+        let (mut gets, mut faults) = (0, 0);
+        for (g, f) in self.fault_window.ticks.iter() {
+            gets += g.load(Ordering::Relaxed);
+            faults += f.load(Ordering::Relaxed);
+        }
+
+        if gets == 0 {
+            0.0
+        } else {
+            faults as f32 / gets as f32
+        }
+    }
+
+    /// The tick of the auto shrinking policy set by `set_auto_shrink`, and the caller shall invoke
+    /// it periodically from its own loop (e.g. an event loop, or a housekeeping task), since the pool
+    /// never spawns a thread by itself. The call returns immediately unless `check_interval` has
+    /// elapsed since the last check; otherwise, it samples which buckets are full, i.e. every
+    /// element of the bucket is idle in the pool, and releases the buckets (dropping their elements)
+    /// that have been found full at every check over the last `idle_threshold`. The pool always
+    /// keeps at least 1 bucket, or as many as set by `set_min_buckets`. Return the number of buckets
+    /// released.
+    ///
+    /// The idleness is sampled, so a bucket drained and refilled between two ticks still counts as
+    /// idle; pick a `check_interval` well below `idle_threshold` to smooth this out. Releasing the
+    /// buckets raises the write barrier same as `with_exclusive`, which happens only if there is
+    /// something to release. Note that the elements checked out of a released bucket can still be
+    /// `put` back to the remaining buckets (or be handed back if the pool is full).
+    pub fn maybe_shrink(&mut self) -> usize {
+        if self.foreign_thread() {
+            return 0;
+        }
+
+        let now = Instant::now();
+        let shrink = match self.auto_shrink.as_mut() {
+            Some(shrink) if now.duration_since(shrink.last_check) >= shrink.check_interval => {
+                shrink
+            }
+            _ => return 0,
+        };
+
+        // scan the buckets as a visitor, such that they won't be resized under our feet
+        let visitor = match VisitorGuard::register(&self.visitor_counter, true, self.spin) {
+            Some(guard) => guard,
+            None => return 0,
+        };
+
+        let buckets = self.slots.len();
+
+        shrink.last_check = now;
+        shrink.idle_since.resize(buckets, None);
+
+        let mut expired = 0;
+        for (bucket, since) in self.slots.iter().zip(shrink.idle_since.iter_mut()) {
+            if bucket.size_hint() < SLOT_CAP {
+                since.take();
+            } else if now.duration_since(*since.get_or_insert(now)) >= shrink.idle_threshold {
+                expired += 1;
+            }
+        }
+
+        drop(visitor);
+        if expired == 0 || buckets <= self.min_buckets {
+            return 0;
+        }
+
+        // the buckets can only grow in the meantime, so the first `buckets` ones are still the same
+        let _guard = ExclusiveGuard::acquire(&self.visitor_counter, &self.waiters);
+        let threshold = shrink.idle_threshold;
+        let mut released = 0;
+
+        // release from the tail, such that the cursors mostly keep pointing at the same buckets
+        for idx in (0..buckets).rev() {
+            if self.slots.len() <= self.min_buckets {
+                break;
+            }
+
+            let idle = match shrink.idle_since[idx] {
+                Some(since) => now.duration_since(since) >= threshold,
+                None => false,
+            };
+
+            // check again, since the bucket may have been used while we're raising the barrier
+            if idle && self.slots[idx].size_hint() == SLOT_CAP {
+                let bucket = self.slots.remove(idx);
+                if let Some(hook) = self.on_evict.as_ref() {
+                    bucket.iter().for_each(hook);
+                }
+
+                shrink.idle_since.remove(idx);
+                released += 1;
+            }
+        }
+
+        #[cfg(feature = "empty-events")]
+        self.recount_idle();
+
+        self.stats
+            .capacity
+            .store(self.slots.len() * SLOT_CAP, Ordering::Release);
+
+        released
+    }
+
+    /// Run `f` with the exclusive access to all the buckets of the pool, each by a `BucketView`,
+    /// e.g. to retain or evict the idle elements in a way the pool doesn't offer. The call raises the write barrier and blocks
+    /// until all visitors have left the pool, same as a blocking `expand`, so keep `f` short. The
+    /// barrier is lowered once `f` returns, or if `f` panics.
+    ///
+    /// Note that every visitor running into the barrier in the meantime will either spin (`put`),
+    /// or fall back to creating a new element (`get`).
+    pub fn with_exclusive<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut [BucketView<'_, T>]) -> R,
+    {
+        let _guard = ExclusiveGuard::acquire(&self.visitor_counter, &self.waiters);
+        let on_evict = &self.on_evict;
+        let mut buckets: Vec<_> = self
+            .slots
+            .iter_mut()
+            .map(|bucket| BucketView::new(bucket, on_evict))
+            .collect();
+        let res = f(&mut buckets);
+
+        #[cfg(feature = "empty-events")]
+        self.recount_idle();
+
+        res
+    }
+
+    /// Move the idle elements around, such that they're spread evenly over the buckets, i.e. the
+    /// number of elements in any two buckets differs by at most 1. After a skewed load, the idle
+    /// elements may cluster in a few buckets, so `get` and `put` would probe more buckets before
+    /// finding an element or a vacancy. Return the number of elements moved.
+    ///
+    /// This takes O(capacity) under the write barrier, same as `with_exclusive`, so it's meant for
+    /// an occasional maintenance, not for the hot path. The elements keep their `EvictOldest`
+    /// stamps, and no reset handle is invoked.
+    pub fn rebalance(&mut self) -> usize {
+        let _guard = ExclusiveGuard::acquire(&self.visitor_counter, &self.waiters);
+
+        let buckets = self.slots.len();
+        let total: usize = self.slots.iter().map(Bucket2::len).sum();
+        let target = |idx: usize| total / buckets + usize::from(idx < total % buckets);
+
+        // take the surplus out first, then hand it to the buckets short of their share
+        let mut surplus = Vec::new();
+        for (idx, bucket) in self.slots.iter_mut().enumerate() {
+            let mut pos = 0;
+            while bucket.len() > target(idx) && pos < SLOT_CAP {
+                let dirty = bucket.is_dirty(pos);
+                surplus.extend(
+                    bucket
+                        .take(pos)
+                        .map(|(val, seq, born)| (val, seq, born, dirty)),
+                );
+                pos += 1;
+            }
+        }
+
+        let moved = surplus.len();
+        for (idx, bucket) in self.slots.iter_mut().enumerate() {
+            while bucket.len() < target(idx) {
+                match surplus.pop() {
+                    Some((val, seq, born, dirty)) => {
+                        if let Err(val) = bucket.place(val, seq, born, dirty) {
+                            surplus.push((val, seq, born, dirty));
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        debug_assert!(surplus.is_empty());
+        moved
+    }
+
+    /// Lower the write barrier and restore the visitor counter, as if the writer holding them had
+    /// finished. The pool lowers the barrier on its own when a writer unwinds from a panic (e.g. of
+    /// the builder filling the new buckets of `expand`), so this is only an escape hatch for a pool
+    /// locked up in other ways, e.g. by a writer thread killed without unwinding. Return `true` if
+    /// the barrier was raised.
+    ///
+    /// # Safety
+    ///
+    /// No writer may be running on the pool, i.e. the barrier must have been left behind by a writer
+    /// that will never resume; otherwise, the visitors will be let into the pool while the writer is
+    /// still updating the buckets.
+    pub unsafe fn force_clear_barrier(&mut self) -> bool {
+        // a writer that got all visitors out has dropped the counter to 0, give it back
+        let _ = self
+            .visitor_counter
+            .0
+            .compare_exchange(0, 1, Ordering::SeqCst, Ordering::Relaxed);
+
+        let raised = self.visitor_counter.1.load(Ordering::SeqCst);
+        self.visitor_counter.1.store(false, Ordering::Release);
+        self.waiters.wake_all();
+        raised
+    }
+
+    /// Run `f` on each idle element of the pool by shared reference, leaving the elements in place,
+    /// e.g. for a read-only health check of the pooled connections. Same as `with_exclusive`, the
+    /// call holds the write barrier while `f` runs on the elements: it blocks until all visitors
+    /// have left, and `get` or `put` calls from other threads will fall back or spin until it's done.
+    pub fn peek_each<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T),
+    {
+        self.with_exclusive(|buckets| {
+            buckets
+                .iter()
+                .flat_map(|bucket| bucket.iter())
+                .for_each(&mut f)
+        });
+    }
+
+    /// The contention stats of the buckets, as a list of `(bucket index, denied accesses)` pairs
+    /// sorted by the number of denied accesses, the most contended bucket first. A well balanced
+    /// pool shall have the denied accesses spread evenly over the buckets.
+    #[cfg(feature = "bucket-stats")]
+    pub fn hot_buckets(&self) -> Vec<(usize, usize)> {
+        let mut stats: Vec<(usize, usize)> = self
+            .slots
+            .iter()
+            .enumerate()
+            .map(|(i, bucket)| (i, bucket.denied_count()))
+            .collect();
+
+        stats.sort_by_key(|&(_, denied)| std::cmp::Reverse(denied));
+        stats
+    }
+
+    /// The shared implementation of `expand` and `try_expand`.
+    fn expand_pool(&mut self, additional: usize, block: bool) -> ExpandResult {
+        // the new elements would be created on a thread the pool is not bound to
+        if self.foreign_thread() {
+            return ExpandResult::Disabled;
+        }
+
+        // if the pool isn't allowed to expand, just return
+        if !self.expansion_enabled() {
+            self.stats.misconfig_count.fetch_add(1, Ordering::Relaxed);
+            return ExpandResult::Disabled;
+        }
+
+        // if exceeding the upper limit, quit
+        if self.slots.len() > EXPANSION_CAP {
+            self.stats.at_cap_count.fetch_add(1, Ordering::Relaxed);
+            return ExpandResult::AtCap;
+        }
+
+        // clamp the request to the room left below the cap, so absurd inputs (e.g. sized from
+        // untrusted figures) can neither overflow the capacity math nor allocate without bounds
+        let additional = additional.min(MAX_BUCKETS - self.slots.len());
+
+        if self.memory_budget < usize::MAX && self.projected_bytes(additional) > self.memory_budget
+        {
+            return ExpandResult::OverBudget;
+        }
+
+        // the guard lowers the barrier once the buckets are in, and also if the builder panics
+        // while filling them, such that the pool won't be locked up for good
+        let stamped = self.full_policy == FullPolicy::EvictOldest;
+        match ExclusiveGuard::try_acquire(&self.visitor_counter, &self.waiters, block) {
+            Ok(_guard) => {
+                push_buckets(&mut self.slots, Some(&self.builder), additional, stamped);
+
+                #[cfg(feature = "max-lifetime")]
+                track_births(&mut self.slots, self.max_lifetime);
+
+                #[cfg(feature = "empty-events")]
+                self.recount_idle();
+            }
+            Err(res) => return res,
+        }
+
+        self.stats
+            .capacity
+            .store(self.slots.len() * SLOT_CAP, Ordering::Release);
+        if self.configure.load(Ordering::Relaxed) & CONFIG_KEEP_MISSES == 0 {
+            self.stats.miss_count.store(0, Ordering::Release);
+        }
+
+        self.report_alloc(AllocEvent::Expand(additional * SLOT_CAP));
+        ExpandResult::Grew(additional * SLOT_CAP)
+    }
+
+    /// The `approx_bytes` of the pool once `additional` buckets are added, assuming the bucket
+    /// storage grows to fit them exactly.
+    fn projected_bytes(&self, additional: usize) -> usize {
+        let buckets = self.slots.capacity().max(self.slots.len() + additional);
+
+        mem::size_of::<Self>()
+            + buckets * mem::size_of::<Bucket2<T>>()
+            + (self.slots.len() + additional) * SLOT_CAP * mem::size_of::<T>()
+    }
+
+    /// The stamp of the position a `put` is about to fill: the next sequence number when the pool
+    /// keeps track of the oldest elements for `FullPolicy::EvictOldest`, or 0 otherwise.
+    #[inline]
+    fn next_stamp(&self) -> u64 {
+        if self.full_policy == FullPolicy::EvictOldest {
+            self.put_seq.fetch_add(1, Ordering::Relaxed) + 1
+        } else {
+            0
+        }
+    }
+
+    /// Record the position an element has just been returned to, for `CheckoutOrder::Lifo`.
+    #[inline]
+    fn push_recent(&self, bucket: usize, pos: usize) {
+        if self.configure.load(Ordering::Relaxed) & CONFIG_LIFO == 0 {
+            return;
+        }
+
+        let top = self.recent.1.fetch_add(1, Ordering::Relaxed);
+        self.recent.0[top % RECENT_CAP].store(bucket * SLOT_CAP + pos + 1, Ordering::Release);
+    }
+
+    /// In the thread-affine mode, if the caller is not on the thread the pool is bound to.
+    #[inline]
+    fn foreign_thread(&self) -> bool {
+        match self.owner {
+            Some(owner) => owner != thread::current().id(),
+            None => false,
+        }
+    }
+
+    /// The wakers of the pending streams.
+    pub(crate) fn waiters(&self) -> &Waiters {
+        &self.waiters
+    }
+
+    /// The async reset handle, if any, see `set_async_reset`.
+    pub(crate) fn async_reset(&mut self) -> &mut Option<AsyncResetHandle<T>> {
+        &mut self.async_reset
+    }
+
+    /// Count an element dropped because its async reset has failed.
+    /// Drop a returned element whose reset has failed instead of storing it, see
+    /// `reset_failure_count`.
+    pub(crate) fn drop_reset_failure(&self, val: Box<T>) {
+        notify_evict(&self.on_evict, &val);
+        self.let_go(&*val as *const T as usize);
+        self.stats
+            .reset_failure_count
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Account for a checked-out element at `addr` which the pool drops instead of storing it, same
+    /// as a `PutOutcome::Dropped` return: it's no longer outstanding, nor tracked by the checkout
+    /// records. The caller notifies the `set_on_evict` hook before the drop.
+    #[cfg_attr(not(feature = "debug-leaks"), allow(unused_variables))]
+    pub(crate) fn let_go(&self, addr: usize) {
+        self.stats.outstanding.fetch_sub(1, Ordering::Relaxed);
+
+        #[cfg(feature = "debug-leaks")]
+        self.untrack_checkout(addr);
+    }
+
+    /// The weak handle to the statistics counters, used by the pool registry.
+    pub(crate) fn stats_handle(&self) -> Weak<PoolCounters> {
+        Arc::downgrade(&self.stats)
+    }
+
+    /// Count one more element checked out, and raise the peak if it's a new high.
+    #[inline]
+    fn update_peak(&self) {
+        let outstanding = self.stats.outstanding.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if outstanding > 0 {
+            self.stats
+                .peak_outstanding
+                .fetch_max(outstanding as usize, Ordering::Relaxed);
+        }
+    }
+
+    /// Keep trying to replace the reset handle until the barrier is raised, such that the new handle
+    /// is never lost to a barrier held by someone else for longer than `replace_reset` waits.
+    fn install_reset(&mut self, mut handle: ResetHandle<T>) {
+        while let Err(back) = self.replace_reset(handle) {
+            handle = back;
+            thread::yield_now();
+        }
+    }
+
+    /// Install the reset handle under the write barrier and return the old one. If the barrier can't
+    /// be raised within about 16ms, nothing is replaced and the new handle is handed back as the
+    /// error.
+    fn replace_reset(
+        &mut self,
+        handle: ResetHandle<T>,
+    ) -> Result<Option<ResetHandle<T>>, ResetHandle<T>> {
+        // busy waiting ... for the first chance a barrier owned by someone else is lowered
+        let mut count: usize = 8;
+        let timeout = Instant::now().add(Duration::from_millis(16));
+
+        loop {
+            match self.visitor_counter.1.compare_exchange(
+                false,
+                true,
+                Ordering::SeqCst,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(_) => {
+                    cpu_relax(count);
+
+                    // update the counter (and the busy wait period)
+                    count = count.saturating_sub(1);
+
+                    if Instant::now() > timeout {
+                        // don't block for more than 16ms
+                        return Err(handle);
+                    } else if count < 4 {
+                        // yield the thread for later try
+                        thread::yield_now();
+                    }
+                }
+            }
+        }
+
+        let old = self.reset_handle.replace(handle);
+
+        self.visitor_counter.1.store(false, Ordering::SeqCst);
+        self.waiters.wake_all();
+        Ok(old)
+    }
+
+    fn update_config(&mut self, mask: usize, target: bool) {
+        update_flag(&self.configure, mask, target);
+    }
+}
+
+/// Invoke the observer set via `set_on_evict`, if any, with the element about to be dropped.
+#[inline]
+pub(crate) fn notify_evict<T>(hook: &Option<EvictHook<T>>, val: &T) {
+    if let Some(hook) = hook {
+        hook(val);
+    }
+}
+
+/// The cursor-driven scan of the buckets shared by `SyncPool` and `ArrayPool`: start from the bucket
+/// at the `cursor`, and move the cursor on after every failed probe, for at most `trials` probes. A
+/// `sticky` scan also parks the cursor at the bucket it has been served by.
+pub(crate) struct Scan<'a> {
+    pub(crate) cursor: &'a AtomicUsize,
+    pub(crate) sticky: bool,
+    pub(crate) trials: usize,
+
+    /// the reproducible sequence overriding the bucket positions, see `set_deterministic`
+    #[cfg(feature = "deterministic")]
+    pub(crate) sequence: &'a Option<AtomicU64>,
+}
+
+impl Scan<'_> {
+    /// Move the cursor past a successful probe at `pos`, by the `CursorPolicy`: park it there if
+    /// sticky, or else at the next bucket, such that the next call starts from there.
+    fn settle(&self, pos: usize) {
+        let next = if self.sticky { pos } else { pos + 1 };
+        self.cursor.store(next, Ordering::Release);
+    }
+
+    /// Check out an element, and return it along with its bucket and its mark.
+    pub(crate) fn get<T>(self, slots: &mut [Bucket2<T>]) -> Option<(usize, Box<T>, Mark)> {
+        let cap = slots.len();
+        let mut pos = self.cursor.load(Ordering::Acquire) % cap;
+
+        #[cfg(feature = "deterministic")]
+        next_in_sequence(self.sequence, &mut pos, cap);
+
+        for _ in 0..self.trials {
+            let slot = &mut slots[pos];
+
+            // try the access or move on
+            if let Ok(i) = slot.access(true) {
+                let checkout = slot.checkout(i);
+                let mark = slot.mark(i);
+                slot.leave(i as u16);
+
+                if let Ok(val) = checkout {
+                    self.settle(pos);
+                    return Some((pos, val, mark));
+                }
+
+                // failed to checkout, which is likely transient, keep probing the other buckets
+            }
+
+            // hold off a bit to reduce contentions
+            cpu_relax(SPIN_PERIOD);
+
+            // update to the next position now, i.e. the position after the cursor's old value.
+            pos = (self.cursor.fetch_add(1, Ordering::AcqRel) + 1) % cap;
+
+            #[cfg(feature = "deterministic")]
+            next_in_sequence(self.sequence, &mut pos, cap);
+        }
+
+        None
+    }
+
+    /// Place `val` (after the `reset`) in a vacancy stamped with `seq` and marked as `dirty`, and
+    /// return the bucket and the position it has been placed at, or hand it back if no vacancy is
+    /// found.
+    pub(crate) fn put<T>(
+        self,
+        slots: &mut [Bucket2<T>],
+        val: Box<T>,
+        seq: u64,
+        reset: Option<&mut dyn FnMut(&mut T)>,
+        dirty: bool,
+    ) -> Result<(usize, usize), Box<T>> {
+        let cap = slots.len();
+        let mut pos = self.cursor.load(Ordering::Acquire) % cap;
+
+        #[cfg(feature = "deterministic")]
+        next_in_sequence(self.sequence, &mut pos, cap);
+
+        for trial in 0..self.trials {
+            let slot = &mut slots[pos];
+
+            // try the access or move on
+            if let Ok(i) = slot.access(false) {
+                self.settle(pos);
+                slot.release(i, val, reset);
+                slot.stamp(i, seq);
+                slot.mark_dirty(i, dirty);
+                slot.leave(i as u16);
+
+                return Ok((pos, i));
+            }
+
+            // hold off a bit to reduce contentions, and yield once all buckets have been probed
+            if self.trials - trial < cap {
+                cpu_relax(SPIN_PERIOD);
+            } else {
+                thread::yield_now();
+            }
+
+            // update to the next position now, i.e. the position after the cursor's old value.
+            pos = (self.cursor.fetch_add(1, Ordering::AcqRel) + 1) % cap;
+
+            #[cfg(feature = "deterministic")]
+            next_in_sequence(self.sequence, &mut pos, cap);
+        }
+
+        Err(val)
+    }
+}
+
+/// Append `count` new buckets to the `slots`, filled by the `filler` if any, and stamped if the
+/// pool is under `FullPolicy::EvictOldest`.
+fn push_buckets<T>(
+    slots: &mut Vec<Bucket2<T>>,
+    filler: Option<&ElemBuilder<T>>,
+    count: usize,
+    stamped: bool,
+) {
+    for _ in 0..count {
+        let mut bucket = Bucket2::new(filler);
+        bucket.track_stamps(stamped);
+        slots.push(bucket);
+    }
+}
+
+/// Time the elements of the buckets (including the ones just added) for the maximum lifetime, if
+/// any, see `SyncPool::set_max_lifetime`. The caller must have obtained an exclusive access.
+#[cfg(feature = "max-lifetime")]
+fn track_births<T>(slots: &mut [Bucket2<T>], max: Option<Duration>) {
+    slots.iter_mut().for_each(|bucket| bucket.track_births(max));
+}
+
+/// Check out an element from the most recently returned positions, the latest first, and return
+/// it along with its bucket and its mark. Each recorded position is tried at most once, and the ones that have
+/// been taken (or released by the shrinking) in the meantime are skipped.
+fn pop_recent<T>(
+    recent: &([AtomicUsize; RECENT_CAP], AtomicUsize),
+    slots: &mut [Bucket2<T>],
+) -> Option<(usize, Box<T>, Mark)> {
+    let top = recent.1.load(Ordering::Relaxed);
+
+    for k in 1..=RECENT_CAP {
+        let entry = &recent.0[top.wrapping_sub(k) % RECENT_CAP];
+        if entry.load(Ordering::Relaxed) == 0 {
+            continue;
+        }
+
+        let code = entry.swap(0, Ordering::AcqRel);
+        if code == 0 {
+            continue;
+        }
+
+        let (idx, pos) = ((code - 1) / SLOT_CAP, (code - 1) % SLOT_CAP);
+        let slot = match slots.get_mut(idx) {
+            Some(slot) => slot,
+            None => continue,
+        };
+
+        if let Ok(i) = slot.try_access_at(pos) {
+            let checkout = slot.checkout(i);
+            let mark = slot.mark(i);
+            slot.leave(i as u16);
+
+            if let Ok(val) = checkout {
+                return Some((idx, val, mark));
+            }
+        }
+    }
+
+    None
+}
+
+/// Replace the oldest idle element among all buckets with `val` (marked as `dirty`), and return the
+/// bucket along with the evicted element; or hand `val` back if the pool is empty, or if the
+/// position has been taken in the meantime.
+fn evict_oldest<T>(
+    slots: &mut [Bucket2<T>],
+    val: Box<T>,
+    seq: u64,
+    reset: Option<&mut dyn FnMut(&mut T)>,
+    dirty: bool,
+) -> Result<(usize, Box<T>), Box<T>> {
+    let oldest = slots
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, slot)| slot.oldest().map(|(pos, stamp)| (idx, pos, stamp)))
+        .min_by_key(|&(_, _, stamp)| stamp);
+
+    match oldest {
+        Some((idx, pos, _)) => slots[idx]
+            .swap(pos, val, seq, reset, dirty)
+            .map(|old| (idx, old)),
+        None => Err(val),
+    }
+}
+
+/// Set (or clear) the `mask` bits of the configure to match the `target`. This is a single atomic
+/// read-modify-write, so it's idempotent (rather than flipping the bits blindly), and no concurrent
+/// update of the other bits will be lost.
+fn update_flag(configure: &AtomicUsize, mask: usize, target: bool) {
+    if target {
+        configure.fetch_or(mask, Ordering::SeqCst);
+    } else {
+        configure.fetch_and(!mask, Ordering::SeqCst);
+    }
+}
+
+impl<T> Default for SyncPool<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        SyncPool::new()
+    }
+}
+
+impl<T> Drop for SyncPool<T> {
+    fn drop(&mut self) {
+        if self.foreign_thread() {
+            // the elements must not be destroyed here, leaking them is the only safe option
+            for bucket in self.slots.drain(..) {
+                mem::forget(bucket);
+            }
+        }
+
+        self.slots.clear();
+
+        // now drop the reset handle if it's not null
+        self.reset_handle.take();
+    }
+}
+
+/// Only the summary of the pool is printed, and all the figures are derived from the atomic states,
+/// so it's safe to debug-print the pool while others are using it.
+impl<T> fmt::Debug for SyncPool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncPool")
+            .field("buckets", &self.slots.len())
+            .field("available", &self.len())
+            .field("miss_count", &self.miss_count())
+            .field("expansion_enabled", &self.expansion_enabled())
+            .finish()
+    }
+}
+
+impl<U: 'static> SyncPool<Vec<U>> {
+    /// Install a reset handle (same as `set_reset`) which clears the returned vectors but keeps
+    /// their capacity for reuse, up to `max_retained_capacity`: a vector grown beyond the cap is
+    /// shrunk back to it, such that a single oversized buffer won't hog the memory as long as it
+    /// stays in the pool.
+    pub fn set_capped_clear_reset(&mut self, max_retained_capacity: usize) -> &mut Self {
+        self.set_reset(move |val: &mut Vec<U>| {
+            val.clear();
+
+            if val.capacity() > max_retained_capacity {
+                val.shrink_to(max_retained_capacity);
+            }
+        })
+    }
+}
+
+impl SyncPool<String> {
+    /// Same as `SyncPool<Vec<U>>::set_capped_clear_reset`, for the pooled strings.
+    pub fn set_capped_clear_reset(&mut self, max_retained_capacity: usize) -> &mut Self {
+        self.set_reset(move |val: &mut String| {
+            val.clear();
+
+            if val.capacity() > max_retained_capacity {
+                val.shrink_to(max_retained_capacity);
+            }
+        })
+    }
+}
+
+pub trait PoolState {
+    fn expansion_enabled(&self) -> bool;
+
+    fn miss_count(&self) -> usize;
+
+    fn capacity(&self) -> usize;
+
+    /// The total number of elements the pool can hold, including the buckets added by `expand`.
+    /// This is the same as `capacity`, and is a best-effort snapshot if the pool is being expanded
+    /// concurrently.
+    fn total_capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn len(&self) -> usize;
+
+    /// If the pool currently holds at least `n` idle elements, i.e. `n` checkouts could be served
+    /// without allocating. This is a best-effort pre-check based on `len`: the elements may be taken
+    /// by others (or be too contended to reach) by the time they're checked out, so it's meant for
+    /// picking a code path, e.g. allocating a large contiguous block when the pool can't help.
+    fn can_satisfy(&self, n: usize) -> bool {
+        self.len() >= n
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The pressure on the pool as a ratio from 0.0 to 1.0, i.e. the share of the total capacity
+    /// that is not available in the pool at the moment: 0.0 if all elements are idle in the pool,
+    /// and 1.0 if the pool has been drained, where further `get` calls will fall back to creating
+    /// new elements. Callers can poll this value to throttle or shed the load upstream.
+    ///
+    /// The value is a best-effort snapshot of the bucket occupancy, which could be stale by the time
+    /// it's returned if others are using the pool concurrently.
+    fn pressure(&self) -> f32 {
+        let total = self.total_capacity();
+        if total == 0 {
+            return 1.0;
+        }
+
+        1.0 - (self.len().min(total) as f32 / total as f32)
+    }
+}
+
+impl<T> PoolState for SyncPool<T> {
+    fn expansion_enabled(&self) -> bool {
+        let configure = self.configure.load(Ordering::SeqCst);
+        configure & CONFIG_ALLOW_EXPANSION > 0
+    }
+
+    /// The number of misses since the pool was created or last expanded. A miss is counted every time
+    /// `get` can't offer an element from the pool, either because the pool is empty (or too
+    /// contended to find an element in time), or because the write barrier is raised, and hence a
+    /// new element is created by the pool's builder instead.
+    ///
+    /// See `set_reset_misses_on_expand` to keep counting across the expansions, and
+    /// `reset_miss_count` to restart the count by hand.
+    fn miss_count(&self) -> usize {
+        self.stats.miss_count.load(Ordering::Acquire)
+    }
+
+    /// The number of elements the pool can hold, i.e. the number of buckets (including the ones
+    /// added by `expand`) times `SLOT_CAP`.
+    fn capacity(&self) -> usize {
+        self.slots.len() * SLOT_CAP
+    }
+
+    fn len(&self) -> usize {
+        self.slots
+            .iter()
+            .fold(0, |sum, item| sum + item.size_hint())
+    }
+}
+
+impl<T> SyncPool<T> {
+    /// If a reset handle has been installed, either via `reset_handle` or via `set_reset`, e.g. to
+    /// assert in tests that the pool will sanitize the returned elements.
+    pub fn has_reset_handle(&self) -> bool {
+        self.reset_handle.is_some()
+    }
+
+    pub fn cursor_policy(&self) -> CursorPolicy {
+        if self.configure.load(Ordering::Relaxed) & CONFIG_ADVANCING_CURSOR > 0 {
+            CursorPolicy::Advancing
+        } else {
+            CursorPolicy::Sticky
+        }
+    }
+
+    /// The order in which `get` hands out the idle elements, see `set_order`.
+    pub fn checkout_order(&self) -> CheckoutOrder {
+        if self.configure.load(Ordering::Relaxed) & CONFIG_LIFO > 0 {
+            CheckoutOrder::Lifo
+        } else {
+            CheckoutOrder::RoundRobin
+        }
+    }
+
+    /// When the reset handle runs on the pooled elements, see `set_reset_timing`.
+    pub fn reset_timing(&self) -> ResetTiming {
+        if self.configure.load(Ordering::Relaxed) & CONFIG_RESET_ON_CHECKOUT > 0 {
+            ResetTiming::OnCheckout
+        } else {
+            ResetTiming::OnReturn
+        }
+    }
+
+    /// The number of times `expand` (or `try_expand`) has been invoked while the pool is not allowed
+    /// to expand. A non-zero value usually means the caller forgot to `allow_expansion(true)`.
+    pub fn misconfig_count(&self) -> usize {
+        self.stats.misconfig_count.load(Ordering::Acquire)
+    }
+
+    /// The number of times `expand` (or `try_expand`) has been refused because the pool has already
+    /// reached its expansion cap. Together with `misconfig_count`, this tells why the expansions
+    /// didn't happen; a rising count while the `miss_count` keeps growing means the pool is maxed
+    /// out, and more pools (or a rework of the workload) shall be provisioned.
+    pub fn at_cap_count(&self) -> usize {
+        self.stats.at_cap_count.load(Ordering::Acquire)
+    }
+
+    /// The number of returned elements that have been dropped by `put`, because the pool had
+    /// already held the maximum number of idle elements set by `set_max_idle`.
+    pub fn idle_drop_count(&self) -> usize {
+        self.stats.idle_drop_count.load(Ordering::Acquire)
+    }
+
+    /// The number of idle elements that have been dropped by `put` to make room for the returned
+    /// ones, under `FullPolicy::EvictOldest`.
+    pub fn eviction_count(&self) -> usize {
+        self.stats.eviction_count.load(Ordering::Acquire)
+    }
+
+    /// The number of returned elements that have been dropped because their reset has failed, i.e.
+    /// the async reset of `put_async` (see `set_async_reset`), or the reset handle panicking on a
+    /// guard's element during unwinding (see `set_panic_reclaim`).
+    pub fn reset_failure_count(&self) -> usize {
+        self.stats.reset_failure_count.load(Ordering::Acquire)
+    }
+
+    /// The number of elements handed out by the checkouts since the pool was created, including the
+    /// ones created on the fly when the pool couldn't offer one. The counter is monotonic (it's not
+    /// affected by `expand` or `reset_peak`) and always on, at the cost of a relaxed increment per
+    /// checkout. Over time, `total_returns` shall roughly follow it, and a growing gap between the
+    /// two signals a leak, or a pool too small to take the elements back.
+    pub fn total_checkouts(&self) -> usize {
+        self.stats.checkout_count.load(Ordering::Acquire)
+    }
+
+    /// The number of elements the pool has taken back via `put` (or any of its variants) since it
+    /// was created, not counting the ones dropped or handed back, nor the ones added by `refill`
+    /// or `expand`, see `total_checkouts`.
+    pub fn total_returns(&self) -> usize {
+        self.stats.return_count.load(Ordering::Acquire)
+    }
+
+    /// The number of panics of the element builder caught while creating an element on the fly,
+    /// either by `get_checked`, or by the other checkouts if `set_catch_builder_panic` is on.
+    pub fn builder_panic_count(&self) -> usize {
+        self.stats.builder_panic_count.load(Ordering::Acquire)
+    }
+
+    /// The number of elements that are considered as checked out of the pool and not yet returned,
+    /// i.e. a live counter raised by every checkout (including the fallback allocations made by
+    /// `get`) and lowered by every return taken by the pool (including the ones dropped because of
+    /// the idle cap), so reading it never scans the buckets. The vacancies of a pool that hasn't
+    /// been filled up yet (see `SyncPool::with_size_lazy`) count as checked out too, until they're
+    /// filled by the prefill or `refill`.
+    ///
+    /// The value is a best-effort snapshot. While the program is running, a stable or fluctuating
+    /// count is expected; a count that keeps growing over time signals that elements are checked
+    /// out but never `put` back, i.e. a leak. Note that elements that are rejected by a full pool
+    /// in `put` (and then dropped by the caller) will also be counted as outstanding.
+    pub fn outstanding_count(&self) -> usize {
+        self.stats.outstanding.load(Ordering::Acquire).max(0) as usize
+    }
+
+    /// The highest `outstanding_count` observed when an element is checked out (i.e. by `get`,
+    /// including its fallback allocations), since the pool was created or last `reset_peak`. This
+    /// is the working-set size the program actually needs, so a pool created with at least this
+    /// many elements (see `buckets_for`) would avoid the fallback allocations entirely.
+    pub fn peak_outstanding(&self) -> usize {
+        self.stats.peak_outstanding.load(Ordering::Acquire)
+    }
+
+    /// An estimate of the memory footprint of the pool in bytes, i.e. the pool struct itself, the
+    /// buckets (including the spare capacity of the bucket storage), and the heap memory of all the
+    /// elements the pool can hold, as if every slot were occupied. The heap memory owned by the
+    /// elements themselves (e.g. the buffer of a pooled `Vec`) is not included.
+    pub fn approx_bytes(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.slots.capacity() * mem::size_of::<Bucket2<T>>()
+            + self.capacity() * mem::size_of::<T>()
+    }
+
+    /// The number of buckets that are empty, partially occupied, and full, in that order. Many full
+    /// buckets while the `miss_count` keeps rising suggest that the pool shall be expanded, while
+    /// many empty buckets suggest that the pool is oversized for the workload.
+    ///
+    /// Same as `len`, this is a best-effort snapshot of the bucket occupancy.
+    pub fn occupancy_breakdown(&self) -> (usize, usize, usize) {
+        self.slots
+            .iter()
+            .fold((0, 0, 0), |(empty, partial, full), bucket| {
+                match bucket.size_hint() {
+                    0 => (empty + 1, partial, full),
+                    SLOT_CAP => (empty, partial, full + 1),
+                    _ => (empty, partial + 1, full),
+                }
+            })
+    }
+}
+
+pub trait PoolManager<T> {
+    fn reset_handle(&mut self, handle: fn(&mut T)) -> &mut Self;
+    fn allow_expansion(&mut self, allow: bool) -> &mut Self;
+    fn expand(&mut self, additional: usize, block: bool) -> bool;
+    fn refill(&mut self, count: usize) -> usize;
+}
+
+/// The pool manager that provide many useful utilities to keep the SyncPool close to the needs of
+/// the caller program.
+impl<T> PoolManager<T> for SyncPool<T> {
+    /// Set or update the reset handle. If set, the reset handle will be invoked every time an element
+    /// has been returned back to the pool (i.e. calling the `put` method), regardless of if the element
+    /// is created by the pool or not.
+    ///
+    /// The handle replacement is race-free by the following invariant: replacing the handle requires
+    /// an exclusive `&mut self` borrow, and so does every API that reads the handle (e.g. `put`),
+    /// hence no `put` can observe the handle while it's being replaced, nor can the old handle be
+    /// dropped while a `put` is still running it. If any of the pool APIs could be reached via a
+    /// shared reference in the future, the handle must be moved behind an atomic (or similar) first.
+    ///
+    /// The handle is replaced under the write barrier. If someone else holds the barrier (e.g. an
+    /// `expand` in progress on another thread), the call waits until it's lowered, instead of
+    /// dropping the new handle.
+    ///
+    /// To reset the elements with a closure (e.g. one capturing some states), use `set_reset`.
+    fn reset_handle(&mut self, handle: fn(&mut T)) -> &mut Self {
+        self.install_reset(ResetHandle::Func(handle));
+        self
+    }
+
+    /// Set or update the settings that if we will allow the `SyncPool` to be expanded.
+    fn allow_expansion(&mut self, allow: bool) -> &mut Self {
+        if !(self.expansion_enabled() ^ allow) {
+            // not flipping the configuration, return
+            return self;
+        }
+
+        self.update_config(CONFIG_ALLOW_EXPANSION, allow);
+        self
+    }
+
+    /// Try to expand the `SyncPool` and add more elements to it. Usually invoke this API only when
+    /// the caller is certain that the pool is under pressure, and that a short block to the access
+    /// of the pool won't cause serious issues, since the function will block the current caller's
+    /// thread until it's finished (i.e. get the opportunity to raise the writer's barrier and wait
+    /// everyone to leave).
+    ///
+    /// If we're unable to expand the pool, it's due to one of the following reasons: 1) someone has
+    /// already raised the writer's barrier and is likely modifying the pool, we will leave immediately,
+    /// and it's up to the caller if they want to try again; 2) we've waited too long but still couldn't
+    /// obtain an exclusive access to the pool, and similar to reason 1), we will quit now.
+    ///
+    /// The number of `additional` buckets is clamped to what the expansion cap still allows, so a
+    /// pathological request (e.g. `usize::MAX`) grows the pool to the cap instead of overflowing.
+    fn expand(&mut self, additional: usize, block: bool) -> bool {
+        matches!(self.expand_pool(additional, block), ExpandResult::Grew(_))
+    }
+
+    /// Due to contentious access to the pool, sometimes the `put` action could not finish and return
+    /// the element to the pool successfully. Overtime, this could cause the number of elements in the
+    /// pool to dwell. This would only happen slowly if we're running a very contentious multithreading
+    /// program, but it surely could happen. If the caller detects such situation, they can invoke the
+    /// `refill` API and try to refill the pool with elements.
+    ///
+    /// We will try to refill as many elements as requested, up to the vacancies of the pool and the
+    /// room left under the idle cap (see `set_max_idle`), and return the number of elements that
+    /// have been stored.
+    fn refill(&mut self, additional: usize) -> usize {
+        if self.foreign_thread() {
+            return 0;
+        }
+
+        let cap = self.capacity();
+        let len = self.len();
+        let quota = additional
+            .min(cap - len)
+            .min(self.max_idle.saturating_sub(len));
+
+        if quota == 0 {
+            return 0;
+        }
+
+        let mut count = 0;
+        let timeout = Instant::now().add(Duration::from_millis(16));
+
+        // try to put `quota` number of elements into the pool
+        while count < quota {
+            self.report_alloc(AllocEvent::Refill);
+
+            let mut val = make_elem(&self.builder);
+            let mut runs = 0;
+
+            // retry to put the allocated element into the pool.
+            // not via `put`, since the new elements are not returns
+            loop {
+                match self.release_slots(val, None, true, false, true, false) {
+                    Ok(PutOutcome::Stored { .. }) => break,
+                    // the pool drops what it can't hold, and would drop the elements to come too
+                    Ok(PutOutcome::Dropped) => return count,
+                    Ok(PutOutcome::Rejected(ret)) | Err(ret) => val = ret,
+                }
+
+                runs += 1;
+
+                // timeout
+                if Instant::now() > timeout {
+                    return count;
+                }
+
+                // check the pool length for every 4 failed attempts to put the element into the pool.
+                if runs % 4 == 0 && self.len() == cap {
+                    return count;
+                }
+
+                // relax a bit
+                if runs > 8 {
+                    thread::yield_now();
+                } else {
+                    cpu_relax(runs / 2);
+                }
+            }
+
+            // the vacancy is no longer counted as checked out
+            self.stats.outstanding.fetch_sub(1, Ordering::Relaxed);
+            count += 1;
+        }
+
+        count
+    }
+}
+
+impl<T> SyncPool<T> {
+    /// Same as `reset_handle`, but takes a closure, which will be boxed and owned by the pool, and
+    /// dropped when replaced or when the pool is dropped. Note that `duplicate` can't clone a boxed
+    /// closure, so the duplicated pool won't carry over the reset handle set via this API.
+    pub fn set_reset<F>(&mut self, handle: F) -> &mut Self
+    where
+        F: FnMut(&mut T) + Send + 'static,
+    {
+        self.install_reset(ResetHandle::Closure(Box::new(handle)));
+        self
+    }
+
+    /// Set or update the busy-waiting settings used by `put` (and other visitors) while the write
+    /// barrier is raised by someone else. Platforms with cheaper context switches may prefer a
+    /// smaller `offset`, while heavily contended deployments may want to keep spinning for longer.
+    pub fn spin_config(&mut self, config: SpinConfig) -> &mut Self {
+        // the visitors spin `1 << exponent` times, keep the exponent well below the bit width
+        let offset = config.offset.min(SPIN_EXPONENT_CAP);
+        self.spin = SpinConfig {
+            offset,
+            cap: config.cap.min(offset),
+        };
+        self
+    }
+
+    /// Set or update how the bucket cursors move after a successful `get` or `put`. `Sticky` keeps
+    /// reusing the warm bucket, while `Advancing` spreads the load over all the buckets.
+    pub fn set_cursor_policy(&mut self, policy: CursorPolicy) -> &mut Self {
+        if self.cursor_policy() == policy {
+            // not flipping the configuration, return
+            return self;
+        }
+
+        self.update_config(CONFIG_ADVANCING_CURSOR, policy == CursorPolicy::Advancing);
+        self
+    }
+
+    /// Set the order in which `get` hands out the idle elements, see `CheckoutOrder`. In the LIFO
+    /// order, `put` records the last few positions the elements have been returned to, and `get`
+    /// tries those positions (the latest first) before scanning the buckets, such that short-lived
+    /// elements are reused while they're still warm in the cache.
+    pub fn set_order(&mut self, order: CheckoutOrder) -> &mut Self {
+        let lifo = order == CheckoutOrder::Lifo;
+        if !lifo {
+            // forget the recorded positions, such that re-enabling the mode won't try stale ones
+            self.recent
+                .0
+                .iter()
+                .for_each(|entry| entry.store(0, Ordering::Relaxed));
+        }
+
+        self.update_config(CONFIG_LIFO, lifo);
+        self
+    }
+
+    /// Set when the reset handle runs on the pooled elements, see `ResetTiming`. With
+    /// `ResetTiming::OnCheckout`, `put` stores the returned element without resetting it, and the
+    /// checkout that hands the element out next runs the reset handle before handing it out, such
+    /// that an expensive reset is paid on the checkout path instead of the return path. The reset
+    /// still runs outside of the slot lock either way.
+    ///
+    /// The pool marks the positions of the elements stored without a reset, and only those are
+    /// reset when they're checked out, so the elements the pool has been created with, the ones
+    /// created on the fly (i.e. by the builder), and the ones returned via `put_no_reset` are not.
+    /// The marks outlive the mode: after switching back to `ResetTiming::OnReturn`, the elements
+    /// stored dirty in the meantime are still reset on their way out.
+    pub fn set_reset_timing(&mut self, timing: ResetTiming) -> &mut Self {
+        self.update_config(CONFIG_RESET_ON_CHECKOUT, timing == ResetTiming::OnCheckout);
+        self
+    }
+
+    /// Set the maximum number of idle elements the pool will hold. Once the pool holds `max` idle
+    /// elements, further `put` calls will reset and drop the returned elements instead of storing
+    /// them, which caps the steady-state memory regardless of how much the pool has been expanded.
+    /// Use `usize::MAX` to lift the limit.
+    pub fn set_max_idle(&mut self, max: usize) -> &mut Self {
+        self.max_idle = max;
+        self
+    }
+
+    /// Set the maximum number of bucket probes a `get` or `put` will make before giving up, i.e.
+    /// `get` falls back to creating a new element, and `put` hands the element back. By default, `get`
+    /// probes up to all the buckets and `put` up to twice as many, which could take long on a heavily
+    /// expanded pool; a smaller limit bounds the worst-case latency at the cost of more fallbacks.
+    /// The limit is at least 1, and use `usize::MAX` to restore the default behavior.
+    pub fn set_max_probe(&mut self, max: usize) -> &mut Self {
+        self.max_probe = max.max(1);
+        self
+    }
+
+    /// Set the memory budget of the pool in bytes: `expand` (and `try_expand`) refuses to grow the
+    /// pool, with `ExpandResult::OverBudget`, if the `approx_bytes` estimate after the growth would
+    /// exceed the budget, e.g. such that an aggressive autoscaler can't push the process out of
+    /// memory. The budget doesn't shrink a pool already over it, and the heap memory owned by the
+    /// elements isn't accounted for, same as `approx_bytes`. Use `usize::MAX` to lift the budget.
+    pub fn set_memory_budget(&mut self, bytes: usize) -> &mut Self {
+        self.memory_budget = bytes;
+        self
+    }
+
+    /// Arm (or disarm) the one-time prefill: the next `get` that can't be served from the pool (and
+    /// would fall back to creating a new element) fills up all the vacancies of the pool under the
+    /// write barrier instead, and is then served from the pool. The latch is cleared by that `get`,
+    /// so the pool is filled up exactly once per arming; the later faults fall back as usual. The
+    /// new elements are reported to the `alloc_hook` as `AllocEvent::Refill`.
+    ///
+    /// This is the middle ground between the eager prefill of the constructors and the pure lazy
+    /// allocation, see `SyncPool::with_size_lazy`, which creates an empty pool armed this way. Until
+    /// it's filled up, the pool counts its missing elements as checked out, see `outstanding_count`.
+    pub fn set_prefill_on_first_fault(&mut self, enable: bool) -> &mut Self {
+        self.prefill_armed.store(enable, Ordering::Release);
+        self
+    }
+
+    /// Set what `put` does when it can't find a vacancy within its probes, see `FullPolicy`. With
+    /// `FullPolicy::EvictOldest`, each position is stamped by the `put` filling it, and the position
+    /// with the oldest stamp takes the returned element, which costs a scan of all the buckets when
+    /// the pool is full; the evictions are counted by `eviction_count`. Note that the strict put mode
+    /// still panics when the returned element can be neither placed nor swapped in.
+    ///
+    /// The stamps are only kept under `FullPolicy::EvictOldest`: switching to it stamps the idle
+    /// elements as the oldest ones, and switching away drops the stamps. This waits for the ongoing
+    /// checkouts and returns to finish, same as `expand`.
+    pub fn set_full_policy(&mut self, policy: FullPolicy) -> &mut Self {
+        let stamped = policy == FullPolicy::EvictOldest;
+
+        if stamped != (self.full_policy == FullPolicy::EvictOldest) {
+            let _guard = ExclusiveGuard::acquire(&self.visitor_counter, &self.waiters);
+            self.slots
+                .iter_mut()
+                .for_each(|bucket| bucket.track_stamps(stamped));
+        }
+
+        self.full_policy = policy;
+        self
+    }
+
+    /// Restart the measurement window of `peak_outstanding` from the current `outstanding_count`.
+    pub fn reset_peak(&mut self) -> &mut Self {
+        self.stats
+            .peak_outstanding
+            .store(self.outstanding_count(), Ordering::Release);
+        self
+    }
+
+    /// Set or update if a successful expansion restarts the `miss_count` from 0, which is the
+    /// default: the misses counted before the expansion say little about the bigger pool. Turn it
+    /// off to keep a monotonic count over the lifetime of the pool, e.g. to graph the cumulative
+    /// misses; `reset_miss_count` still restarts the count either way.
+    pub fn set_reset_misses_on_expand(&mut self, reset: bool) -> &mut Self {
+        self.update_config(CONFIG_KEEP_MISSES, !reset);
+        self
+    }
+
+    /// Restart the `miss_count` from 0, regardless of `set_reset_misses_on_expand`.
+    pub fn reset_miss_count(&mut self) -> &mut Self {
+        self.stats.miss_count.store(0, Ordering::Release);
+        self
+    }
+
+    /// Set or update if `put` shall panic, instead of handing the element back to the caller, when
+    /// it can't find a vacancy for the element, e.g. because the pool is full or too contended. The
+    /// panic message contains the pool's occupancy and the number of buckets probed. This is off by
+    /// default; turn it on where such a `put` shall never happen by design, e.g. in tests, such that
+    /// the silently dropped elements become loud failures. The elements dropped because of the idle
+    /// cap (see `set_max_idle`) are not affected.
+    pub fn set_strict_put(&mut self, strict: bool) -> &mut Self {
+        if !((self.configure.load(Ordering::Relaxed) & CONFIG_STRICT_PUT > 0) ^ strict) {
+            // not flipping the configuration, return
+            return self;
+        }
+
+        self.update_config(CONFIG_STRICT_PUT, strict);
+        self
+    }
+
+    /// Set or update if a panic of the element builder, while `get` (or the other checkouts) creates
+    /// an element on the fly, is caught and re-raised with a message identifying the pool, i.e. the
+    /// `BuilderPanic` of `get_checked`, such that a failing construction can be traced to its pool.
+    /// The caught panics are counted by `builder_panic_count`. This is off by default, since the
+    /// `catch_unwind` around the builder has a cost on every fallback allocation; the checkouts
+    /// served from the pool are not affected either way. Use `get_checked` to get the failure as an
+    /// error instead.
+    pub fn set_catch_builder_panic(&mut self, catch: bool) -> &mut Self {
+        self.update_config(CONFIG_CATCH_BUILDER_PANIC, catch);
+        self
+    }
+
+    /// Bind the pool to the current thread, for elements that must be destroyed on the thread that
+    /// created them (e.g. some GUI or GPU handles). In the thread-affine mode, the pool only
+    /// creates, hands out, takes back and drops its elements on the bound thread, and on any other
+    /// thread:
+    ///
+    /// - `get` doesn't touch the pool and creates a new element for the calling thread instead,
+    ///   which counts as a miss (and a fallback) same as an empty pool;
+    /// - `put` (and `put_bucket`) refuses the element and hands it back, such that the caller can send
+    ///   it to the right thread, rather than have it silently dropped on the wrong one;
+    /// - `expand` won't grow the pool (`try_expand` returns `ExpandResult::Disabled`), and `refill`
+    ///   adds nothing;
+    /// - dropping the pool leaks the idle elements instead of destroying them.
+    ///
+    /// Hence the call sites shall either stay on the bound thread, or be prepared to get their
+    /// elements refused by `put`. Call this right after the pool is created, such that every pooled
+    /// element has been created on the bound thread. Pass `false` to leave the mode.
+    pub fn set_thread_affine(&mut self, affine: bool) -> &mut Self {
+        self.owner = if affine {
+            Some(thread::current().id())
+        } else {
+            None
+        };
+
+        self
+    }
+
+    /// Enable the auto shrinking, which releases the buckets whose elements have all been idle in the
+    /// pool for at least `idle_threshold`, such that the memory stays proportional to the recent
+    /// load. The policy is driven by the caller's calls to `maybe_shrink` instead of a background
+    /// thread, and the buckets are checked at most once every `check_interval`. Calling this again
+    /// replaces the policy and restarts the idle tracking.
+    pub fn set_auto_shrink(
+        &mut self,
+        idle_threshold: Duration,
+        check_interval: Duration,
+    ) -> &mut Self {
+        self.auto_shrink.replace(AutoShrink {
+            idle_threshold,
+            check_interval,
+            last_check: Instant::now(),
+            idle_since: Vec::new(),
+        });
+
+        self
+    }
+
+    /// Set the number of buckets the auto shrinking (see `maybe_shrink`) won't release the pool
+    /// below, e.g. the initial number of buckets, such that the pool won't shrink past its baseline
+    /// between bursts only to be expanded again. The floor is clamped between 1 and `MAX_BUCKETS`,
+    /// i.e. the most buckets an expansion may grow the pool to, and it doesn't expand a pool that is
+    /// currently smaller.
+    pub fn set_min_buckets(&mut self, min: usize) -> &mut Self {
+        self.min_buckets = min.clamp(1, MAX_BUCKETS);
+        self
+    }
+
+    /// Set or update the hook to be invoked every time `get` can't offer an element from the pool and
+    /// falls back to creating a new one. The hook receives the label passed to `get_labeled`, or
+    /// `None` if the element is requested via `get` (or the APIs built on it, e.g. `Scope::get`).
+    /// Keep the hook cheap, since it runs on the caller's thread right before the allocation.
+    pub fn fallback_hook(&mut self, hook: fn(Option<&'static str>)) -> &mut Self {
+        self.fallback_hook.replace(hook);
+        self
+    }
+
+    /// Tag the pool, and set or update the hook to be invoked with the tag for every allocation the
+    /// pool makes (see `AllocEvent`), e.g. to attribute the allocator pressure in a jemalloc or
+    /// mimalloc profile to the pools driving it. Without the hook, the pool only pays for a `None`
+    /// check at each allocation. Same as `fallback_hook`, the hook runs on the caller's thread.
+    pub fn alloc_hook(
+        &mut self,
+        tag: &'static str,
+        hook: fn(&'static str, AllocEvent),
+    ) -> &mut Self {
+        self.alloc_hook.replace((tag, hook));
+        self
+    }
+
+    /// Set or update the observer to be invoked with every element the pool drops on its own, right
+    /// before the element is dropped, e.g. to account for the release of the resources it holds.
+    /// The pool drops the returned elements over `set_max_idle` or under `FullPolicy::DropIncoming`,
+    /// the idle elements evicted under `FullPolicy::EvictOldest`, the elements of the buckets
+    /// released by `maybe_shrink`, the ones `transfer_into` has no room for in the destination, the
+    /// ones `BucketView::retain` drops within `with_exclusive`, and the idle elements dropped by
+    /// `into_storage`. The elements handed back by `put` are the caller's to drop.
+    ///
+    /// The observer is never invoked while holding a slot lock, but it may run while the write
+    /// barrier is raised (i.e. in `maybe_shrink`), so it must not call back into the pool. Same as
+    /// `set_reset`, `duplicate` won't carry over the observer.
+    pub fn set_on_evict<F>(&mut self, observer: F) -> &mut Self
+    where
+        F: Fn(&T) + Send + 'static,
+    {
+        self.on_evict.replace(Box::new(observer));
+        self
+    }
+
+    /// Harden the guards (i.e. `Scope` and `UninitGuard`) against a panic while elements are checked
+    /// out, same as `set_panic_reclaim(true)`.
+    pub fn install_panic_reclaim(&mut self) -> &mut Self {
+        self.set_panic_reclaim(true)
+    }
+
+    /// Set or update if the guards (i.e. `Scope` and `UninitGuard`) are hardened against a panic
+    /// while elements are checked out. The guards always return their elements to the pool when the
+    /// thread unwinds, but by default the returned elements go through the reset handle as usual.
+    /// If the reset handle panics as well, that's a panic during a panic, and the process will
+    /// abort. With the panic reclaim on, elements returned during unwinding are reset under
+    /// `catch_unwind`, and the ones the reset handle panics on are dropped (and counted by
+    /// `reset_failure_count`) rather than stored dirty, so no second panic escapes the pool, and
+    /// the pool is never handed a half-reset element.
+    ///
+    /// Elements checked out as bare boxes via `get` are not tracked by the pool, and are still lost
+    /// when the owning thread panics. This is off by default.
+    pub fn set_panic_reclaim(&mut self, enable: bool) -> &mut Self {
+        if !((self.configure.load(Ordering::Relaxed) & CONFIG_PANIC_RECLAIM > 0) ^ enable) {
+            // not flipping the configuration, return
+            return self;
+        }
+
+        self.update_config(CONFIG_PANIC_RECLAIM, enable);
+        self
+    }
+
+    /// Similar to `expand`, but the API will never block the caller's thread. Instead of a simple
+    /// `bool`, the returned `ExpandResult` tells why the expansion didn't happen, such that the
+    /// caller can decide if it makes sense to retry later (i.e. `Contended`), or give up (i.e.
+    /// `Disabled`, `AtCap` or `OverBudget`).
+    pub fn try_expand(&mut self, additional: usize) -> ExpandResult {
+        self.expand_pool(additional, false)
+    }
+
+    /// Same as `expand`, but grow the pool to a total capacity of at least `target_objects`
+    /// elements, i.e. `buckets_for(target_objects)` buckets, instead of by a number of additional
+    /// buckets. Do nothing and return `false` if the pool can already hold that many elements; the
+    /// growth is clamped by the expansion cap the same way.
+    pub fn expand_to(&mut self, target_objects: usize, block: bool) -> bool {
+        let additional = buckets_for(target_objects).saturating_sub(self.slots.len());
+        if additional == 0 {
+            return false;
+        }
+
+        self.expand(additional, block)
+    }
+}
+
+/// The minimal interface of an object pool, such that the code using a pool can be generic over
+/// (or take a `dyn ObjectPool<T>` of) the pool backends, e.g. a `SyncPool`, an `ArrayPool`, or a
+/// `PoolHandle`, or a pool of another crate wrapped in this trait.
+pub trait ObjectPool<T> {
+    /// Check out an element, which is created on the fly if the pool can't offer one.
+    fn acquire(&mut self) -> Box<T>;
+
+    /// Return the element to the pool, or hand it back if the pool can't take it.
+    fn release(&mut self, val: Box<T>) -> Option<Box<T>>;
+}
+
+impl<T> ObjectPool<T> for SyncPool<T> {
+    /// Same as `SyncPool::get`.
+    fn acquire(&mut self) -> Box<T> {
+        self.get()
+    }
+
+    /// Same as `SyncPool::put`.
+    fn release(&mut self, val: Box<T>) -> Option<Box<T>> {
+        self.put(val)
+    }
+}
+
+#[cfg(test)]
+mod pool_tests {
+    use super::*;
+    use std::vec;
+
+    struct BigStruct {
+        a: u32,
+        b: u32,
+        c: Vec<u8>,
+    }
+
+    impl BigStruct {
+        fn new() -> Self {
             BigStruct {
                 a: 1,
                 b: 42,
@@ -630,34 +3785,2125 @@ mod pool_tests {
             }
         }
 
-        fn initializer(mut self: Box<Self>) -> Box<Self> {
-            self.a = 1;
-            self.b = 42;
-            self.c = vec::from_elem(0u8, 0x1_000_000);
+        fn initializer(mut self: Box<Self>) -> Box<Self> {
+            self.a = 1;
+            self.b = 42;
+            self.c = vec::from_elem(0u8, 0x1_000_000);
+
+            self
+        }
+    }
+
+    #[test]
+    fn use_packer() {
+        let mut pool = SyncPool::with_packer(BigStruct::initializer);
+
+        let big_box = pool.get();
+
+        assert_eq!(big_box.a, 1);
+        assert_eq!(big_box.b, 42);
+        assert_eq!(big_box.c.len(), 0x1_000_000);
+    }
+
+    #[test]
+    fn foreign_impls() {
+        // a downstream pool implementing only the original trait methods, which keeps compiling
+        // since the later additions to the traits come with default bodies
+        struct Fixed(usize);
+
+        impl PoolState for Fixed {
+            fn expansion_enabled(&self) -> bool {
+                false
+            }
+
+            fn miss_count(&self) -> usize {
+                0
+            }
+
+            fn capacity(&self) -> usize {
+                4
+            }
+
+            fn len(&self) -> usize {
+                self.0
+            }
+        }
+
+        impl PoolManager<u8> for Fixed {
+            fn reset_handle(&mut self, _handle: fn(&mut u8)) -> &mut Self {
+                self
+            }
+
+            fn allow_expansion(&mut self, _allow: bool) -> &mut Self {
+                self
+            }
+
+            fn expand(&mut self, _additional: usize, _block: bool) -> bool {
+                false
+            }
+
+            fn refill(&mut self, count: usize) -> usize {
+                let added = count.min(4 - self.0);
+                self.0 += added;
+                added
+            }
+        }
+
+        let mut pool = Fixed(1);
+        assert_eq!(pool.total_capacity(), 4);
+        assert!(pool.can_satisfy(1));
+        assert!(!pool.can_satisfy(2));
+        assert_eq!(pool.pressure(), 0.75);
+
+        assert_eq!(pool.refill(8), 3);
+        assert!(!pool.is_empty());
+        assert_eq!(pool.pressure(), 0.0);
+    }
+
+    #[test]
+    fn use_builder() {
+        let mut pool = SyncPool::with_builder(BigStruct::new);
+
+        let big_box = pool.get();
+
+        assert_eq!(big_box.a, 1);
+        assert_eq!(big_box.b, 42);
+        assert_eq!(big_box.c.len(), 0x1_000_000);
+    }
+
+    #[test]
+    fn reset_handle_interleaved() {
+        use std::sync::{Arc, Mutex};
+
+        // `SyncPool` is `!Sync`, so the workers are serialized by the mutex: this covers handle
+        // replacements interleaved with the checkouts and returns of other threads, not visitors
+        // running in parallel, which the `&mut self` receivers rule out.
+
+        fn reset_one(ary: &mut [u8; 32]) {
+            ary[0] = 1;
+        }
+
+        fn reset_two(ary: &mut [u8; 32]) {
+            ary[0] = 2;
+        }
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(32);
+        pool.reset_handle(reset_one);
+
+        let pool = Arc::new(Mutex::new(pool));
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let pool = Arc::clone(&pool);
+                thread::spawn(move || {
+                    for _ in 0..1024 {
+                        let mut ary = pool.lock().unwrap().get();
+                        assert_ne!(ary[0], 42);
+
+                        ary[0] = 42;
+                        pool.lock().unwrap().put(ary);
+                    }
+                })
+            })
+            .collect();
+
+        for i in 0..256 {
+            let handle = if i % 2 == 0 { reset_one } else { reset_two };
+            pool.lock().unwrap().reset_handle(handle);
+        }
+
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        let mut pool = pool.lock().unwrap();
+        for _ in 0..32 {
+            assert_ne!(pool.get()[0], 42);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "bucket-stats")]
+    fn hot_buckets() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(16);
+        pool.set_cursor_policy(CursorPolicy::Sticky);
+
+        // drain the first bucket, then keep asking: the first bucket keeps denying first
+        let checkouts: Vec<_> = (0..12).map(|_| pool.get()).collect();
+
+        let stats = pool.hot_buckets();
+        assert_eq!(stats.len(), 2);
+        assert!(stats[0].1 >= stats[1].1);
+        assert!(stats[0].1 > 0);
+
+        drop(checkouts);
+    }
+
+    #[test]
+    fn duplicate() {
+        fn reset(ary: &mut [u8; 32]) {
+            ary[0] = 7;
+        }
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.reset_handle(reset)
+            .allow_expansion(true)
+            .set_full_policy(FullPolicy::DropIncoming);
+
+        let mut ary = pool.get();
+        ary[1] = 42;
+        pool.put(ary);
+
+        let checkout = pool.get();
+        pool.put(checkout);
+        pool.visitor_counter.1.store(true, Ordering::SeqCst);
+        pool.get();
+        pool.visitor_counter.1.store(false, Ordering::SeqCst);
+        assert_eq!(pool.miss_count(), 1);
+
+        let mut copy = pool.duplicate();
+        assert_eq!(copy.len(), 8);
+        assert_eq!(copy.capacity(), 8);
+        assert_eq!(copy.miss_count(), 0);
+        assert!(copy.expansion_enabled());
+
+        // the full policy is carried over, the full copy drops the incoming element
+        assert!(copy.put(Box::new([0u8; 32])).is_none());
+        assert_eq!(copy.len(), 8);
+
+        // the copies are independent from the origin
+        let copied: Vec<_> = (0..8).map(|_| copy.get()).collect();
+        assert_eq!(copied.iter().filter(|ary| ary[1] == 42).count(), 1);
+        assert_eq!(copy.len(), 0);
+        assert_eq!(pool.len(), 8);
+
+        copy.put(Box::new([0u8; 32]));
+        assert_eq!(copy.get()[0], 7);
+    }
+
+    #[test]
+    fn put_no_reset() {
+        fn reset(ary: &mut [u8; 32]) {
+            ary[0] = 0;
+        }
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.reset_handle(reset);
+
+        let mut ary = pool.get();
+        ary[0] = 42;
+        assert!(pool.put_no_reset(ary).is_none());
+
+        let found = (0..8).map(|_| pool.get()).filter(|ary| ary[0] == 42).count();
+        assert_eq!(found, 1);
+    }
+
+    #[test]
+    fn miss_count() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        let _checkouts: Vec<_> = (0..10).map(|_| pool.get()).collect();
+        assert_eq!(pool.miss_count(), 2);
+
+        // fallback while the write barrier is raised also counts
+        pool.visitor_counter.1.store(true, Ordering::SeqCst);
+        pool.get();
+        pool.visitor_counter.1.store(false, Ordering::SeqCst);
+        assert_eq!(pool.miss_count(), 3);
+
+        pool.allow_expansion(true);
+        assert!(pool.expand(1, true));
+        assert_eq!(pool.miss_count(), 0);
+    }
+
+    #[test]
+    fn keep_misses_on_expand() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.allow_expansion(true).set_reset_misses_on_expand(false);
+
+        let _checkouts: Vec<_> = (0..10).map(|_| pool.get()).collect();
+        assert!(pool.expand(1, true));
+        assert_eq!(pool.miss_count(), 2);
+
+        pool.reset_miss_count();
+        assert_eq!(pool.miss_count(), 0);
+
+        pool.get();
+        pool.set_reset_misses_on_expand(true);
+        assert!(pool.expand(1, true));
+        assert_eq!(pool.miss_count(), 0);
+    }
+
+    #[test]
+    fn visitor_handshake() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+
+        {
+            let _guard = VisitorGuard::register(&pool.visitor_counter, true, pool.spin).unwrap();
+            assert_eq!(pool.visitor_counter.0.load(Ordering::SeqCst), 2);
+        }
+
+        // a visitor running into the barrier shall back off without leaving a trace
+        pool.visitor_counter.1.store(true, Ordering::SeqCst);
+        assert!(VisitorGuard::register(&pool.visitor_counter, true, pool.spin).is_none());
+        assert_eq!(pool.visitor_counter.0.load(Ordering::SeqCst), 1);
+        pool.visitor_counter.1.store(false, Ordering::SeqCst);
+
+        pool.allow_expansion(true);
+        assert!(pool.expand(1, true));
+        assert_eq!(pool.visitor_counter.0.load(Ordering::SeqCst), 1);
+        assert!(!pool.visitor_counter.1.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn debug_print() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(16);
+        let _ary = pool.get();
+
+        assert_eq!(
+            format!("{:?}", pool),
+            "SyncPool { buckets: 2, available: 15, miss_count: 0, expansion_enabled: false }"
+        );
+    }
+
+    #[test]
+    fn size_rounding() {
+        let pool: SyncPool<[u8; 32]> = SyncPool::new();
+        assert_eq!(pool.capacity(), POOL_SIZE * SLOT_CAP);
+
+        for &(size, expected) in [
+            (0, SLOT_CAP),
+            (1, SLOT_CAP),
+            (SLOT_CAP, SLOT_CAP),
+            (SLOT_CAP + 4, 2 * SLOT_CAP),
+            (4 * SLOT_CAP - 1, 4 * SLOT_CAP),
+            (4 * SLOT_CAP, 4 * SLOT_CAP),
+            (7, 8),
+            (8, 8),
+            (9, 16),
+            (16, 16),
+            (17, 24),
+        ]
+        .iter()
+        {
+            let pool: SyncPool<[u8; 32]> = SyncPool::with_size(size);
+            assert!(pool.capacity() >= size);
+            assert_eq!(pool.capacity(), expected);
+            assert_eq!(pool.len(), expected);
+            assert_eq!(buckets_for(size) * SLOT_CAP, expected);
+        }
+
+        let pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(3);
+        assert_eq!(pool.capacity(), 3 * SLOT_CAP);
+    }
+
+    #[test]
+    fn put_async() {
+        use crate::PoolHandle;
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake};
+
+        struct Counter(AtomicUsize);
+
+        impl Wake for Counter {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let counter = Arc::new(Counter(AtomicUsize::new(0)));
+        let waker = Arc::clone(&counter).into();
+        let mut cx = Context::from_waker(&waker);
+        let handle: PoolHandle<[u8; 32]> = SyncPool::with_size(8).into_handle();
+        let raise = |raised: bool| {
+            handle.with(|pool| pool.visitor_counter.1.store(raised, Ordering::SeqCst));
+        };
+
+        // the barrier is raised, the future shall yield instead of blocking
+        let val = handle.get();
+        raise(true);
+        {
+            let mut fut = handle.put_async(val);
+            assert!(Pin::new(&mut fut).poll(&mut cx).is_pending());
+            assert!(fut.into_inner().is_some());
+        }
+
+        raise(false);
+        let val = handle.get();
+        let mut fut = handle.put_async(val);
+        assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(None));
+        drop(fut);
+        assert_eq!(handle.with(|pool| pool.len()), 7);
+
+        // the pending future is woken once the barrier is lowered, and not before
+        raise(true);
+        let mut fut = handle.put_async(Box::new([0u8; 32]));
+        let wakes = counter.0.load(Ordering::SeqCst);
+        assert!(Pin::new(&mut fut).poll(&mut cx).is_pending());
+        assert_eq!(counter.0.load(Ordering::SeqCst), wakes);
+
+        // no writer is running, the barrier has been raised by hand
+        assert!(handle.with(|pool| unsafe { pool.force_clear_barrier() }));
+        assert_eq!(counter.0.load(Ordering::SeqCst), wakes + 1);
+        assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(None));
+        drop(fut);
+        assert_eq!(handle.with(|pool| pool.len()), 8);
+
+        // a future dropped by the executor before completion returns the element if it can
+        let val = handle.get();
+        drop(handle.put_async(val));
+        assert_eq!(handle.with(|pool| pool.len()), 8);
+
+        // but the drop doesn't wait for the barrier, the element is dropped instead
+        let val = handle.get();
+        raise(true);
+        drop(handle.put_async(val));
+        raise(false);
+        assert_eq!(handle.with(|pool| pool.len()), 7);
+    }
+
+    #[test]
+    fn bucket_access() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(2);
+        assert_eq!(pool.bucket_count(), 2);
+
+        let mut held = Vec::new();
+        while let Some(val) = pool.get_bucket(1) {
+            held.push(val);
+        }
+
+        // only the addressed bucket is drained, and the cursors are not moved
+        assert_eq!(held.len(), SLOT_CAP);
+        assert_eq!(pool.len(), SLOT_CAP);
+        assert_eq!(pool.curr.0.load(Ordering::Relaxed), 0);
+
+        assert!(pool.get_bucket(2).is_none());
+        assert!(pool.put_bucket(2, held.pop().unwrap()).is_some());
+        assert!(pool.put_bucket(0, held.pop().unwrap()).is_some());
+
+        for val in held.drain(..) {
+            assert!(pool.put_bucket(1, val).is_none());
+        }
+
+        assert_eq!(pool.len(), 2 * SLOT_CAP - 2);
+        assert_eq!(pool.curr.1.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn set_reset() {
+        use std::sync::Arc;
+
+        let resets = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&resets);
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.set_reset(move |val| {
+            val[0] = 0;
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        for i in 1..=4 {
+            let mut val = pool.get();
+            val[0] = i;
+            assert!(pool.put(val).is_none());
+        }
+
+        assert_eq!(resets.load(Ordering::Relaxed), 4);
+        assert_eq!(pool.get()[0], 0);
+
+        // the pool owns the closure until it's replaced
+        pool.reset_handle(|val| val[0] = 0);
+        assert_eq!(Arc::strong_count(&resets), 1);
+    }
+
+    #[test]
+    fn replace_reset_under_barrier() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+
+        // someone else holds the barrier: the new handle is handed back, not dropped
+        pool.visitor_counter.1.store(true, Ordering::SeqCst);
+        let res = pool.replace_reset(ResetHandle::Func(|val| val[0] = 0));
+        assert!(matches!(res, Err(ResetHandle::Func(_))));
+        assert!(pool.reset_handle.is_none());
+
+        pool.visitor_counter.1.store(false, Ordering::SeqCst);
+        if let Err(handle) = res {
+            pool.install_reset(handle);
+        }
+
+        let mut val = pool.get();
+        val[0] = 42;
+        pool.put(val);
+        assert!((0..8).all(|_| pool.get()[0] == 0));
+    }
+
+    #[test]
+    fn peak_outstanding() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        assert_eq!(pool.peak_outstanding(), 0);
+
+        let held: Vec<_> = (0..6).map(|_| pool.get()).collect();
+        assert_eq!(pool.peak_outstanding(), 6);
+
+        for val in held {
+            pool.put(val);
+        }
+
+        pool.get_bucket(0);
+        assert_eq!(pool.peak_outstanding(), 6);
+
+        pool.reset_peak();
+        assert_eq!(pool.peak_outstanding(), 1);
+
+        // fallback allocations are part of the working set as well
+        let held: Vec<_> = (0..9).map(|_| pool.get()).collect();
+        assert_eq!(pool.peak_outstanding(), 10);
+        drop(held);
+    }
+
+    #[test]
+    fn transfer_into() {
+        let mut src: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(2);
+        let mut dst: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+
+        // make room in `dst` for some of the elements only
+        let held: Vec<_> = (0..6).map(|_| dst.get_bucket(0).unwrap()).collect();
+        let checked_out = src.get();
+
+        assert_eq!(src.transfer_into(&mut dst), 6);
+        assert_eq!(src.len(), 0);
+        assert_eq!(dst.len(), 8);
+
+        // the moved elements are not checkouts of the old pool
+        assert_eq!((src.outstanding_count(), src.peak_outstanding()), (1, 1));
+        assert_eq!(dst.outstanding_count(), 0);
+
+        // the element checked out from the old pool can still be returned
+        assert!(src.put(checked_out).is_none());
+        assert_eq!(src.outstanding_count(), 0);
+        drop(held);
+    }
+
+    #[test]
+    fn fallback_hook() {
+        static LABELED: AtomicUsize = AtomicUsize::new(0);
+        static UNLABELED: AtomicUsize = AtomicUsize::new(0);
+
+        fn hook(label: Option<&'static str>) {
+            match label {
+                Some(label) => {
+                    assert_eq!(label, "starving_site");
+                    LABELED.fetch_add(1, Ordering::Relaxed);
+                }
+                None => {
+                    UNLABELED.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.fallback_hook(hook);
+
+        let held: Vec<_> = (0..SLOT_CAP)
+            .map(|_| pool.get_labeled("starving_site"))
+            .collect();
+        assert_eq!(LABELED.load(Ordering::Relaxed), 0);
+
+        pool.get_labeled("starving_site");
+        pool.get();
+        assert_eq!(LABELED.load(Ordering::Relaxed), 1);
+        assert_eq!(UNLABELED.load(Ordering::Relaxed), 1);
+        drop(held);
+    }
+
+    #[test]
+    fn alloc_hook() {
+        static FALLBACKS: AtomicUsize = AtomicUsize::new(0);
+        static EXPANDED: AtomicUsize = AtomicUsize::new(0);
+        static REFILLS: AtomicUsize = AtomicUsize::new(0);
+
+        fn hook(tag: &'static str, event: AllocEvent) {
+            assert_eq!(tag, "buffers");
+
+            match event {
+                AllocEvent::Fallback(label) => {
+                    assert_eq!(label, Some("starving_site"));
+                    FALLBACKS.fetch_add(1, Ordering::Relaxed);
+                }
+                AllocEvent::Expand(count) => {
+                    EXPANDED.fetch_add(count, Ordering::Relaxed);
+                }
+                AllocEvent::Refill => {
+                    REFILLS.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.alloc_hook("buffers", hook).allow_expansion(true);
+
+        let held: Vec<_> = (0..=SLOT_CAP)
+            .map(|_| pool.get_labeled("starving_site"))
+            .collect();
+        assert_eq!(FALLBACKS.load(Ordering::Relaxed), 1);
+
+        assert!(pool.expand(1, true));
+        assert_eq!(EXPANDED.load(Ordering::Relaxed), SLOT_CAP);
+
+        drop(held);
+        assert_eq!(pool.refill(SLOT_CAP), SLOT_CAP);
+        assert_eq!(REFILLS.load(Ordering::Relaxed), SLOT_CAP);
+    }
+
+    #[test]
+    fn pressure() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        assert_eq!(pool.pressure(), 0.0);
+
+        let mut held: Vec<_> = (0..6).map(|_| pool.get()).collect();
+        assert_eq!(pool.pressure(), 0.75);
+
+        held.extend((0..4).map(|_| pool.get()));
+        assert_eq!(pool.pressure(), 1.0);
+    }
+
+    #[test]
+    fn occupancy_breakdown() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(3);
+        assert_eq!(pool.occupancy_breakdown(), (0, 0, 3));
+
+        let held: Vec<_> = (0..SLOT_CAP).map(|_| pool.get_bucket(0).unwrap()).collect();
+        pool.get_bucket(1).unwrap();
+        assert_eq!(pool.occupancy_breakdown(), (1, 1, 1));
+
+        drop(held);
+    }
+
+    #[test]
+    fn thread_affine() {
+        let mut pool: SyncPool<Vec<u8>> = SyncPool::with_size(8);
+        pool.set_thread_affine(true).allow_expansion(true);
+
+        let val = pool.get();
+        assert_eq!(pool.len(), 7);
+
+        let (val, mut pool) = thread::spawn(move || {
+            // on a foreign thread, the pool is not touched and the returns are refused
+            let fresh = pool.get();
+            assert_eq!(pool.len(), 7);
+            assert_eq!(pool.miss_count(), 1);
+            assert!(pool.put(fresh).is_some());
+
+            let val = pool.put(val).unwrap();
+            assert!(!pool.expand(1, true));
+            assert_eq!(pool.refill(1), 0);
+
+            (val, pool)
+        })
+        .join()
+        .unwrap();
+
+        assert!(pool.put(val).is_none());
+        assert_eq!(pool.len(), 8);
+
+        pool.set_thread_affine(false);
+        thread::spawn(move || assert!({ pool }.get_bucket(0).is_some()))
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn get_or_else() {
+        let mut pool: SyncPool<Vec<u8>> = SyncPool::with_size(8);
+        let held: Vec<_> = (0..8).map(|_| pool.get_or_else(|| vec![1])).collect();
+        assert!(held.iter().all(|val| val.is_empty()));
+
+        let made = pool.get_or_else(|| vec![42]);
+        assert_eq!(*made, vec![42]);
+        assert_eq!(pool.miss_count(), 1);
+
+        assert!(pool.put(made).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "max-lifetime")]
+    fn max_lifetime() {
+        static BUILT: AtomicUsize = AtomicUsize::new(0);
+        static EVICTED: AtomicUsize = AtomicUsize::new(0);
+
+        let mut pool = SyncPool::with_builder_and_size(8, || BUILT.fetch_add(1, Ordering::SeqCst));
+        pool.set_max_lifetime(Duration::from_millis(20));
+        pool.set_on_evict(|_| {
+            EVICTED.fetch_add(1, Ordering::SeqCst);
+        });
+
+        // the elements keep their creation times across the returns
+        let held: Vec<_> = (0..8).map(|_| pool.get()).collect();
+        for val in held {
+            pool.put(val);
+        }
+
+        thread::sleep(Duration::from_millis(30));
+
+        // but they're rotated once they have outlived their lifetime
+        let val = pool.get();
+        assert_eq!(*val, 8);
+        assert_eq!(BUILT.load(Ordering::SeqCst), 9);
+
+        pool.put(val);
+        assert_eq!(pool.len(), 8);
+        assert_eq!(EVICTED.load(Ordering::SeqCst), 1);
+
+        // the checkouts that can't allocate come back empty instead
+        thread::sleep(Duration::from_millis(30));
+        assert!(pool.get_bucket(0).is_none());
+        assert_eq!(BUILT.load(Ordering::SeqCst), 9);
+        assert_eq!(EVICTED.load(Ordering::SeqCst), 2);
+        assert_eq!((pool.len(), pool.outstanding_count()), (7, 0));
+
+        // the times move along with the elements between the slots
+        let mut pool: SyncPool<usize> = SyncPool::with_size(16);
+        pool.set_max_lifetime(Duration::from_millis(20));
+
+        let held: Vec<_> = (0..8).filter_map(|_| pool.get_bucket(1)).collect();
+        assert_eq!(held.len(), 8);
+
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(pool.rebalance(), 4);
+        assert!(pool.get_bucket(1).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "empty-events")]
+    fn empty_events() {
+        static EMPTY: AtomicUsize = AtomicUsize::new(0);
+        static NON_EMPTY: AtomicUsize = AtomicUsize::new(0);
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.on_empty(|| {
+            EMPTY.fetch_add(1, Ordering::SeqCst);
+        });
+        pool.on_non_empty(|| {
+            NON_EMPTY.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let mut held: Vec<_> = (0..7).map(|_| pool.get()).collect();
+        assert_eq!(EMPTY.load(Ordering::SeqCst), 0);
+
+        // only the edges fire, not the checkouts from (or the returns to) the pool in the same state
+        held.push(pool.get());
+        held.push(pool.get());
+        assert_eq!(EMPTY.load(Ordering::SeqCst), 1);
+
+        pool.put(held.pop().unwrap());
+        pool.put(held.pop().unwrap());
+        assert_eq!(NON_EMPTY.load(Ordering::SeqCst), 1);
+
+        held.push(pool.get());
+        held.push(pool.get_wait_free());
+        assert_eq!(EMPTY.load(Ordering::SeqCst), 2);
+
+        held.into_iter().for_each(|val| {
+            pool.put(val);
+        });
+        assert_eq!(NON_EMPTY.load(Ordering::SeqCst), 2);
+
+        // the elements dropped under the exclusive access are recounted, the checkout finds the
+        // pool empty
+        pool.with_exclusive(|slots| {
+            slots.iter_mut().for_each(|bucket| {
+                bucket.retain(|_| false);
+            })
+        });
+        pool.get();
+        assert_eq!(EMPTY.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "reset-timing")]
+    fn reset_timing() {
+        static SLOW: AtomicUsize = AtomicUsize::new(0);
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.reset_handle(|val| {
+            if val[0] == 1 {
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+        pool.set_slow_reset_hook(Duration::from_millis(1), |_| {
+            SLOW.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let fast = pool.get();
+        let mut slow = pool.get();
+        slow[0] = 1;
+
+        pool.put(fast);
+        pool.put(slow);
+
+        let histogram = pool.reset_duration_histogram();
+        assert_eq!(histogram.iter().sum::<usize>(), 2);
+
+        // 5 ms is at least 2^12 us
+        assert_eq!(histogram[13..].iter().sum::<usize>(), 1);
+        assert_eq!(SLOW.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "fault-window")]
+    fn fault_rate_window() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        assert_eq!(pool.fault_rate_window(), 0.0);
+
+        // 8 gets served by the pool, then 8 faults
+        let held: Vec<_> = (0..2 * SLOT_CAP).map(|_| pool.get()).collect();
+        assert_eq!(pool.fault_rate_window(), 0.5);
+
+        pool.tick();
+        for val in held {
+            pool.put(val);
+        }
+
+        let val = pool.get();
+        pool.put(val);
+        assert_eq!(pool.fault_rate_window(), 8.0 / 17.0);
+
+        // the faulting tick falls out of the window
+        for _ in 0..FAULT_WINDOW - 1 {
+            pool.tick();
+        }
+
+        assert_eq!(pool.fault_rate_window(), 0.0);
+    }
+
+    #[test]
+    fn auto_shrink() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(4);
+        assert_eq!(pool.maybe_shrink(), 0);
+
+        pool.set_auto_shrink(Duration::from_millis(20), Duration::from_millis(0));
+        let held = pool.get_bucket(1).unwrap();
+
+        // the first tick only starts the idle tracking
+        assert_eq!(pool.maybe_shrink(), 0);
+        thread::sleep(Duration::from_millis(30));
+
+        // only the bucket in use is kept
+        assert_eq!(pool.maybe_shrink(), 3);
+        assert_eq!(pool.bucket_count(), 1);
+        assert_eq!(pool.capacity(), SLOT_CAP);
+
+        // and the last bucket always stays
+        assert!(pool.put(held).is_none());
+        assert_eq!(pool.maybe_shrink(), 0);
+        thread::sleep(Duration::from_millis(30));
+
+        assert_eq!(pool.maybe_shrink(), 0);
+        assert_eq!(pool.len(), SLOT_CAP);
+    }
+
+    #[test]
+    fn min_buckets() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(4);
+        pool.set_auto_shrink(Duration::from_millis(20), Duration::from_millis(0))
+            .set_min_buckets(3);
+
+        assert_eq!(pool.maybe_shrink(), 0);
+        thread::sleep(Duration::from_millis(30));
+
+        assert_eq!(pool.maybe_shrink(), 1);
+        assert_eq!(pool.bucket_count(), 3);
+
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(pool.maybe_shrink(), 0);
+
+        pool.set_min_buckets(0);
+        assert_eq!(pool.maybe_shrink(), 2);
+        assert_eq!(pool.bucket_count(), 1);
+    }
+
+    #[test]
+    fn expand_while_getting() {
+        use std::sync::atomic::AtomicBool;
+
+        struct Shared(*mut SyncPool<[u8; 32]>);
+        unsafe impl Send for Shared {}
+        unsafe impl Sync for Shared {}
+
+        const EXPANSIONS: usize = 32;
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.allow_expansion(true);
+
+        // share the pool the same way the examples do, i.e. behind a raw pointer
+        let shared = Shared(&mut pool);
+        let done = AtomicBool::new(false);
+
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    let pool = unsafe { &mut *shared.0 };
+
+                    while !done.load(Ordering::Acquire) {
+                        let mut val = pool.get();
+                        val[0] = 42;
+                        pool.put(val);
+                    }
+                });
+            }
+
+            // every expansion reallocates the buckets while the getters hammer the pool
+            let pool = unsafe { &mut *shared.0 };
+            for _ in 0..EXPANSIONS {
+                assert!(pool.expand(1, true));
+                thread::yield_now();
+            }
+
+            done.store(true, Ordering::Release);
+        });
+
+        assert_eq!(pool.capacity(), (EXPANSIONS + 1) * SLOT_CAP);
+        assert!(pool.len() <= pool.capacity());
+    }
+
+    #[test]
+    fn with_max_capacity() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_max_capacity();
+        assert_eq!(pool.capacity(), MAX_BUCKETS * SLOT_CAP);
+        assert_eq!(pool.len(), pool.capacity());
+
+        pool.allow_expansion(true);
+        assert_eq!(pool.try_expand(1), ExpandResult::AtCap);
+    }
+
+    #[test]
+    fn from_storage() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.allow_expansion(true).expand(15, true);
+        let held = pool.get();
+
+        let storage = pool.into_storage();
+        assert!(storage.bucket_capacity() >= 16);
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::from_storage(storage, 32);
+        assert_eq!(pool.capacity(), 32);
+        assert_eq!(pool.len(), 32);
+        assert!(pool.slots.capacity() >= 16);
+
+        pool.get();
+        assert!(pool.put(held).is_none());
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        let held: Vec<_> = (0..10).map(|_| pool.get()).collect();
+
+        let snapshot = pool.snapshot();
+        assert_eq!(snapshot.capacity, 8);
+        assert_eq!(snapshot.len, 0);
+        assert_eq!(snapshot.outstanding_count, pool.outstanding_count());
+        assert_eq!(snapshot.peak_outstanding, 10);
+        assert_eq!(snapshot.fallback_count, 2);
+
+        drop(held);
+    }
+
+    #[test]
+    fn full_policy() {
+        let mut pool: SyncPool<usize> = SyncPool::with_size(8);
+        assert!(pool.put(Box::new(1)).is_some());
+
+        pool.set_full_policy(FullPolicy::DropIncoming);
+        assert!(pool.put(Box::new(1)).is_none());
+        assert_eq!(pool.eviction_count(), 0);
+
+        // the initial elements go first, then the ones placed by the earliest `put`
+        pool.set_full_policy(FullPolicy::EvictOldest);
+        for i in 1..=10 {
+            assert!(pool.put(Box::new(i)).is_none());
+        }
+
+        assert_eq!(pool.eviction_count(), 10);
+        assert_eq!(pool.len(), 8);
+
+        let mut kept: Vec<_> = (0..8).map(|_| *pool.get()).collect();
+        kept.sort_unstable();
+        assert_eq!(kept, (3..=10).collect::<Vec<_>>());
+
+        // the buckets are only stamped under the policy, including the ones added meanwhile
+        (0..8).for_each(|i| assert!(pool.put(Box::new(i)).is_none()));
+        pool.allow_expansion(true);
+        assert!(pool.expand(1, true));
+        assert!(pool.slots.iter().all(|bucket| bucket.is_stamped()));
+
+        pool.set_full_policy(FullPolicy::Reject);
+        assert!(pool.slots.iter().all(|bucket| !bucket.is_stamped()));
+        assert!(pool.put(Box::new(11)).is_some());
+
+        // a refill looks for the vacancy instead of evicting the idle elements on the way
+        let mut pool: SyncPool<usize> = SyncPool::with_size(16);
+        pool.set_full_policy(FullPolicy::EvictOldest);
+        pool.set_max_probe(1);
+        pool.set_cursor(0);
+        assert!(pool.get_bucket(1).is_some());
+
+        assert_eq!(pool.refill(1), 1);
+        assert_eq!(pool.eviction_count(), 0);
+        assert_eq!((pool.len(), pool.outstanding_count()), (16, 0));
+    }
+
+    #[test]
+    fn peek_each() {
+        let mut pool: SyncPool<usize> = SyncPool::with_size(16);
+        pool.reset_handle(|val| *val = 7);
+
+        let held: Vec<_> = (0..4).map(|_| pool.get()).collect();
+        held.into_iter().for_each(|val| {
+            pool.put(val);
+        });
+
+        let mut seen = Vec::new();
+        pool.peek_each(|val| seen.push(*val));
+
+        assert_eq!(seen.len(), 16);
+        assert_eq!(seen.iter().filter(|&&val| val == 7).count(), 4);
+        assert_eq!(pool.len(), 16);
+    }
+
+    #[test]
+    fn checkout_order() {
+        let mut pool: SyncPool<usize> = SyncPool::with_size(64);
+        assert_eq!(pool.checkout_order(), CheckoutOrder::RoundRobin);
+
+        pool.set_order(CheckoutOrder::Lifo);
+        assert_eq!(pool.checkout_order(), CheckoutOrder::Lifo);
+
+        let mut held: Vec<_> = (0..32).map(|_| pool.get()).collect();
+        for (i, val) in held.iter_mut().enumerate() {
+            **val = i + 1;
+        }
+
+        for val in held {
+            pool.put(val);
+        }
+
+        // the last returned element comes back first, then the ones before it
+        assert_eq!(*pool.get(), 32);
+        assert_eq!(*pool.get(), 31);
+
+        pool.set_order(CheckoutOrder::RoundRobin);
+        assert_eq!(pool.checkout_order(), CheckoutOrder::RoundRobin);
+    }
+
+    #[test]
+    fn has_reset_handle() {
+        let mut pool: SyncPool<usize> = SyncPool::with_size(8);
+        assert!(!pool.has_reset_handle());
+
+        pool.reset_handle(|val| *val = 0);
+        assert!(pool.has_reset_handle());
+
+        let mut pool: SyncPool<usize> = SyncPool::with_size(8);
+        pool.set_reset(|val| *val = 0);
+        assert!(pool.has_reset_handle());
+    }
+
+    #[test]
+    fn put_hint() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(4);
+
+        // check out from bucket 2, and leave vacancies in the buckets the put cursor visits first
+        pool.set_cursor(2);
+        let val = pool.get_wait_free();
+        drop(pool.get_bucket(0));
+        drop(pool.get_bucket(1));
+        pool.set_cursor(0);
+
+        // the element goes back to the bucket it was checked out from, not the cursor's one
+        match pool.put_tracked(val) {
+            PutOutcome::Stored { bucket } => assert_eq!(bucket, 2),
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        }
+        assert_eq!(pool.slots[2].len(), SLOT_CAP);
+        assert_eq!(pool.slots[0].len(), SLOT_CAP - 1);
+        assert_eq!(pool.slots[1].len(), SLOT_CAP - 1);
+    }
+
+    #[test]
+    fn put_tracked() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(2);
+        let val = pool.get_bucket(1).unwrap();
+
+        match pool.put_tracked(val) {
+            PutOutcome::Stored { bucket } => assert_eq!(bucket, 1),
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        }
+
+        let outcome = pool.put_tracked(Box::new([0u8; 32]));
+        assert_eq!(format!("{:?}", outcome), "Rejected(..)");
+
+        pool.set_full_policy(FullPolicy::DropIncoming);
+        assert!(matches!(
+            pool.put_tracked(Box::new([0u8; 32])),
+            PutOutcome::Dropped
+        ));
+    }
+
+    #[test]
+    fn expand_to() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.allow_expansion(true);
+
+        assert!(!pool.expand_to(8, true));
+        assert!(pool.expand_to(16, true));
+        assert_eq!(pool.capacity(), 16);
+
+        assert!(!pool.expand_to(10, true));
+        assert!(pool.expand_to(17, true));
+        assert_eq!(pool.capacity(), 24);
+    }
+
+    #[test]
+    fn on_evict() {
+        let evicted = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let observed = Arc::clone(&evicted);
+
+        let mut pool: SyncPool<usize> = SyncPool::with_exact_buckets(1);
+        pool.set_on_evict(move |val| observed.lock().unwrap().push(*val));
+
+        // handed back to the caller, not dropped by the pool
+        assert!(pool.put(Box::new(1)).is_some());
+
+        pool.set_full_policy(FullPolicy::DropIncoming);
+        assert!(pool.put(Box::new(2)).is_none());
+
+        // the oldest idle element is one of the default ones
+        pool.set_full_policy(FullPolicy::EvictOldest);
+        assert!(pool.put(Box::new(3)).is_none());
+
+        pool.set_max_idle(pool.len());
+        assert!(pool.put(Box::new(4)).is_none());
+
+        assert_eq!(*evicted.lock().unwrap(), vec![2, 0, 4]);
+
+        // the elements dropped under the exclusive access
+        let dropped = pool.with_exclusive(|buckets| buckets[0].retain(|val| *val != 3));
+        assert_eq!(dropped, 1);
+
+        // the ones the destination has no room for, which is 1 here
+        let mut dst: SyncPool<usize> = SyncPool::with_exact_buckets(1);
+        let _held = dst.get();
+        assert_eq!(pool.transfer_into(&mut dst), 1);
+
+        // and the idle ones dropped along with the buckets
+        assert!(pool.put(Box::new(5)).is_none());
+        pool.into_storage();
+
+        let mut expected = vec![2, 0, 4, 3];
+        expected.extend([0; SLOT_CAP - 2].iter());
+        expected.push(5);
+        assert_eq!(*evicted.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn memory_budget() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.allow_expansion(true);
+
+        let bucket_bytes = mem::size_of::<Bucket2<[u8; 32]>>() + SLOT_CAP * 32;
+        pool.set_memory_budget(pool.approx_bytes() + bucket_bytes);
+
+        assert_eq!(pool.try_expand(2), ExpandResult::OverBudget);
+        assert_eq!(pool.bucket_count(), 1);
+
+        assert_eq!(pool.try_expand(1), ExpandResult::Grew(SLOT_CAP));
+        assert_eq!(pool.try_expand(1), ExpandResult::OverBudget);
+
+        pool.set_memory_budget(usize::MAX);
+        assert!(pool.expand(1, true));
+    }
+
+    #[test]
+    #[cfg(feature = "test-hooks")]
+    fn injected_access_failures() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(2);
+        let held: Vec<_> = (0..2).map(|_| pool.get()).collect();
+        assert_eq!(pool.miss_count(), 0);
+
+        // both buckets are "contended": get falls back, and put hands the element back
+        pool.inject_access_failures(0, usize::MAX);
+        pool.inject_access_failures(1, usize::MAX);
+        pool.get();
+        assert_eq!(pool.miss_count(), 1);
+        assert!(pool.put(Box::new([0u8; 32])).is_some());
+
+        // a single denied access moves on to the next bucket
+        pool.inject_access_failures(0, 1);
+        pool.inject_access_failures(1, 0);
+        for val in held {
+            assert!(pool.put(val).is_none());
+        }
+
+        pool.inject_access_failures(0, 0);
+        pool.get();
+        assert_eq!(pool.miss_count(), 1);
+    }
+
+    #[test]
+    fn rebalance() {
+        let mut pool: SyncPool<usize> = SyncPool::with_exact_buckets(4);
+        let lens = |pool: &mut SyncPool<usize>| {
+            pool.with_exclusive(|slots| slots.iter().map(BucketView::len).collect::<Vec<_>>())
+        };
+
+        // skew the pool: only the first bucket keeps its elements
+        let mut held = Vec::new();
+        for bucket in pool.slots[1..].iter_mut() {
+            held.extend((0..SLOT_CAP).filter_map(|pos| bucket.take(pos)));
+        }
+        assert_eq!(lens(&mut pool), vec![SLOT_CAP, 0, 0, 0]);
+
+        assert_eq!(pool.rebalance(), 3 * SLOT_CAP / 4);
+        assert_eq!(lens(&mut pool), vec![SLOT_CAP / 4; 4]);
+
+        // an uneven total leaves the extra elements in the first buckets
+        for _ in 0..3 {
+            let (val, _, _) = held.pop().unwrap();
+            pool.slots[0].place(val, 0, None, false).unwrap();
+        }
+
+        pool.rebalance();
+        assert_eq!(lens(&mut pool), vec![3, 3, 3, 2]);
+        assert_eq!(pool.rebalance(), 0);
+    }
+
+    #[test]
+    fn with_config_of() {
+        fn reset(val: &mut [u8; 32]) {
+            val[0] = 7;
+        }
+
+        let mut template: SyncPool<[u8; 32]> = SyncPool::with_builder(|| [1u8; 32]);
+        template
+            .reset_handle(reset)
+            .allow_expansion(true)
+            .set_full_policy(FullPolicy::DropIncoming);
+
+        let mut pool = SyncPool::with_config_of(&template, 16);
+        assert_eq!(pool.capacity(), 16);
+        assert_ne!(pool.id(), template.id());
+        assert!(pool.expansion_enabled());
+        assert!(pool.has_reset_handle());
+
+        let mut val = pool.get();
+        assert_eq!(val[0], 1);
+
+        pool.put(val);
+        val = pool.get();
+        assert_eq!(val[0], 7);
+
+        assert!(pool.put(val).is_none());
+        assert!(pool.put(Box::new([0u8; 32])).is_none());
+        assert_eq!(template.len(), POOL_SIZE * SLOT_CAP);
+    }
+
+    #[test]
+    fn slot_state() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(2);
+        assert_eq!(pool.slot_state(1, 0), Some(SlotState::Occupied));
+        assert_eq!(pool.slot_state(2, 0), None);
+        assert_eq!(pool.slot_state(0, SLOT_CAP), None);
+
+        let val = pool.get_bucket(1).unwrap();
+        let states: Vec<_> = (0..SLOT_CAP)
+            .filter_map(|pos| pool.slot_state(1, pos))
+            .collect();
+        assert_eq!(states.iter().filter(|&&s| s == SlotState::Empty).count(), 1);
+
+        let pos = states.iter().position(|&s| s == SlotState::Empty).unwrap();
+        let lock = pool.slots[1].access(false).unwrap();
+        assert_eq!(lock, pos);
+        assert_eq!(pool.slot_state(1, pos), Some(SlotState::Locked));
+
+        pool.slots[1].release(lock, val, None);
+        pool.slots[1].leave(lock as u16);
+        assert_eq!(pool.slot_state(1, pos), Some(SlotState::Occupied));
+    }
+
+    #[test]
+    fn capped_clear_reset() {
+        let mut pool: SyncPool<Vec<u8>> = SyncPool::with_size(8);
+        pool.set_capped_clear_reset(64);
+
+        let mut small = pool.get();
+        small.reserve_exact(32);
+        let kept = small.capacity();
+
+        let mut large = pool.get();
+        large.resize(4096, 1);
+
+        pool.put(small);
+        pool.put(large);
+
+        let vals: Vec<_> = (0..8).map(|_| pool.get()).collect();
+        assert!(vals
+            .iter()
+            .all(|val| val.is_empty() && val.capacity() <= 64));
+        assert!(vals.iter().any(|val| val.capacity() == kept));
+
+        let mut strings: SyncPool<String> = SyncPool::with_size(8);
+        strings.set_capped_clear_reset(8);
+
+        let mut val = strings.get();
+        val.push_str("longer than the cap");
+        strings.put(val);
+
+        let vals: Vec<_> = (0..8).map(|_| strings.get()).collect();
+        assert!(vals.iter().all(|val| val.is_empty() && val.capacity() <= 8));
+    }
+
+    #[test]
+    fn can_satisfy() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        assert!(pool.can_satisfy(0));
+        assert!(pool.can_satisfy(8));
+        assert!(!pool.can_satisfy(9));
+
+        let held: Vec<_> = (0..3).map(|_| pool.get()).collect();
+        assert!(pool.can_satisfy(5));
+        assert!(!pool.can_satisfy(6));
+
+        for val in held {
+            pool.put(val);
+        }
+        assert!(pool.can_satisfy(8));
+    }
+
+    #[test]
+    fn put_to() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(4);
+        let first = pool.get_bucket(2).unwrap();
+        let second = pool.get_bucket(3).unwrap();
+
+        // a plain `put` would land wherever the cursor points at
+        assert!(pool.put_to(first, 2).is_none());
+        assert_eq!(pool.slots[2].len(), SLOT_CAP);
+        assert_eq!(pool.slots[3].len(), SLOT_CAP - 1);
+
+        // an out-of-bounds hint falls back to the normal scan
+        assert!(pool.put_to(second, 9).is_none());
+        assert_eq!(pool.len(), 4 * SLOT_CAP);
+        assert!(pool.put_to(Box::new([0u8; 32]), 0).is_some());
+    }
+
+    #[test]
+    fn get_wait_free() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(4);
+        let held: Vec<_> = (0..4 * SLOT_CAP).map(|_| pool.get_wait_free()).collect();
+        assert!(pool.is_empty());
+        assert_eq!(pool.miss_count(), 0);
+
+        // an empty pool costs one probe per bucket, and then a fallback
+        let cursor = pool.curr.0.load(Ordering::SeqCst);
+        pool.get_wait_free();
+        assert_eq!(pool.miss_count(), 1);
+        assert_eq!(pool.curr.0.load(Ordering::SeqCst), cursor);
+
+        for val in held {
+            pool.put(val);
+        }
+
+        pool.set_max_probe(1);
+        pool.get_wait_free();
+        assert_eq!(pool.miss_count(), 1);
+    }
+
+    #[test]
+    fn bucket_conversions() {
+        assert_eq!(objects_to_buckets(0), 0);
+        assert_eq!(objects_to_buckets(1), 1);
+        assert_eq!(objects_to_buckets(SLOT_CAP), 1);
+        assert_eq!(objects_to_buckets(SLOT_CAP + 1), 2);
+        assert_eq!(objects_to_buckets(usize::MAX), usize::MAX / SLOT_CAP + 1);
+
+        assert_eq!(buckets_to_objects(0), 0);
+        assert_eq!(buckets_to_objects(3), 3 * SLOT_CAP);
+        assert_eq!(buckets_to_objects(usize::MAX), usize::MAX);
+
+        for objects in 0..4 * SLOT_CAP {
+            assert!(buckets_to_objects(objects_to_buckets(objects)) >= objects);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "debug-leaks")]
+    fn outstanding_checkouts() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+
+        let first = pool.get_labeled("first");
+        let held: Vec<_> = (0..8).map(|_| pool.get()).collect();
+        assert_eq!(pool.outstanding_checkouts().len(), 9);
+
+        for val in held {
+            pool.put(val);
+        }
+
+        // the one left out is the leak
+        let leaks = pool.outstanding_checkouts();
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].label, Some("first"));
+
+        // handed back by the full pool, hence still out
+        let first = pool.put(first).unwrap();
+        assert_eq!(pool.outstanding_checkouts().len(), 1);
+
+        // make room for it by leaking another one
+        drop(pool.get());
+        pool.put(first);
+        assert_eq!(pool.outstanding_checkouts().len(), 1);
+    }
+
+    #[test]
+    fn object_pool() {
+        fn churn(pool: &mut dyn ObjectPool<[u8; 32]>) -> usize {
+            let held: Vec<_> = (0..10).map(|_| pool.acquire()).collect();
+            held.into_iter().filter_map(|val| pool.release(val)).count()
+        }
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        assert_eq!(churn(&mut pool), 2);
+        assert_eq!(pool.len(), 8);
+
+        // same for the other backends
+        let mut array: crate::ArrayPool<[u8; 32], 1> = crate::ArrayPool::new();
+        assert_eq!(churn(&mut array), 2);
+
+        let mut handle = SyncPool::with_size(8).into_handle();
+        assert_eq!(churn(&mut handle), 2);
+    }
+
+    #[test]
+    fn builder_panic() {
+        static FAULTY: AtomicBool = AtomicBool::new(false);
+
+        fn faulty() -> Vec<u8> {
+            if FAULTY.load(Ordering::Relaxed) {
+                panic!("out of buffers");
+            }
+
+            Vec::new()
+        }
+
+        let mut pool = SyncPool::with_builder(faulty);
+        FAULTY.store(true, Ordering::Relaxed);
+        let held: Vec<_> = (0..pool.capacity()).map(|_| pool.get()).collect();
 
-            self
+        let err = pool.get_checked().unwrap_err();
+        assert_eq!(err.pool, pool.id());
+        assert_eq!(err.message.as_deref(), Some("out of buffers"));
+        assert_eq!(
+            (pool.builder_panic_count(), pool.total_checkouts()),
+            (1, held.len())
+        );
+
+        // re-raised with the pool's identity
+        pool.set_catch_builder_panic(true);
+        let res = panic::catch_unwind(AssertUnwindSafe(|| pool.get()));
+        let payload = res.unwrap_err();
+        let message = payload.downcast_ref::<String>().unwrap();
+        assert!(message.contains(&format!("{:?}", pool.id())));
+        assert!(message.ends_with(": out of buffers"));
+        assert_eq!(pool.builder_panic_count(), 2);
+
+        // the elements in store are handed out as usual
+        held.into_iter().for_each(|val| {
+            pool.put(val);
+        });
+        assert!(pool.get_checked().is_ok());
+    }
+
+    #[test]
+    fn total_checkouts_and_returns() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        let held: Vec<_> = (0..10).map(|_| pool.get()).collect();
+        assert_eq!((pool.total_checkouts(), pool.total_returns()), (10, 0));
+
+        // the 2 handed back by the full pool are not returns
+        for val in held {
+            pool.put(val);
         }
+        assert_eq!((pool.total_checkouts(), pool.total_returns()), (10, 8));
+
+        // nor are the elements added by refill
+        pool.get();
+        assert_eq!(pool.refill(1), 1);
+        let snapshot = pool.snapshot();
+        assert_eq!((snapshot.checkout_count, snapshot.return_count), (11, 8));
     }
 
     #[test]
-    fn use_packer() {
-        let mut pool = SyncPool::with_packer(BigStruct::initializer);
+    fn set_cursor() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(4);
+        assert_eq!(pool.cursor(), 0);
 
-        let big_box = pool.get();
+        pool.set_cursor(6);
+        assert_eq!(pool.cursor(), 2);
 
-        assert_eq!(big_box.a, 1);
-        assert_eq!(big_box.b, 42);
-        assert_eq!(big_box.c.len(), 0x1_000_000);
+        // the scan starts from the cursor
+        let val = pool.get_wait_free();
+        assert_eq!(pool.slots[2].len(), SLOT_CAP - 1);
+        assert!(matches!(
+            pool.put_tracked(val),
+            PutOutcome::Stored { bucket: 2 }
+        ));
     }
 
     #[test]
-    fn use_builder() {
-        let mut pool = SyncPool::with_builder(BigStruct::new);
+    fn prefill_on_first_fault() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size_lazy(16);
+        assert_eq!((pool.len(), pool.capacity()), (0, 16));
 
-        let big_box = pool.get();
+        // the first fault fills up the pool, and is served from it
+        let val = pool.get();
+        assert_eq!(pool.len(), 15);
+        assert_eq!(pool.miss_count(), 0);
+        pool.put(val);
 
-        assert_eq!(big_box.a, 1);
-        assert_eq!(big_box.b, 42);
-        assert_eq!(big_box.c.len(), 0x1_000_000);
+        // but only the first one does
+        let held: Vec<_> = (0..17).map(|_| pool.get()).collect();
+        assert_eq!(pool.miss_count(), 1);
+        assert!(pool.is_empty());
+
+        for val in held {
+            pool.put(val);
+        }
+        assert_eq!(pool.len(), 16);
+    }
+
+    #[test]
+    fn get_with_policy() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        let held: Vec<_> = (0..8).map(|_| pool.get()).collect();
+
+        assert!(pool.get_with_policy(FallbackPolicy::Fail).is_none());
+        // nothing can be returned in the meantime, so this doesn't wait
+        assert!(pool
+            .get_with_policy(FallbackPolicy::Wait(Duration::from_secs(10)))
+            .is_none());
+        assert_eq!((pool.miss_count(), pool.snapshot().fallback_count), (2, 0));
+
+        assert!(pool.get_with_policy(FallbackPolicy::Allocate).is_some());
+        assert_eq!((pool.miss_count(), pool.snapshot().fallback_count), (3, 1));
+
+        for val in held {
+            pool.put(val);
+        }
+        assert!(pool.get_with_policy(FallbackPolicy::Fail).is_some());
+    }
+
+    #[test]
+    fn poison() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        let val = pool.get();
+
+        pool.poison();
+        assert!(pool.is_poisoned());
+        assert!(pool.is_empty());
+        assert!(pool.try_get().is_none());
+        assert!(pool.get_bucket(0).is_none());
+
+        // nothing is taken back while poisoned
+        assert!(matches!(pool.put_tracked(val), PutOutcome::Dropped));
+        assert!(pool.is_empty());
+
+        pool.unpoison();
+        assert!(!pool.is_poisoned());
+        assert_eq!(pool.refill(8), 8);
+        assert!(pool.try_get().is_some());
+    }
+
+    #[test]
+    fn reset_on_checkout() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.reset_handle(|ary| ary[0] = 0);
+        assert_eq!(pool.reset_timing(), ResetTiming::OnReturn);
+
+        pool.set_reset_timing(ResetTiming::OnCheckout);
+        assert_eq!(pool.reset_timing(), ResetTiming::OnCheckout);
+
+        let mut held: Vec<_> = (0..8).map(|_| pool.get()).collect();
+        held.iter_mut().for_each(|ary| ary[0] = 1);
+
+        // stored dirty ...
+        for val in held {
+            pool.put(val);
+        }
+        assert!(pool.slots[0].iter().all(|ary| ary[0] == 1));
+
+        // ... but reset before being handed out
+        let mut held: Vec<_> = (0..4).map(|_| pool.get()).collect();
+        assert!(held.iter().all(|ary| ary[0] == 0));
+        assert_eq!(pool.get_wait_free()[0], 0);
+
+        // the ones stored dirty are still reset after switching back
+        pool.set_reset_timing(ResetTiming::OnReturn);
+        let rest: Vec<_> = (0..3).map(|_| pool.get()).collect();
+        assert!(rest.iter().all(|ary| ary[0] == 0));
+
+        // while the ones stored clean are not reset again
+        pool.set_reset_timing(ResetTiming::OnCheckout);
+        let mut val = held.pop().unwrap();
+        val[0] = 2;
+        pool.put_no_reset(val);
+        assert_eq!(pool.get()[0], 2);
+    }
+
+    #[test]
+    fn swap_reset_handle() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        assert!(pool
+            .swap_reset_handle(ResetHandle::Func(|ary| ary[0] = 1))
+            .unwrap()
+            .is_none());
+
+        let old = pool
+            .swap_reset_handle(ResetHandle::Closure(Box::new(|ary| ary[0] = 2)))
+            .unwrap();
+
+        // the old handle is handed back intact
+        let mut ary = [0u8; 32];
+        match old {
+            Some(ResetHandle::Func(handle)) => handle(&mut ary),
+            other => panic!("unexpected handle: {:?}", other),
+        }
+        assert_eq!(ary[0], 1);
+
+        // and the new one is in place
+        let held: Vec<_> = (0..8).map(|_| pool.get()).collect();
+        for val in held {
+            pool.put(val);
+        }
+        assert_eq!(pool.get()[0], 2);
+    }
+
+    #[test]
+    fn footprint_independent_of_size() {
+        // the slots hold the pointers to the boxed elements, not the elements inline
+        assert_eq!(
+            mem::size_of::<Bucket2<[u64; 32]>>(),
+            mem::size_of::<Bucket2<u64>>()
+        );
+
+        // hence the pools differ by the elements alone
+        let large: SyncPool<[u64; 32]> = SyncPool::with_exact_buckets(1);
+        let small: SyncPool<u64> = SyncPool::with_exact_buckets(1);
+        assert_eq!(
+            large.approx_bytes() - small.approx_bytes(),
+            SLOT_CAP * (mem::size_of::<[u64; 32]>() - mem::size_of::<u64>())
+        );
+    }
+
+    #[test]
+    fn checkout_failure() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(2);
+
+        // vacate the slots of the first bucket behind its bitmap's back, such that the access will
+        // succeed but the checkout will fail
+        for pos in 0..SLOT_CAP {
+            assert!(pool.slots[0].take_behind(pos).is_some());
+        }
+
+        // the scan moves on to the second bucket instead of giving up and creating a new element
+        assert!(pool.try_get().is_some());
+        assert_eq!(pool.slots[1].len(), SLOT_CAP - 1);
+        assert_eq!(pool.cursor(), 1);
+
+        pool.get();
+        assert_eq!(pool.slots[1].len(), SLOT_CAP - 2);
+        assert_eq!(pool.snapshot().fallback_count, 0);
+    }
+
+    #[test]
+    fn with_exclusive() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(2);
+        pool.get_bucket(0).unwrap();
+
+        let total: usize = pool.with_exclusive(|buckets| {
+            buckets[1].iter_mut().take(3).for_each(|val| val[0] = 1);
+            buckets
+                .iter_mut()
+                .map(|bucket| bucket.retain(|val| val[0] == 0))
+                .sum()
+        });
+
+        assert_eq!(total, 3);
+        assert_eq!(pool.len(), 2 * SLOT_CAP - 4);
+        assert_eq!(pool.visitor_counter.0.load(Ordering::SeqCst), 1);
+
+        // the barrier is lowered when the closure panics
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            pool.with_exclusive(|_| panic!("exclusive access panicked"))
+        }));
+
+        assert!(res.is_err());
+        assert!(!pool.visitor_counter.1.load(Ordering::SeqCst));
+        assert_eq!(pool.visitor_counter.0.load(Ordering::SeqCst), 1);
+
+        // the vacated slots can be filled again
+        assert_eq!(pool.refill(4), 4);
+        assert_eq!(pool.len(), 2 * SLOT_CAP);
+    }
+
+    #[test]
+    fn max_probe() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(2);
+        let held: Vec<_> = (0..SLOT_CAP).map(|_| pool.get_bucket(0).unwrap()).collect();
+
+        // a limit of 0 is raised to 1, so only the drained bucket is probed
+        pool.set_max_probe(0);
+        assert!(pool.try_get().is_none());
+
+        // while the cursor has moved on to the next bucket
+        assert!(pool.try_get().is_some());
+
+        pool.set_max_probe(usize::MAX);
+        pool.curr.0.store(0, Ordering::SeqCst);
+        assert!(pool.try_get().is_some());
+        drop(held);
+    }
+
+    #[test]
+    fn approx_bytes() {
+        let mut pool: SyncPool<[u64; 32]> = SyncPool::with_exact_buckets(2);
+        let base = pool.approx_bytes();
+        assert!(base >= 2 * SLOT_CAP * 256);
+
+        pool.allow_expansion(true).expand(2, true);
+        assert!(pool.approx_bytes() >= base + 2 * SLOT_CAP * 256);
+    }
+
+    #[test]
+    fn read_guard() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        let mut val = pool.get_bucket(0).unwrap();
+        val[0] = 42;
+        pool.put_bucket(0, val);
+
+        let guards: Vec<_> = (0..SLOT_CAP).map(|idx| pool.read(idx).unwrap()).collect();
+        let shared = pool.read(SLOT_CAP - 1).unwrap();
+        assert!(guards.iter().any(|guard| guard[0] == 42));
+        assert!(pool.read(SLOT_CAP).is_none());
+        assert_eq!(pool.visitor_counter.0.load(Ordering::SeqCst), 2 + SLOT_CAP);
+
+        // pinned elements can't be checked out, until all of their readers are gone
+        assert!(pool.slots[0].access(true).is_err());
+
+        drop(shared);
+        assert!(pool.slots[0].access(true).is_err());
+
+        drop(guards);
+        assert!(pool.try_get().is_some());
+        assert_eq!(pool.visitor_counter.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn read_while_getting() {
+        use std::sync::atomic::AtomicBool;
+
+        struct Shared(*mut SyncPool<[u8; 32]>);
+        unsafe impl Send for Shared {}
+        unsafe impl Sync for Shared {}
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(2);
+        let mut val = pool.get_bucket(0).unwrap();
+        val[0] = 42;
+        pool.put_bucket(0, val);
+
+        // share the pool the same way the examples do, i.e. behind a raw pointer
+        let shared = Shared(&mut pool);
+        let done = AtomicBool::new(false);
+
+        let reader = unsafe { &*shared.0 };
+        let idx = (0..SLOT_CAP).find(|&idx| reader.read(idx).unwrap()[0] == 42);
+        let guard = reader.read(idx.unwrap()).unwrap();
+
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    let pool = unsafe { &mut *shared.0 };
+
+                    // the pinned element is never checked out while its reader holds it
+                    while !done.load(Ordering::Acquire) {
+                        let val = pool.get();
+                        assert_ne!(val[0], 42);
+                        pool.put(val);
+                    }
+                });
+            }
+
+            for _ in 0..64 {
+                assert_eq!(guard[0], 42);
+                thread::sleep(Duration::from_micros(100));
+            }
+
+            done.store(true, Ordering::Release);
+        });
+
+        drop(guard);
+        assert_eq!(pool.visitor_counter.0.load(Ordering::SeqCst), 1);
+        assert!((0..2 * SLOT_CAP).any(|_| pool.get()[0] == 42));
+    }
+
+    #[test]
+    fn guard_belongs_to() {
+        let first: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        let second: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        assert_ne!(first.id(), second.id());
+
+        let guard = first.read(0).unwrap();
+        assert!(guard.belongs_to(&first));
+        assert!(!guard.belongs_to(&second));
+    }
+
+    #[test]
+    fn concurrent_config_flips() {
+        let configure = AtomicUsize::new(0);
+        let masks = [
+            CONFIG_ALLOW_EXPANSION,
+            CONFIG_ADVANCING_CURSOR,
+            CONFIG_PANIC_RECLAIM,
+            CONFIG_STRICT_PUT,
+        ];
+
+        // every thread keeps flipping its own flag, and a flag of its neighbour to the same target
+        thread::scope(|s| {
+            for (i, &mask) in masks.iter().enumerate() {
+                let configure = &configure;
+                let shared = masks[(i + 1) % masks.len()];
+
+                s.spawn(move || {
+                    for round in 0..1000 {
+                        update_flag(configure, mask, round % 2 == 0);
+                        update_flag(configure, shared, true);
+                        thread::yield_now();
+                    }
+
+                    // the last round sets the flag
+                    update_flag(configure, mask, true);
+                });
+            }
+        });
+
+        assert_eq!(configure.load(Ordering::SeqCst), 0b1111);
+
+        // updates matching the current state are no-ops
+        update_flag(&configure, CONFIG_STRICT_PUT, true);
+        update_flag(&configure, CONFIG_PANIC_RECLAIM, false);
+        update_flag(&configure, CONFIG_PANIC_RECLAIM, false);
+        assert_eq!(configure.load(Ordering::SeqCst), 0b1011);
+    }
+
+    #[test]
+    fn panic_reclaim() {
+        fn reset(val: &mut [u8; 32]) {
+            if val[0] > 1 {
+                // would abort the process if it escaped while the scope is unwinding
+                panic!("dirty element");
+            }
+
+            val[0] = 0;
+        }
+
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.reset_handle(reset).install_panic_reclaim();
+
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            pool.scope(|scoped| {
+                scoped.get()[0] = 1;
+                scoped.get()[0] = 2;
+                panic!("scope panicked");
+            })
+        }));
+
+        // the element the reset handle panicked on is dropped, the other one is reset and stored
+        assert!(res.is_err());
+        assert_eq!(pool.len(), 7);
+        assert_eq!(pool.reset_failure_count(), 1);
+        assert_eq!(pool.outstanding_count(), 0);
+        assert!((0..7).map(|_| pool.get()).all(|ary| ary[0] == 0));
+
+        // out of the unwinding, the reset handle is back in charge
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.reset_handle(reset).install_panic_reclaim();
+        pool.scope(|scoped| {
+            scoped.get()[0] = 1;
+        });
+        assert_eq!(pool.len(), 8);
+
+        pool.set_panic_reclaim(false);
+        assert_eq!(
+            pool.configure.load(Ordering::SeqCst) & CONFIG_PANIC_RECLAIM,
+            0
+        );
+    }
+
+    #[test]
+    fn panic_mid_expand() {
+        use std::sync::atomic::AtomicBool;
+
+        static POISONED: AtomicBool = AtomicBool::new(false);
+
+        fn build() -> usize {
+            if POISONED.load(Ordering::Relaxed) {
+                panic!("builder failed");
+            }
+
+            1
+        }
+
+        let mut pool = SyncPool::with_builder_and_size(8, build);
+        pool.allow_expansion(true);
+
+        POISONED.store(true, Ordering::Relaxed);
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| pool.expand(1, true)));
+        assert!(res.is_err());
+        POISONED.store(false, Ordering::Relaxed);
+
+        // the barrier has been lowered while unwinding, so the pool is still available
+        assert!(!pool.visitor_counter.1.load(Ordering::SeqCst));
+        assert_eq!(*pool.get(), 1);
+        assert_eq!(pool.try_expand(1), ExpandResult::Grew(SLOT_CAP));
+
+        // the escape hatch for a barrier left behind without unwinding
+        pool.visitor_counter.1.store(true, Ordering::SeqCst);
+        assert!(pool.try_get().is_none());
+        assert!(unsafe { pool.force_clear_barrier() });
+        assert!(pool.try_get().is_some());
+        assert!(!unsafe { pool.force_clear_barrier() });
+    }
+
+    #[cfg(feature = "deterministic")]
+    #[test]
+    fn deterministic() {
+        fn run(seed: u64) -> Vec<usize> {
+            let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(64);
+            pool.set_deterministic(seed);
+
+            let held: Vec<_> = (0..40).map(|_| pool.get()).collect();
+            for val in held.into_iter().take(20) {
+                pool.put(val);
+            }
+
+            pool.slots.iter().map(|bucket| bucket.size_hint()).collect()
+        }
+
+        assert_eq!(run(42), run(42));
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(42), run(7));
+    }
+
+    #[test]
+    #[should_panic(expected = "can't find a vacancy after probing 2 buckets, with 8 of 8")]
+    fn strict_put() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.set_strict_put(false);
+        assert!(pool.put(Box::new([0u8; 32])).is_some());
+
+        pool.set_strict_put(true).set_strict_put(true);
+        pool.put(Box::new([0u8; 32]));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 1 bucket")]
+    fn zero_buckets() {
+        let _pool: SyncPool<[u8; 32]> = SyncPool::with_exact_buckets(0);
+    }
+
+    #[test]
+    fn spin_config() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::new();
+        assert_eq!(pool.spin, SpinConfig { offset: 8, cap: 4 });
+
+        let config = SpinConfig { offset: 2, cap: 1 };
+        pool.spin_config(config);
+        assert_eq!(pool.spin, config);
+
+        let ary = pool.get();
+        assert!(pool.put(ary).is_none());
+
+        // exponents past the cap would overflow the spin count
+        let config = SpinConfig {
+            offset: 64,
+            cap: 80,
+        };
+        pool.spin_config(config);
+        assert_eq!(pool.spin.offset, 16);
+        assert_eq!(pool.spin.cap, 16);
+
+        let ary = pool.get();
+        assert!(pool.put(ary).is_none());
+    }
+
+    #[test]
+    fn try_expand() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        assert_eq!(pool.try_expand(1), ExpandResult::Disabled);
+        assert!(!pool.expand(1, false));
+        assert_eq!(pool.misconfig_count(), 2);
+
+        pool.allow_expansion(true);
+        assert_eq!(pool.try_expand(2), ExpandResult::Grew(2 * SLOT_CAP));
+        assert_eq!(pool.capacity(), 3 * SLOT_CAP);
+        assert_eq!(pool.total_capacity(), 3 * SLOT_CAP);
+        assert_eq!(pool.len(), 3 * SLOT_CAP);
+
+        pool.visitor_counter.1.store(true, Ordering::SeqCst);
+        assert_eq!(pool.try_expand(1), ExpandResult::InProgress);
+        pool.visitor_counter.1.store(false, Ordering::SeqCst);
+
+        pool.visitor_counter.0.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(pool.try_expand(1), ExpandResult::Contended);
+        pool.visitor_counter.0.fetch_sub(1, Ordering::SeqCst);
+
+        pool.expand(EXPANSION_CAP, true);
+        assert_eq!(pool.try_expand(1), ExpandResult::AtCap);
+        assert!(!pool.expand(1, true));
+        assert_eq!(pool.misconfig_count(), 2);
+        assert_eq!(pool.at_cap_count(), 2);
+
+        // absurd requests are clamped to the cap
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.allow_expansion(true);
+
+        let grown = EXPANSION_CAP * SLOT_CAP;
+        assert_eq!(pool.try_expand(usize::MAX), ExpandResult::Grew(grown));
+        assert_eq!(pool.capacity(), grown + SLOT_CAP);
+    }
+
+    #[test]
+    fn outstanding_count() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        assert_eq!(pool.outstanding_count(), 0);
+
+        // balanced checkouts won't move the count
+        for _ in 0..32 {
+            let ary = pool.get();
+            pool.put(ary);
+        }
+
+        assert_eq!(pool.outstanding_count(), 0);
+
+        // leak a few elements, including the fallback ones when the pool runs dry
+        for i in 1..=12 {
+            drop(pool.get());
+            assert_eq!(pool.outstanding_count(), i);
+        }
+
+        // the returns dropped by the idle cap are back all the same
+        pool.set_max_idle(0);
+        pool.put(Box::new([0u8; 32]));
+        assert_eq!(pool.outstanding_count(), 11);
+
+        // a lazy pool counts its vacancies until they're filled
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size_lazy(8);
+        assert_eq!(pool.outstanding_count(), pool.capacity());
+        pool.refill(3);
+        assert_eq!(pool.outstanding_count(), pool.capacity() - 3);
+    }
+
+    #[test]
+    fn cursor_policy() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(32);
+        assert_eq!(pool.cursor_policy(), CursorPolicy::Sticky);
+
+        pool.allow_expansion(true);
+        pool.set_cursor_policy(CursorPolicy::Advancing);
+        assert_eq!(pool.cursor_policy(), CursorPolicy::Advancing);
+        assert!(pool.expansion_enabled());
+
+        for _ in 0..64 {
+            let ary = pool.get();
+            assert!(pool.put(ary).is_none());
+        }
+
+        pool.set_cursor_policy(CursorPolicy::Sticky);
+        assert_eq!(pool.cursor_policy(), CursorPolicy::Sticky);
+        assert!(pool.expansion_enabled());
+
+        // the buckets serving consecutive uncontended calls, by the policy
+        let served = |policy| {
+            let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(32);
+            pool.set_cursor_policy(policy);
+
+            let mut held = Vec::new();
+            let gets: Vec<_> = (0..3)
+                .map(|_| {
+                    held.push(pool.get());
+                    pool.last_checkout.load(Ordering::Relaxed)
+                })
+                .collect();
+
+            // empty the pool, and see where the returns land
+            held.extend((0..29).map(|_| pool.get()));
+            for val in held.drain(..3) {
+                assert!(pool.put(val).is_none());
+            }
+
+            let lens = pool.with_exclusive(|slots| slots.iter().map(BucketView::len).collect());
+            (gets, lens)
+        };
+
+        let (gets, lens): (_, Vec<usize>) = served(CursorPolicy::Sticky);
+        assert_eq!(gets, vec![0, 0, 0]);
+        assert_eq!(lens.iter().filter(|&&len| len > 0).count(), 1);
+
+        let (gets, lens) = served(CursorPolicy::Advancing);
+        assert_eq!(gets, vec![0, 1, 2]);
+        assert_eq!(lens.iter().filter(|&&len| len > 0).count(), 3);
+    }
+
+    #[test]
+    fn max_idle() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        let mut checkouts: Vec<_> = (0..6).map(|_| pool.get()).collect();
+        assert_eq!(pool.len(), 2);
+
+        pool.set_max_idle(4);
+
+        for _ in 0..2 {
+            assert!(pool.put(checkouts.pop().unwrap()).is_none());
+        }
+
+        assert_eq!(pool.len(), 4);
+        assert_eq!(pool.idle_drop_count(), 0);
+
+        for _ in 0..4 {
+            assert!(pool.put(checkouts.pop().unwrap()).is_none());
+        }
+
+        assert_eq!(pool.len(), 4);
+        assert_eq!(pool.idle_drop_count(), 4);
+
+        // the refill stops at the cap as well, and only counts what's been stored
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(16);
+        (0..16).for_each(|_| drop(pool.get()));
+        pool.set_max_idle(10);
+
+        assert_eq!(pool.refill(16), 10);
+        assert_eq!(pool.len(), 10);
+        assert_eq!(pool.outstanding_count(), 6);
+        assert_eq!(pool.refill(16), 0);
+        assert_eq!(pool.outstanding_count(), 6);
+        assert_eq!(pool.idle_drop_count(), 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "occupied slot")]
+    fn release_into_occupied_slot() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.slots[0].release(0, Box::new([0u8; 32]), None);
     }
 }
@@ -0,0 +1,81 @@
+//! This module contains the shared read access to the pooled elements. Instead of checking out an
+//! element exclusively, a `ReadGuard` pins the element in its slot, such that many guards can read
+//! the same element at once, e.g. for read-mostly objects like a configuration cache.
+//!
+//! Note that `SyncPool` is `!Sync`, so a `&SyncPool` (and hence a guard) can't be handed to other
+//! threads: the guards are held by the thread owning the pool, or by the threads sharing the pool
+//! outside of the borrow checker (e.g. behind a raw pointer, as in the examples). In the latter
+//! case, the `get` calls of the other threads skip the pinned element instead of checking it out
+//! from under its readers.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::prelude::*;
+//!
+//! let pool: SyncPool<Vec<u8>> = SyncPool::with_size(8);
+//!
+//! let first = pool.read(0).unwrap();
+//! let second = pool.read(0).unwrap();
+//! assert_eq!(first.len(), second.len());
+//! ```
+
+use crate::bucket::Bucket2;
+use crate::pool::{PoolId, SyncPool, VisitorGuard};
+use std::ops::Deref;
+
+/// The shared access to an element pinned in its slot by `SyncPool::read`. Every read guard of the
+/// same slot shares a reader count, and the slot stays pinned until the last read guard is dropped.
+///
+/// While pinned, the element is locked the same way as if someone were operating at the slot: `get`
+/// and `get_bucket` skip the slot and move on to the other ones (falling back to creating a new
+/// element if nothing else is available), and since the slot is occupied, no `put` will overwrite
+/// it. A read guard also counts as a visitor of the pool, so a blocking `expand` (or `with_exclusive`)
+/// waits for all read guards to be dropped, and `read` returns `None` while the barrier is raised.
+/// Hence keep the read guards short-lived.
+pub struct ReadGuard<'a, T> {
+    pool: PoolId,
+    bucket: &'a Bucket2<T>,
+    pos: usize,
+    val: &'a T,
+    _visitor: VisitorGuard<'a>,
+}
+
+impl<'a, T> ReadGuard<'a, T> {
+    pub(crate) fn new(
+        pool: PoolId,
+        bucket: &'a Bucket2<T>,
+        pos: usize,
+        val: &'a T,
+        visitor: VisitorGuard<'a>,
+    ) -> Self {
+        ReadGuard {
+            pool,
+            bucket,
+            pos,
+            val,
+            _visitor: visitor,
+        }
+    }
+
+    /// If the guard has been obtained from `pool`, e.g. to assert in tests that the pinned elements
+    /// are read from the right one of many pools.
+    pub fn belongs_to(&self, pool: &SyncPool<T>) -> bool {
+        self.pool == pool.id()
+    }
+}
+
+impl<'a, T> Deref for ReadGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.val
+    }
+}
+
+impl<'a, T> Drop for ReadGuard<'a, T> {
+    fn drop(&mut self) {
+        // unpin the slot first, the visitor registration is released afterwards
+        self.bucket.unpin(self.pos);
+    }
+}
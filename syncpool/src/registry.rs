@@ -0,0 +1,161 @@
+//! This module contains the global registry of the pools, which offers a single place to query the
+//! statistics of every pool in a large program. A pool is registered with `SyncPool::register`, and
+//! the registry only holds a weak handle to the pool's statistics, so it never keeps a pool alive;
+//! once a pool is dropped, it simply disappears from the registry.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::prelude::*;
+//! use syncpool::registry;
+//!
+//! let mut pool: SyncPool<Vec<u8>> = SyncPool::with_size(8);
+//! pool.register("buffers");
+//!
+//! let val = pool.get();
+//! pool.put(val);
+//!
+//! let stats = registry::all_stats();
+//! let buffers = stats.iter().find(|stats| stats.name == "buffers").unwrap();
+//! assert_eq!(buffers.capacity, 8);
+//!
+//! drop(pool);
+//! assert!(registry::all_stats().iter().all(|stats| stats.name != "buffers"));
+//! ```
+
+use crate::pool::{PoolCounters, SyncPool};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+static REGISTRY: Mutex<Vec<(String, Weak<PoolCounters>)>> = Mutex::new(Vec::new());
+
+/// The snapshot of a registered pool's statistics. See the getters of `SyncPool` for the meaning of each
+/// figure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolStats {
+    /// The name the pool has been registered with.
+    pub name: String,
+    pub capacity: usize,
+    pub miss_count: usize,
+    /// The number of elements created on the fly by `get` since the pool was created.
+    pub fallback_count: usize,
+    pub misconfig_count: usize,
+    pub at_cap_count: usize,
+    pub idle_drop_count: usize,
+    pub eviction_count: usize,
+    pub reset_failure_count: usize,
+    pub checkout_count: usize,
+    pub return_count: usize,
+    pub peak_outstanding: usize,
+}
+
+impl<T> SyncPool<T> {
+    /// Register the pool in the global registry with `name`, such that its statistics will be
+    /// included in `registry::all_stats`. Registering the same pool again only updates its name.
+    /// Names are not required to be unique.
+    pub fn register(&self, name: &str) {
+        let handle = self.stats_handle();
+        let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match registry
+            .iter_mut()
+            .find(|(_, registered)| registered.ptr_eq(&handle))
+        {
+            Some(entry) => entry.0 = name.to_string(),
+            None => registry.push((name.to_string(), handle)),
+        }
+    }
+}
+
+/// Take a snapshot of the statistics of every registered pool that is still alive, in the order
+/// the pools have been registered. The pools dropped since the last call are purged here.
+///
+/// Each snapshot is taken from the pool's atomic counters without blocking the pool, so the figures
+/// of a pool in use are best-effort, same as their `SyncPool` counterparts.
+pub fn all_stats() -> Vec<PoolStats> {
+    let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut stats = Vec::with_capacity(registry.len());
+
+    registry.retain(|(name, handle)| match handle.upgrade() {
+        Some(counters) => {
+            stats.push(snapshot(name, &counters));
+            true
+        }
+        None => false,
+    });
+
+    stats
+}
+
+fn snapshot(name: &str, counters: &Arc<PoolCounters>) -> PoolStats {
+    PoolStats {
+        name: name.to_string(),
+        capacity: counters.capacity.load(Ordering::Acquire),
+        miss_count: counters.miss_count.load(Ordering::Acquire),
+        fallback_count: counters.fallback_count.load(Ordering::Acquire),
+        misconfig_count: counters.misconfig_count.load(Ordering::Acquire),
+        at_cap_count: counters.at_cap_count.load(Ordering::Acquire),
+        idle_drop_count: counters.idle_drop_count.load(Ordering::Acquire),
+        eviction_count: counters.eviction_count.load(Ordering::Acquire),
+        reset_failure_count: counters.reset_failure_count.load(Ordering::Acquire),
+        checkout_count: counters.checkout_count.load(Ordering::Acquire),
+        return_count: counters.return_count.load(Ordering::Acquire),
+        peak_outstanding: counters.peak_outstanding.load(Ordering::Acquire),
+    }
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use super::*;
+    use crate::PoolManager;
+    use std::thread;
+
+    fn find(name: &str) -> Option<PoolStats> {
+        all_stats().into_iter().find(|stats| stats.name == name)
+    }
+
+    #[test]
+    fn weak_registration() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+        pool.register("registry_tests::weak");
+
+        let held: Vec<_> = (0..10).map(|_| pool.get()).collect();
+        pool.allow_expansion(true).expand(1, true);
+
+        let stats = find("registry_tests::weak").unwrap();
+        assert_eq!(stats.capacity, 16);
+        assert_eq!(stats.fallback_count, 2);
+        assert_eq!(stats.peak_outstanding, 10);
+
+        // renaming keeps a single entry
+        pool.register("registry_tests::renamed");
+        assert!(find("registry_tests::weak").is_none());
+
+        drop(held);
+        drop(pool);
+        assert!(find("registry_tests::renamed").is_none());
+    }
+
+    #[test]
+    fn concurrent_registration() {
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    let pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+                    pool.register("registry_tests::concurrent");
+                    all_stats();
+                    pool
+                })
+            })
+            .collect();
+
+        let pools: Vec<_> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+        let count = all_stats()
+            .iter()
+            .filter(|stats| stats.name == "registry_tests::concurrent")
+            .count();
+
+        assert_eq!(count, 4);
+        drop(pools);
+    }
+}
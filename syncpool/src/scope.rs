@@ -0,0 +1,159 @@
+//! This module contains the scoped access to the `SyncPool`, where all elements checked out through
+//! the scope handle will be returned to the pool automatically once the scope is over, including
+//! when the scope is unwinding from a panic. If the pool's reset handle may panic as well, consider
+//! `SyncPool::install_panic_reclaim`, such that a double panic won't abort the process.
+//!
+//! Elements obtained from the scope are lent out as `&mut T`, which are tied to the lifetime of the
+//! scope, so they can't be moved out of the scope. If the content of an element shall outlive the
+//! scope, either move the content out of the element (e.g. via `std::mem::take`), or check out the
+//! element from the pool directly with `SyncPool::get`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::prelude::*;
+//!
+//! let mut pool: SyncPool<Vec<u8>> = SyncPool::with_size(8);
+//!
+//! let total = pool.scope(|scoped| {
+//!     let first = scoped.get();
+//!     let second = scoped.get();
+//!
+//!     first.push(1);
+//!     second.push(2);
+//!
+//!     first.len() + second.len()
+//! });
+//!
+//! assert_eq!(total, 2);
+//! assert_eq!(pool.len(), 8);
+//! ```
+
+use crate::pool::SyncPool;
+use std::cell::RefCell;
+use std::ptr::NonNull;
+
+/// The handle to the `SyncPool` within a `SyncPool::scope` call. Every element checked out through
+/// the handle is held by the scope, and will be returned to the pool when the scope ends.
+pub struct Scope<'a, T> {
+    pool: RefCell<&'a mut SyncPool<T>>,
+    held: RefCell<Vec<NonNull<T>>>,
+}
+
+impl<'a, T> Scope<'a, T> {
+    /// Check out an element from the pool, which will be returned to the pool at the end of the
+    /// scope. Same as `SyncPool::get`, a new element will be created if the pool can't offer one.
+    #[allow(clippy::mut_from_ref)]
+    pub fn get(&self) -> &mut T {
+        let val = self.pool.borrow_mut().get();
+
+        // The scope owns the element as a raw pointer rather than a box, such that growing the vec
+        // never moves (and retags) a box the lent-out reference is derived from. The box is rebuilt
+        // only when the scope drops, which can't happen while any of the lent-out references (bound
+        // to the scope's lifetime) is still alive, and each pointer is lent out exactly once.
+        let mut ptr = NonNull::from(Box::leak(val));
+        self.held.borrow_mut().push(ptr);
+        unsafe { ptr.as_mut() }
+    }
+
+    /// The number of elements currently held by the scope.
+    pub fn held(&self) -> usize {
+        self.held.borrow().len()
+    }
+}
+
+impl<'a, T> Drop for Scope<'a, T> {
+    fn drop(&mut self) {
+        let pool = self.pool.get_mut();
+
+        // take back the ownership of all the elements first, so they're still dropped if the
+        // reclaim panics halfway
+        let held: Vec<Box<T>> = self
+            .held
+            .get_mut()
+            .drain(..)
+            .map(|ptr| unsafe { Box::from_raw(ptr.as_ptr()) })
+            .collect();
+
+        for val in held {
+            // if the pool is full, the element will just be dropped here
+            pool.reclaim(val);
+        }
+    }
+}
+
+impl<T> SyncPool<T> {
+    /// Run the closure with a scope handle to the pool. Any elements checked out via the handle will
+    /// be returned to the pool when the closure ends, even if the closure panics. This is handy for
+    /// request-scoped or test-scoped usages, where the control flow could make it easy to forget
+    /// returning the elements.
+    pub fn scope<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&Scope<'_, T>) -> R,
+    {
+        let scope = Scope {
+            pool: RefCell::new(self),
+            held: RefCell::new(Vec::new()),
+        };
+
+        f(&scope)
+    }
+}
+
+#[cfg(test)]
+mod scope_tests {
+    use super::*;
+    use crate::PoolState;
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn return_on_exit() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+
+        pool.scope(|scoped| {
+            for i in 0..6 {
+                let ary = scoped.get();
+                ary[0] = i;
+            }
+
+            assert_eq!(scoped.held(), 6);
+        });
+
+        assert_eq!(pool.len(), 8);
+    }
+
+    #[test]
+    fn stable_refs() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+
+        pool.scope(|scoped| {
+            let first = scoped.get();
+            first[0] = 42;
+
+            // growing the held list (and checking out new elements) leaves the first one in place
+            let rest: Vec<_> = (1..64).map(|_| scoped.get()).collect();
+            first[1] = 7;
+
+            assert_eq!(rest.len(), 63);
+            assert_eq!(&first[..2], &[42, 7]);
+        });
+
+        assert_eq!(pool.len(), 8);
+    }
+
+    #[test]
+    fn return_on_panic() {
+        let mut pool: SyncPool<[u8; 32]> = SyncPool::with_size(8);
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.scope(|scoped| {
+                scoped.get();
+                scoped.get();
+                panic!("scope panicked");
+            })
+        }));
+
+        assert!(res.is_err());
+        assert_eq!(pool.len(), 8);
+    }
+}
@@ -0,0 +1,118 @@
+//! This module contains the pool of pools, which spreads the elements over a number of independent
+//! `SyncPool` shards, e.g. one per worker thread or per data shard. Each shard has its own buckets
+//! and cursors, so the callers routed to different shards never contend with each other. The calls
+//! are routed either by an explicit shard index, or by the hash of a key.
+//!
+//! An element can be returned to any shard, but returning it to the shard it has been checked out
+//! from (i.e. with the same key or index) keeps the shards balanced.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::ShardedPool;
+//!
+//! let mut pool: ShardedPool<Vec<u8>> = ShardedPool::new(4, 16);
+//!
+//! let buf = pool.get_sharded(&"tenant-42");
+//! assert!(pool.put_sharded(&"tenant-42", buf).is_none());
+//!
+//! assert_eq!(pool.shard_count(), 4);
+//! ```
+
+use crate::pool::SyncPool;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// A fixed number of `SyncPool` shards, see the module documentation.
+pub struct ShardedPool<T> {
+    shards: Vec<SyncPool<T>>,
+}
+
+impl<T: Default> ShardedPool<T> {
+    /// Create `shards` pools (at least 1), each created by `SyncPool::with_size(per_shard_size)`.
+    pub fn new(shards: usize, per_shard_size: usize) -> Self {
+        ShardedPool {
+            shards: (0..shards.max(1))
+                .map(|_| SyncPool::with_size(per_shard_size))
+                .collect(),
+        }
+    }
+}
+
+impl<T> ShardedPool<T> {
+    /// The number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// The index of the shard the `key` is routed to. The routing is stable for the lifetime of the
+    /// pool, and the same across pools with the same number of shards.
+    pub fn shard_for<K: Hash + ?Sized>(&self, key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    /// Check out an element from the shard the `key` is routed to, same as `SyncPool::get`.
+    pub fn get_sharded<K: Hash + ?Sized>(&mut self, key: &K) -> Box<T> {
+        let idx = self.shard_for(key);
+        self.shards[idx].get()
+    }
+
+    /// Return the element to the shard the `key` is routed to, same as `SyncPool::put`.
+    pub fn put_sharded<K: Hash + ?Sized>(&mut self, key: &K, val: Box<T>) -> Option<Box<T>> {
+        let idx = self.shard_for(key);
+        self.shards[idx].put(val)
+    }
+
+    /// Check out an element from the shard at `idx`, wrapping around the number of shards.
+    pub fn get_at(&mut self, idx: usize) -> Box<T> {
+        let len = self.shards.len();
+        self.shards[idx % len].get()
+    }
+
+    /// Return the element to the shard at `idx`, wrapping around the number of shards.
+    pub fn put_at(&mut self, idx: usize, val: Box<T>) -> Option<Box<T>> {
+        let len = self.shards.len();
+        self.shards[idx % len].put(val)
+    }
+
+    /// The shard at `idx`, e.g. to query its `PoolState`, or `None` if `idx` is out of bounds.
+    pub fn shard(&self, idx: usize) -> Option<&SyncPool<T>> {
+        self.shards.get(idx)
+    }
+
+    /// The shard at `idx`, e.g. to configure it, or `None` if `idx` is out of bounds.
+    pub fn shard_mut(&mut self, idx: usize) -> Option<&mut SyncPool<T>> {
+        self.shards.get_mut(idx)
+    }
+}
+
+#[cfg(test)]
+mod sharded_tests {
+    use super::*;
+    use crate::PoolState;
+
+    #[test]
+    fn routing() {
+        let mut pool: ShardedPool<[u8; 32]> = ShardedPool::new(4, 8);
+        assert_eq!(pool.shard_count(), 4);
+        assert!(pool.shard(4).is_none());
+
+        let idx = pool.shard_for("key");
+        assert_eq!(idx, pool.shard_for("key"));
+
+        let val = pool.get_sharded("key");
+        assert_eq!(pool.shard(idx).unwrap().len(), 7);
+
+        assert!(pool.put_sharded("key", val).is_none());
+        assert_eq!(pool.shard(idx).unwrap().len(), 8);
+
+        let val = pool.get_at(5);
+        assert_eq!(pool.shard(1).unwrap().len(), 7);
+        assert!(pool.put_at(1, val).is_none());
+
+        assert_eq!(ShardedPool::<[u8; 32]>::new(0, 8).shard_count(), 1);
+    }
+}
@@ -0,0 +1,124 @@
+//! This module contains the pool of fixed-length boxed byte slices, e.g. for fixed-size framing,
+//! where a `Box<[u8]>` can't be resized by accident the way a `Vec<u8>` can. Every buffer of the
+//! pool has the same length, which is preserved across the reuse. The returned buffers are either
+//! zeroed, or left as-is for the callers that overwrite the whole buffer anyway.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::SliceBufferPool;
+//!
+//! let mut pool = SliceBufferPool::new(1500, 16, true);
+//!
+//! let mut frame = pool.get();
+//! assert_eq!(frame.len(), 1500);
+//!
+//! frame[0] = 42;
+//! pool.put(frame);
+//!
+//! assert_eq!(pool.get()[0], 0);
+//! ```
+
+use crate::pool::{PoolManager, SyncPool};
+
+/// A `SyncPool` of `Box<[u8]>` buffers, which all have the same length.
+pub struct SliceBufferPool {
+    pool: SyncPool<Box<[u8]>>,
+    len: usize,
+}
+
+impl SliceBufferPool {
+    /// Create a pool of `capacity` buffers (rounded the same way as `SyncPool::with_size`), each of
+    /// `len` bytes. If `zeroed` is set, the buffers are zeroed when they're returned to the pool;
+    /// otherwise, the next `get` may observe the content left by the previous user.
+    pub fn new(len: usize, capacity: usize, zeroed: bool) -> Self {
+        let mut pool = SyncPool::with_size(capacity);
+
+        pool.with_exclusive(|buckets| {
+            for buf in buckets.iter_mut().flat_map(|bucket| bucket.iter_mut()) {
+                *buf = vec![0u8; len].into_boxed_slice();
+            }
+        });
+
+        if zeroed {
+            pool.reset_handle(zero);
+        }
+
+        SliceBufferPool { pool, len }
+    }
+
+    /// The length of every buffer from this pool in bytes.
+    pub fn buffer_len(&self) -> usize {
+        self.len
+    }
+
+    /// Obtain a buffer from the pool, or allocate a new (zeroed) one if the pool can't offer one.
+    pub fn get(&mut self) -> Box<[u8]> {
+        match self.pool.try_get() {
+            Some(buf) => *buf,
+            None => vec![0u8; self.len].into_boxed_slice(),
+        }
+    }
+
+    /// Return the buffer to the pool. Same as `SyncPool::put`, return `None` if the buffer has been
+    /// placed in the pool; otherwise, i.e. if the pool is full, or if the buffer's length doesn't
+    /// match the pool's, the buffer is handed back to the caller.
+    pub fn put(&mut self, buf: Box<[u8]>) -> Option<Box<[u8]>> {
+        if buf.len() != self.len {
+            return Some(buf);
+        }
+
+        self.pool.put(Box::new(buf)).map(|buf| *buf)
+    }
+
+    /// The underlying pool, e.g. to query its `PoolState`.
+    pub fn pool(&self) -> &SyncPool<Box<[u8]>> {
+        &self.pool
+    }
+}
+
+fn zero(buf: &mut Box<[u8]>) {
+    buf.fill(0);
+}
+
+#[cfg(test)]
+mod slice_tests {
+    use super::*;
+    use crate::PoolState;
+
+    #[test]
+    fn round_trip() {
+        let mut pool = SliceBufferPool::new(100, 8, true);
+        assert_eq!(pool.pool().len(), 8);
+
+        let held: Vec<_> = (0..10).map(|_| pool.get()).collect();
+        assert!(held.iter().all(|buf| buf.len() == 100));
+
+        for mut buf in held {
+            buf[99] = 1;
+            pool.put(buf);
+        }
+
+        let buf = pool.get();
+        assert_eq!(buf.len(), 100);
+        assert!(buf.iter().all(|&b| b == 0));
+
+        // buffers of other lengths are rejected
+        assert!(pool.put(vec![0u8; 64].into_boxed_slice()).is_some());
+    }
+
+    #[test]
+    fn as_is() {
+        let mut pool = SliceBufferPool::new(16, 8, false);
+        let held: Vec<_> = (0..8).map(|_| pool.get()).collect();
+
+        for mut buf in held {
+            buf[0] = 7;
+            pool.put(buf);
+        }
+
+        let buf = pool.get();
+        assert_eq!(buf.len(), 16);
+        assert_eq!(buf[0], 7);
+    }
+}
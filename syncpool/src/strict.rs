@@ -0,0 +1,88 @@
+//! This module contains the pool that never allocates on the fly. A `StrictPool` only offers the
+//! elements created along with the pool: there's no `get` falling back to creating a new element,
+//! so a checkout from an empty pool yields `None` instead of an allocation, and the element type
+//! doesn't need to implement `Default`. This makes "no surprise allocations" a property of the type
+//! rather than of a runtime flag, e.g. for the hot paths where an allocation is a bug.
+//!
+//! The strict pool is built from a configured `SyncPool` via `SyncPool::into_strict`, and doesn't
+//! hand out the `&mut SyncPool`, so neither `get` nor `refill` can be reached through it.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::prelude::*;
+//!
+//! struct Frame([u8; 1500]);
+//!
+//! let mut pool = SyncPool::with_builder_and_size(8, || Frame([0u8; 1500])).into_strict();
+//!
+//! let held: Vec<_> = (0..8).map(|_| pool.try_get().unwrap()).collect();
+//! assert!(pool.try_get().is_none());
+//!
+//! for frame in held {
+//!     pool.put(frame);
+//! }
+//!
+//! assert!(pool.try_get().is_some());
+//! ```
+
+use crate::pool::SyncPool;
+
+/// A `SyncPool` without the allocating checkouts, see the module documentation.
+pub struct StrictPool<T> {
+    pool: SyncPool<T>,
+}
+
+impl<T> StrictPool<T> {
+    /// Check out an element from the pool, or return `None` if the pool can't offer one (i.e. it's
+    /// empty, too contended, or the write barrier is raised). Nothing is allocated either way.
+    pub fn try_get(&mut self) -> Option<Box<T>> {
+        self.pool.try_get()
+    }
+
+    /// Return the element to the pool, same as `SyncPool::put`.
+    pub fn put(&mut self, val: Box<T>) -> Option<Box<T>> {
+        self.pool.put(val)
+    }
+
+    /// The underlying pool, e.g. to query its `PoolState`.
+    pub fn pool(&self) -> &SyncPool<T> {
+        &self.pool
+    }
+}
+
+impl<T> SyncPool<T> {
+    /// Move the pool into a `StrictPool`, which never allocates on the fly. Configure the pool (e.g.
+    /// its reset handle) before the conversion, since the strict pool doesn't offer `&mut` access to
+    /// it.
+    pub fn into_strict(self) -> StrictPool<T> {
+        StrictPool { pool: self }
+    }
+}
+
+#[cfg(test)]
+mod strict_tests {
+    use super::*;
+    use crate::{PoolManager, PoolState};
+
+    struct NoDefault(usize);
+
+    #[test]
+    fn no_fallback() {
+        let mut pool = SyncPool::with_builder_and_size(8, || NoDefault(1));
+        pool.reset_handle(|val| val.0 = 2);
+
+        let mut pool = pool.into_strict();
+        let held: Vec<_> = (0..8).map(|_| pool.try_get().unwrap()).collect();
+        assert!(held.iter().all(|val| val.0 == 1));
+
+        assert!(pool.try_get().is_none());
+        assert_eq!(pool.pool().miss_count(), 0);
+
+        for val in held {
+            assert!(pool.put(val).is_none());
+        }
+
+        assert_eq!(pool.try_get().unwrap().0, 2);
+    }
+}
@@ -0,0 +1,144 @@
+//! This module contains the tiered recycling of the pooled elements, e.g. a fast pool local to a
+//! thread in front of a global pool shared by all of them. An element checked out of the local
+//! pool via `PoolHandle::get_tiered` is held by a `TieredGuard`, which returns the element to the
+//! local pool when it drops, or to the overflow pool if the local pool is full, such that the
+//! surplus of one thread can be picked up by the others instead of being dropped.
+//!
+//! Both pools are reached through a `PoolHandle`, and every guard holds a clone of each, so any
+//! number of guards can be live at a time, and both pools stay alive for as long as any guard
+//! does, even if all other handles to them have been dropped. If the overflow pool is full as well,
+//! the element is dropped.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::prelude::*;
+//!
+//! let global = SyncPool::with_size(8).into_handle();
+//! let local: PoolHandle<Vec<u8>> = SyncPool::with_size(8).into_handle();
+//!
+//! let extra = global.get();
+//! {
+//!     let mut buf = local.get_tiered(&global);
+//!     buf.push(1);
+//!
+//!     // the local pool is full again ...
+//!     assert!(buf.origin().put(extra).is_none());
+//! }
+//!
+//! // ... so the guard's element has overflowed to the global pool
+//! assert_eq!(global.with(|pool| pool.len()), 8);
+//! ```
+
+use crate::handle::PoolHandle;
+use std::ops::{Deref, DerefMut};
+
+/// An element checked out from a pool with an overflow pool to fall back to when returning it, see
+/// the module documentation.
+pub struct TieredGuard<T> {
+    origin: PoolHandle<T>,
+    overflow: PoolHandle<T>,
+    val: Option<Box<T>>,
+}
+
+impl<T> TieredGuard<T> {
+    /// The handle of the pool the element has been checked out from.
+    pub fn origin(&self) -> &PoolHandle<T> {
+        &self.origin
+    }
+
+    /// Take the element out of the guard, such that it won't be returned to either pool.
+    pub fn into_inner(mut self) -> Box<T> {
+        self.val
+            .take()
+            .expect("the element is only taken when dropped")
+    }
+}
+
+impl<T> Deref for TieredGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.val
+            .as_ref()
+            .expect("the element is only taken when dropped")
+    }
+}
+
+impl<T> DerefMut for TieredGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.val
+            .as_mut()
+            .expect("the element is only taken when dropped")
+    }
+}
+
+impl<T> Drop for TieredGuard<T> {
+    fn drop(&mut self) {
+        if let Some(val) = self.val.take() {
+            // the origin first, then the overflow; if both are full, the element is dropped here
+            if let Some(val) = self.origin.with(|pool| pool.reclaim(val)) {
+                self.overflow.put(val);
+            }
+        }
+    }
+}
+
+impl<T> PoolHandle<T> {
+    /// Check out an element as a `TieredGuard`, which returns it to this pool when it drops, or to
+    /// the `overflow` pool if this one is full, including when the thread is unwinding from a
+    /// panic. Same as `get`, a new element will be created if the pool can't offer one. The guard
+    /// holds a clone of both handles, see the module documentation.
+    pub fn get_tiered(&self, overflow: &PoolHandle<T>) -> TieredGuard<T> {
+        TieredGuard {
+            origin: self.clone(),
+            overflow: overflow.clone(),
+            val: Some(self.get()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tiered_tests {
+    use super::*;
+    use crate::{PoolState, SyncPool};
+
+    #[test]
+    fn overflow_on_full() {
+        let global = SyncPool::with_size(8).into_handle();
+        let local: PoolHandle<usize> = SyncPool::with_size(8).into_handle();
+
+        // returned to the origin while it has room
+        drop(local.get_tiered(&global));
+        assert_eq!(local.with(|pool| pool.len()), 8);
+
+        let held: Vec<_> = (0..8).map(|_| global.get()).collect();
+        {
+            let mut guard = local.get_tiered(&global);
+            *guard = 42;
+
+            let extra = held.into_iter().next().unwrap();
+            assert!(guard.origin().put(extra).is_none());
+        }
+
+        assert_eq!(local.with(|pool| pool.len()), 8);
+        assert_eq!(global.with(|pool| pool.len()), 1);
+        assert_eq!(*global.get(), 42);
+    }
+
+    #[test]
+    fn live_together() {
+        let global = SyncPool::with_size(8).into_handle();
+        let local: PoolHandle<usize> = SyncPool::with_size(8).into_handle();
+        let held: Vec<_> = (0..4).map(|_| global.get()).collect();
+
+        // more guards than the local pool holds, the surplus overflows as they drop
+        let guards: Vec<_> = (0..12).map(|_| local.get_tiered(&global)).collect();
+        assert_eq!(local.with(|pool| pool.len()), 0);
+
+        drop(guards);
+        assert_eq!(local.with(|pool| pool.len()), 8);
+        assert_eq!(global.with(|pool| pool.len()), 8);
+        drop(held);
+    }
+}
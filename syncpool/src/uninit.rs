@@ -0,0 +1,166 @@
+//! This module contains the API to check out the storage of a pooled element as uninitialized
+//! memory, such that a very large struct can be constructed in place, directly in the heap memory
+//! owned by the pool, instead of being built somewhere else and then moved (i.e. `memcpy`-ed) in.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use syncpool::prelude::*;
+//!
+//! struct BigStruct {
+//!     id: usize,
+//!     buf: [u8; 0x10_000],
+//! }
+//!
+//! let mut pool = SyncPool::with_packer(|mut src: Box<BigStruct>| {
+//!     src.id = 0;
+//!     src
+//! });
+//!
+//! if let Some(mut guard) = pool.get_uninit() {
+//!     let ptr = guard.as_uninit().as_mut_ptr();
+//!
+//!     // initialize every field in place
+//!     unsafe {
+//!         (*ptr).id = 42;
+//!         (*ptr).buf = [7u8; 0x10_000];
+//!     }
+//!
+//!     let big = unsafe { guard.assume_init() };
+//!     assert_eq!(big.id, 42);
+//!
+//!     // the element is returned to the pool when the guard is dropped
+//! };
+//! ```
+
+use crate::pool::SyncPool;
+use std::mem::MaybeUninit;
+use std::ptr;
+
+/// A checked out element whose content has been dropped, such that the caller can construct a new
+/// value in place. The guard holds the pool exclusively until it is dropped.
+///
+/// When the guard is dropped, the element is returned to the pool if it has been initialized, i.e.
+/// `assume_init` or `write` has been called. Otherwise, the uninitialized memory is deallocated
+/// without running any destructor, and the pool will have one vacant position, which can be filled
+/// again by `put` or `refill`.
+pub struct UninitGuard<'a, T> {
+    pool: &'a mut SyncPool<T>,
+    val: Option<Box<MaybeUninit<T>>>,
+    init: bool,
+}
+
+impl<'a, T> UninitGuard<'a, T> {
+    /// The uninitialized storage of the element.
+    pub fn as_uninit(&mut self) -> &mut MaybeUninit<T> {
+        self.val.as_mut().expect("the guard always holds the storage")
+    }
+
+    /// Mark the storage as initialized, and return the reference to the initialized value.
+    ///
+    /// # Safety
+    ///
+    /// The caller must make sure that every field of the value has been properly initialized via
+    /// `as_uninit` before calling this API, otherwise reading the value, or returning it to the
+    /// pool (where it will be handed out by `get` or dropped later), is undefined behavior.
+    pub unsafe fn assume_init(&mut self) -> &mut T {
+        self.init = true;
+        &mut *self.as_uninit().as_mut_ptr()
+    }
+
+    /// Move the value into the storage, and return the reference to the initialized value. If the
+    /// storage has been initialized already, the old value will be dropped first.
+    pub fn write(&mut self, val: T) -> &mut T {
+        let init = self.init;
+        let slot = self.as_uninit();
+
+        if init {
+            unsafe { ptr::drop_in_place(slot.as_mut_ptr()) };
+        }
+
+        let ptr = slot.as_mut_ptr();
+        unsafe {
+            ptr.write(val);
+            self.init = true;
+            &mut *ptr
+        }
+    }
+
+    /// Take the initialized element out of the guard instead of returning it to the pool. If the
+    /// storage has not been initialized, the memory will be deallocated and `None` is returned.
+    pub fn into_box(mut self) -> Option<Box<T>> {
+        let val = self.val.take()?;
+
+        if self.init {
+            Some(unsafe { Box::from_raw(Box::into_raw(val) as *mut T) })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> Drop for UninitGuard<'a, T> {
+    fn drop(&mut self) {
+        if let Some(val) = self.val.take() {
+            if self.init {
+                // the storage has been initialized, so it's safe to restore the typed box
+                let boxed = unsafe { Box::from_raw(Box::into_raw(val) as *mut T) };
+                self.pool.reclaim(boxed);
+            }
+
+            // otherwise, `val` only deallocates the memory here, no destructor will run
+        }
+    }
+}
+
+impl<T> SyncPool<T> {
+    /// Check out an element from the pool as uninitialized storage for in-place construction. The
+    /// old content of the element will be dropped before the storage is handed out. Return `None`
+    /// if the pool can't offer an element at the moment, since there is no point in allocating new
+    /// storage for the caller.
+    pub fn get_uninit(&mut self) -> Option<UninitGuard<'_, T>> {
+        let raw = Box::into_raw(self.try_get()?);
+
+        // Drop the old content in place. The pointer is turned into a raw one first, so if the
+        // destructor panics, the memory is leaked instead of being dropped twice.
+        unsafe { ptr::drop_in_place(raw) };
+
+        Some(UninitGuard {
+            pool: self,
+            val: Some(unsafe { Box::from_raw(raw as *mut MaybeUninit<T>) }),
+            init: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod uninit_tests {
+    use super::*;
+    use crate::PoolState;
+
+    #[test]
+    fn init_and_return() {
+        let mut pool: SyncPool<Vec<u8>> = SyncPool::with_size(8);
+
+        {
+            let mut guard = pool.get_uninit().unwrap();
+            let vec = guard.write(vec![1, 2, 3]);
+            assert_eq!(vec.len(), 3);
+        }
+
+        assert_eq!(pool.len(), 8);
+    }
+
+    #[test]
+    fn drop_uninit() {
+        let mut pool: SyncPool<Vec<u8>> = SyncPool::with_size(8);
+
+        drop(pool.get_uninit().unwrap());
+        assert_eq!(pool.len(), 7);
+
+        let mut guard = pool.get_uninit().unwrap();
+        guard.write(vec![42]);
+        assert_eq!(guard.into_box().unwrap()[0], 42);
+        assert_eq!(pool.len(), 6);
+    }
+}
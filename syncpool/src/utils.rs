@@ -3,7 +3,9 @@
 use crate::boxed::make_box;
 use crate::bucket::SLOT_CAP;
 use crate::pool::ElemBuilder;
-use std::sync::atomic;
+use std::hint;
+#[cfg(feature = "deterministic")]
+use std::sync::atomic::{AtomicU64, Ordering};
 
 const GET_MASK: u16 = 0b1010_1010_1010_1010;
 const PUT_MASK: u16 = 0b1111_1111_1111_1111;
@@ -23,78 +25,45 @@ pub(crate) fn make_elem<T>(builder: &ElemBuilder<T>) -> Box<T> {
 #[inline(always)]
 pub(crate) fn cpu_relax(count: usize) {
     for _ in 0..(1 << count) {
-        atomic::spin_loop_hint()
+        hint::spin_loop()
     }
 }
 
-pub(crate) fn check_len(src: u16) -> usize {
-    match src & FULL_FLAG {
-        0 => 0,
-        FULL_FLAG => 8,
-        mut base => {
-            let mut count = 0;
-
-            while base > 0 {
-                if base & 1 == 1 {
-                    count += 1;
-                }
-
-                base >>= 2;
-            }
-
-            count
-        }
+/// If the deterministic mode is enabled, override the bucket position with the next one in the
+/// reproducible sequence, i.e. a linear congruential generator seeded by `set_deterministic`.
+#[cfg(feature = "deterministic")]
+#[inline]
+pub(crate) fn next_in_sequence(seq: &Option<AtomicU64>, pos: &mut usize, cap: usize) {
+    if let Some(state) = seq {
+        let next = state
+            .load(Ordering::Relaxed)
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+
+        state.store(next, Ordering::Relaxed);
+        *pos = (next >> 33) as usize % cap;
     }
 }
 
+pub(crate) fn check_len(src: u16) -> usize {
+    // the element bit of each position is the low bit of its pair
+    (src & FULL_FLAG).count_ones() as usize
+}
+
 /// Assuming we have 8 elements per slot, otherwise must update the assumption.
 pub(crate) fn enter(src: u16, get: bool) -> Result<u16, ()> {
-    // get the base bits to check on. If we're not going to meet the needs, terminate early.
-    let mut base = if get {
-        if src == 0 {
-            return Err(());
-        }
-
-        src ^ GET_MASK
-    } else {
-        if src == FULL_FLAG {
-            return Err(());
-        }
-
-        src ^ PUT_MASK
-    };
-
-    // find the starting position for the spot check
-    let mut pos: u16 = {
-        // a little trick: pre-calculate the starting point for finding the location
-        let val = (base & PUT_MASK).trailing_zeros() as u16;
-
-        // if bit 15 (or above) is 0, then we won't find a location in this bucket, skip the
-        // remainder logic/loop.
-        if val > 14 {
-            return Err(());
-        }
-
-        if val % 2 == 1 {
-            base >>= val + 1;
-            (val + 1) / 2
-        } else {
-            base >>= val;
-            val / 2
-        }
-    };
-
-    while base > 0 {
-        if base & 0b11 == 0b11 {
-            // update the state and the position
-            return Ok(pos);
-        }
-
-        pos += 1;
-        base >>= 2;
+    // flip the bits such that a wanted position reads 0b11, i.e. free to lock, and holding an
+    // element (when getting) or empty (when putting).
+    let base = if get { src ^ GET_MASK } else { src ^ PUT_MASK };
+
+    // fold each pair into its low bit, which is set only if both bits of the pair are set, then the
+    // first wanted position is found at once, instead of walking the bitmap pair by pair.
+    let wanted = base & (base >> 1) & FULL_FLAG;
+    if wanted == 0 {
+        return Err(());
     }
 
-    Err(())
+    Ok(wanted.trailing_zeros() as u16 / 2)
 }
 
 /// Assuming we have 8 elements per slot. A wrapper over the out-state
@@ -164,4 +133,92 @@ mod utils_test {
         assert_eq!(enter(test2, false), Err(()));
         assert_eq!(enter(test2, true), Ok(1));
     }
+
+    #[test]
+    fn access_scan() {
+        // every position locked or in the wrong state, save for the last one
+        assert_eq!(enter(0b0110_1010_1010_1010, true), Ok(7));
+        assert_eq!(enter(0b0001_1110_1010_1010, false), Ok(7));
+        assert_eq!(enter(0, true), Err(()));
+        assert_eq!(enter(FULL_FLAG, false), Err(()));
+
+        assert_eq!(check_len(0), 0);
+        assert_eq!(check_len(FULL_FLAG), 8);
+        assert_eq!(check_len(0b0111_0001_1010_0100), 4);
+    }
+
+    /// The scan `enter` replaced, which walks the bitmap pair by pair.
+    fn walk_enter(src: u16, get: bool) -> Result<u16, ()> {
+        let mut base = if get {
+            if src == 0 {
+                return Err(());
+            }
+
+            src ^ GET_MASK
+        } else {
+            if src == FULL_FLAG {
+                return Err(());
+            }
+
+            src ^ PUT_MASK
+        };
+
+        let mut pos: u16 = {
+            let val = (base & PUT_MASK).trailing_zeros() as u16;
+            if val > 14 {
+                return Err(());
+            }
+
+            if val % 2 == 1 {
+                base >>= val + 1;
+                val.div_ceil(2)
+            } else {
+                base >>= val;
+                val / 2
+            }
+        };
+
+        while base > 0 {
+            if base & 0b11 == 0b11 {
+                return Ok(pos);
+            }
+
+            pos += 1;
+            base >>= 2;
+        }
+
+        Err(())
+    }
+
+    /// The count `check_len` replaced, same as `walk_enter`.
+    fn walk_len(src: u16) -> usize {
+        let mut base = src & FULL_FLAG;
+        let mut count = 0;
+
+        while base > 0 {
+            count += usize::from(base & 1 == 1);
+            base >>= 2;
+        }
+
+        count
+    }
+
+    #[test]
+    fn access_exhaustive() {
+        for src in 0..=u16::MAX {
+            assert_eq!(
+                enter(src, true),
+                walk_enter(src, true),
+                "get at {:016b}",
+                src
+            );
+            assert_eq!(
+                enter(src, false),
+                walk_enter(src, false),
+                "put at {:016b}",
+                src
+            );
+            assert_eq!(check_len(src), walk_len(src), "len of {:016b}", src);
+        }
+    }
 }